@@ -0,0 +1,273 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::State;
+use tokio_util::sync::CancellationToken;
+
+const ACLED_BASE_URL: &str = "https://api.acleddata.com/acled/read";
+const ACLED_OAUTH_API_URL: &str = "https://acleddata.com/api/acled/read";
+const ACLED_TOKEN_URL: &str = "https://acleddata.com/oauth/token";
+// Point the OAuth endpoints elsewhere, e.g. at a local mock server
+const API_URL_ENV: &str = "ARSON_ACLED_API_URL";
+const TOKEN_URL_ENV: &str = "ARSON_ACLED_TOKEN_URL";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Shared HTTP client so connections to the API are reused across queries,
+/// plus a cancellation token for each query still running.
+pub struct AcledClient {
+    http: reqwest::Client,
+    in_flight: Mutex<HashMap<u32, CancellationToken>>,
+}
+
+impl Default for AcledClient {
+    fn default() -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .expect("failed to build HTTP client");
+        Self {
+            http,
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+/// The raw HTTP outcome; the frontend parses the body the same way it does
+/// for requests made from the webview.
+#[derive(Serialize)]
+pub struct AcledHttpResponse {
+    status: u16,
+    status_text: String,
+    body: String,
+    retry_after: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum QueryAuth {
+    ApiKey { email: String, api_key: String },
+    Bearer { access_token: String },
+}
+
+fn endpoint(env: &str, default: &str) -> String {
+    std::env::var(env).unwrap_or_else(|_| default.to_string())
+}
+
+/// Runs one ACLED read query. Credentials travel in the command arguments and
+/// only ever appear in the native request. `request_id` lets the frontend
+/// cancel it with [`cancel_acled_query`].
+#[tauri::command]
+pub async fn acled_query(
+    client: State<'_, AcledClient>,
+    auth: QueryAuth,
+    query: Vec<(String, String)>,
+    request_id: u32,
+) -> Result<AcledHttpResponse, String> {
+    let client = client.inner();
+    let token = CancellationToken::new();
+    client
+        .in_flight
+        .lock()
+        .unwrap()
+        .insert(request_id, token.clone());
+
+    let result = token
+        .run_until_cancelled(send_query(&client.http, &auth, &query))
+        .await;
+    client.in_flight.lock().unwrap().remove(&request_id);
+
+    result.unwrap_or_else(|| Err("Request cancelled".to_string()))
+}
+
+/// Cancels a running [`acled_query`]; unknown or finished ids are ignored.
+#[tauri::command]
+pub fn cancel_acled_query(client: State<'_, AcledClient>, request_id: u32) {
+    if let Some(token) = client.inner().in_flight.lock().unwrap().remove(&request_id) {
+        token.cancel();
+    }
+}
+
+async fn send_query(
+    client: &reqwest::Client,
+    auth: &QueryAuth,
+    query: &[(String, String)],
+) -> Result<AcledHttpResponse, String> {
+    let request = match auth {
+        QueryAuth::ApiKey { email, api_key } => client
+            .get(ACLED_BASE_URL)
+            .query(&[("key", api_key.as_str()), ("email", email.as_str())]),
+        QueryAuth::Bearer { access_token } => client
+            .get(endpoint(API_URL_ENV, ACLED_OAUTH_API_URL))
+            .query(&[("_format", "json")])
+            .bearer_auth(access_token),
+    };
+
+    let response = request
+        .query(query)
+        .send()
+        .await
+        .map_err(|e| request_error("Request to ACLED failed", e))?;
+
+    into_http_response(response).await
+}
+
+/// Posts an OAuth token request (password or refresh grant) as a form.
+#[tauri::command]
+pub async fn acled_token(
+    client: State<'_, AcledClient>,
+    form: Vec<(String, String)>,
+) -> Result<AcledHttpResponse, String> {
+    send_token_request(&client.inner().http, &form).await
+}
+
+async fn send_token_request(
+    client: &reqwest::Client,
+    form: &[(String, String)],
+) -> Result<AcledHttpResponse, String> {
+    let response = client
+        .post(endpoint(TOKEN_URL_ENV, ACLED_TOKEN_URL))
+        .form(form)
+        .send()
+        .await
+        .map_err(|e| request_error("Token request failed", e))?;
+
+    into_http_response(response).await
+}
+
+fn request_error(context: &str, error: reqwest::Error) -> String {
+    if error.is_timeout() {
+        format!(
+            "{}: timed out after {}s",
+            context,
+            REQUEST_TIMEOUT.as_secs()
+        )
+    } else {
+        format!("{}: {}", context, error.without_url())
+    }
+}
+
+async fn into_http_response(response: reqwest::Response) -> Result<AcledHttpResponse, String> {
+    let status = response.status();
+    let retry_after = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let body = response
+        .text()
+        .await
+        .map_err(|e| request_error("Failed to read ACLED response", e))?;
+
+    Ok(AcledHttpResponse {
+        status: status.as_u16(),
+        status_text: status.canonical_reason().unwrap_or_default().to_string(),
+        body,
+        retry_after,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+    use std::thread;
+
+    /// Serves one canned response per connection and reports each request's
+    /// path and form body.
+    fn mock_token_server(
+        responses: Vec<(u16, &'static str)>,
+    ) -> (String, mpsc::Receiver<(String, String)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/oauth/token", listener.local_addr().unwrap());
+        let (requests, received) = mpsc::channel();
+
+        thread::spawn(move || {
+            for (status, body) in responses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut content_length = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = header.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                let mut form = vec![0; content_length];
+                reader.read_exact(&mut form).unwrap();
+                requests
+                    .send((request_line, String::from_utf8(form).unwrap()))
+                    .unwrap();
+
+                let response = format!(
+                    "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                reader.get_mut().write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        (url, received)
+    }
+
+    fn form(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn token_requests_go_to_the_configured_endpoint() {
+        let (url, received) = mock_token_server(vec![
+            (
+                200,
+                r#"{"access_token":"a1","refresh_token":"r1","expires_in":3600}"#,
+            ),
+            (401, r#"{"error":"invalid_grant"}"#),
+        ]);
+        std::env::set_var(TOKEN_URL_ENV, &url);
+        let client = AcledClient::default();
+
+        let password = tauri::async_runtime::block_on(send_token_request(
+            &client.http,
+            &form(&[
+                ("username", "analyst@example.org"),
+                ("password", "p&ss word"),
+                ("grant_type", "password"),
+            ]),
+        ))
+        .unwrap();
+        let (request_line, body) = received.recv().unwrap();
+        assert!(request_line.starts_with("POST /oauth/token "));
+        assert_eq!(
+            body,
+            "username=analyst%40example.org&password=p%26ss+word&grant_type=password"
+        );
+        assert_eq!(password.status, 200);
+        assert!(password.body.contains(r#""access_token":"a1""#));
+
+        let refresh = tauri::async_runtime::block_on(send_token_request(
+            &client.http,
+            &form(&[("grant_type", "refresh_token"), ("refresh_token", "r1")]),
+        ))
+        .unwrap();
+        let (_, body) = received.recv().unwrap();
+        assert_eq!(body, "grant_type=refresh_token&refresh_token=r1");
+        assert_eq!(refresh.status, 401);
+        assert_eq!(refresh.body, r#"{"error":"invalid_grant"}"#);
+    }
+}
@@ -0,0 +1,313 @@
+//! Serialized ACLED credentials in the OS secret store (Keychain, Credential Manager,
+//! Secret Service), with an AES-GCM encrypted file for machines that have no
+//! secret store, such as headless test runners.
+//!
+//! `ARSON_CREDENTIAL_STORE=file` forces the file store; otherwise it is only
+//! used when the secret store is unreachable. Either way its key is derived
+//! from `ARSON_CREDENTIAL_PASSPHRASE` with Argon2id and a random salt kept in
+//! the file, so nothing is stored unencrypted.
+
+use crate::logs;
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, OsRng};
+use aes_gcm::{AeadCore, Aes256Gcm, KeyInit, Nonce};
+use argon2::Argon2;
+use std::fs;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+const SERVICE: &str = "arson";
+const ACCOUNT: &str = "acled";
+const FILE_NAME: &str = "credentials.enc";
+const STORE_ENV: &str = "ARSON_CREDENTIAL_STORE";
+const PASSPHRASE_ENV: &str = "ARSON_CREDENTIAL_PASSPHRASE";
+// File layout: format version, salt, nonce, ciphertext
+const FILE_VERSION: u8 = 2;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const HEADER_LEN: usize = 1 + SALT_LEN + NONCE_LEN;
+
+enum StoreError {
+    /// The secret store itself could not be reached.
+    Unavailable(String),
+    Failed(String),
+}
+
+impl From<StoreError> for String {
+    fn from(error: StoreError) -> Self {
+        match error {
+            StoreError::Unavailable(message) | StoreError::Failed(message) => message,
+        }
+    }
+}
+
+impl From<keyring::Error> for StoreError {
+    fn from(error: keyring::Error) -> Self {
+        match error {
+            keyring::Error::PlatformFailure(_) | keyring::Error::NoStorageAccess(_) => {
+                StoreError::Unavailable(format!("Secret store unavailable: {}", error))
+            }
+            _ => StoreError::Failed(format!("Secret store error: {}", error)),
+        }
+    }
+}
+
+enum Store {
+    Keyring(keyring::Entry),
+    File { path: PathBuf, passphrase: String },
+}
+
+// Argon2id with its default cost (19 MiB, 2 passes), fresh for every salt
+fn file_cipher(passphrase: &str, salt: &[u8]) -> Result<Aes256Gcm, StoreError> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| StoreError::Failed(format!("Failed to derive the file key: {}", e)))?;
+    Ok(Aes256Gcm::new(&key.into()))
+}
+
+impl Store {
+    fn open(app: &AppHandle) -> Result<Self, String> {
+        if std::env::var(STORE_ENV).is_ok_and(|store| store == "file") {
+            return Self::file(app);
+        }
+
+        keyring::Entry::new(SERVICE, ACCOUNT)
+            .map(Store::Keyring)
+            .map_err(|e| String::from(StoreError::from(e)))
+    }
+
+    fn file(app: &AppHandle) -> Result<Self, String> {
+        let passphrase = std::env::var(PASSPHRASE_ENV)
+            .map_err(|_| format!("Set {} to use the encrypted file store", PASSPHRASE_ENV))?;
+        let path = app
+            .path()
+            .app_data_dir()
+            .map_err(|e| format!("No app data directory: {}", e))?
+            .join(FILE_NAME);
+
+        Ok(Store::File { path, passphrase })
+    }
+
+    fn save(&self, credentials: &str) -> Result<(), StoreError> {
+        match self {
+            Store::Keyring(entry) => Ok(entry.set_password(credentials)?),
+            Store::File { path, passphrase } => {
+                let mut salt = [0u8; SALT_LEN];
+                OsRng.fill_bytes(&mut salt);
+                let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+                let ciphertext = file_cipher(passphrase, &salt)?
+                    .encrypt(&nonce, credentials.as_bytes())
+                    .map_err(|_| StoreError::Failed("Failed to encrypt credentials".into()))?;
+
+                let mut contents = vec![FILE_VERSION];
+                contents.extend(salt);
+                contents.extend(nonce);
+                contents.extend(ciphertext);
+                if let Some(dir) = path.parent() {
+                    fs::create_dir_all(dir).map_err(|e| StoreError::Failed(e.to_string()))?;
+                }
+                fs::write(path, contents).map_err(|e| {
+                    StoreError::Failed(format!("Failed to write {}: {}", path.display(), e))
+                })
+            }
+        }
+    }
+
+    fn load(&self) -> Result<Option<String>, StoreError> {
+        match self {
+            Store::Keyring(entry) => match entry.get_password() {
+                Ok(credentials) => Ok(Some(credentials)),
+                Err(keyring::Error::NoEntry) => Ok(None),
+                Err(e) => Err(e.into()),
+            },
+            Store::File { path, passphrase } => {
+                if !path.exists() {
+                    return Ok(None);
+                }
+                let contents = fs::read(path).map_err(|e| {
+                    StoreError::Failed(format!("Failed to read {}: {}", path.display(), e))
+                })?;
+                // Files from before the salted format have no version byte
+                if contents.len() < HEADER_LEN || contents[0] != FILE_VERSION {
+                    return Err(StoreError::Failed(
+                        "Credentials file is corrupted or from an older version; sign in again to replace it".into(),
+                    ));
+                }
+                let (salt, rest) = contents[1..].split_at(SALT_LEN);
+                let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+                let plaintext = file_cipher(passphrase, salt)?
+                    .decrypt(Nonce::from_slice(nonce), ciphertext)
+                    .map_err(|_| {
+                        StoreError::Failed(
+                            "Failed to decrypt credentials (wrong passphrase?)".into(),
+                        )
+                    })?;
+                String::from_utf8(plaintext)
+                    .map(Some)
+                    .map_err(|e| StoreError::Failed(e.to_string()))
+            }
+        }
+    }
+
+    fn clear(&self) -> Result<(), StoreError> {
+        match self {
+            Store::Keyring(entry) => match entry.delete_credential() {
+                Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+                Err(e) => Err(e.into()),
+            },
+            Store::File { path, .. } => match fs::remove_file(path) {
+                Ok(()) => Ok(()),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+                Err(e) => Err(StoreError::Failed(e.to_string())),
+            },
+        }
+    }
+}
+
+/// Runs `op` against the configured store, retrying on the encrypted file when
+/// the secret store is unreachable and a passphrase is configured.
+fn with_store<T>(
+    app: &AppHandle,
+    op: impl Fn(&Store) -> Result<T, StoreError>,
+) -> Result<T, String> {
+    let store = Store::open(app)?;
+
+    match op(&store) {
+        Err(StoreError::Unavailable(reason))
+            if matches!(store, Store::Keyring(_)) && std::env::var(PASSPHRASE_ENV).is_ok() =>
+        {
+            logs::record(
+                app,
+                "warn",
+                "CREDENTIALS",
+                format!("{}; falling back to the encrypted file store", reason),
+            );
+            op(&Store::file(app)?).map_err(String::from)
+        }
+        result => result.map_err(String::from),
+    }
+}
+
+// Secret store calls block (D-Bus round trips, OS prompts), so keep them off
+// the async runtime's worker threads
+async fn run_blocking<T: Send + 'static>(
+    app: AppHandle,
+    op: impl Fn(&Store) -> Result<T, StoreError> + Send + 'static,
+) -> Result<T, String> {
+    tauri::async_runtime::spawn_blocking(move || with_store(&app, op))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn save_credentials(app: AppHandle, credentials: String) -> Result<(), String> {
+    run_blocking(app, move |store| store.save(&credentials)).await
+}
+
+#[tauri::command]
+pub async fn load_credentials(app: AppHandle) -> Result<Option<String>, String> {
+    run_blocking(app, |store| store.load()).await
+}
+
+#[tauri::command]
+pub async fn clear_credentials(app: AppHandle) -> Result<(), String> {
+    run_blocking(app, |store| store.clear()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CREDENTIALS: &str = r#"{"email":"analyst@example.org","api_key":"k3y"}"#;
+
+    fn file_store(test: &str, passphrase: &str) -> Store {
+        let dir = std::env::temp_dir().join(format!("arson-{}-{}", test, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        Store::File {
+            path: dir.join(FILE_NAME),
+            passphrase: passphrase.to_string(),
+        }
+    }
+
+    fn path(store: &Store) -> &PathBuf {
+        match store {
+            Store::File { path, .. } => path,
+            Store::Keyring(_) => unreachable!(),
+        }
+    }
+
+    fn error(result: Result<Option<String>, StoreError>) -> String {
+        match result {
+            Ok(loaded) => panic!("expected an error, loaded {:?}", loaded),
+            Err(e) => e.into(),
+        }
+    }
+
+    #[test]
+    fn file_store_round_trips() {
+        let store = file_store("round-trip", "correct horse");
+        assert!(matches!(store.load(), Ok(None)));
+
+        assert!(store.save(CREDENTIALS).is_ok());
+        assert!(matches!(store.load(), Ok(Some(loaded)) if loaded == CREDENTIALS));
+
+        assert!(store.clear().is_ok());
+        assert!(matches!(store.load(), Ok(None)));
+    }
+
+    #[test]
+    fn wrong_passphrase_fails_to_decrypt() {
+        let store = file_store("wrong-passphrase", "correct horse");
+        assert!(store.save(CREDENTIALS).is_ok());
+
+        let other = Store::File {
+            path: path(&store).clone(),
+            passphrase: "battery staple".to_string(),
+        };
+        assert_eq!(
+            error(other.load()),
+            "Failed to decrypt credentials (wrong passphrase?)"
+        );
+    }
+
+    #[test]
+    fn truncated_or_unversioned_file_is_corrupted() {
+        let store = file_store("corrupted", "correct horse");
+        assert!(store.save(CREDENTIALS).is_ok());
+        let contents = fs::read(path(&store)).unwrap();
+        let corrupted =
+            "Credentials file is corrupted or from an older version; sign in again to replace it";
+
+        fs::write(path(&store), &contents[..HEADER_LEN - 1]).unwrap();
+        assert_eq!(error(store.load()), corrupted);
+
+        let mut old_version = contents;
+        old_version[0] = FILE_VERSION - 1;
+        fs::write(path(&store), &old_version).unwrap();
+        assert_eq!(error(store.load()), corrupted);
+    }
+
+    #[test]
+    fn clearing_a_missing_file_succeeds() {
+        let store = file_store("clear-missing", "correct horse");
+        assert!(!path(&store).exists());
+        assert!(store.clear().is_ok());
+    }
+
+    #[test]
+    fn each_save_uses_a_fresh_salt_and_nonce() {
+        let store = file_store("fresh-salt", "correct horse");
+        assert!(store.save(CREDENTIALS).is_ok());
+        let first = fs::read(path(&store)).unwrap();
+        assert!(store.save(CREDENTIALS).is_ok());
+        let second = fs::read(path(&store)).unwrap();
+
+        let salt = 1..1 + SALT_LEN;
+        let nonce = 1 + SALT_LEN..HEADER_LEN;
+        assert_eq!(first[0], FILE_VERSION);
+        assert_ne!(first[salt.clone()], second[salt]);
+        assert_ne!(first[nonce.clone()], second[nonce]);
+        assert_ne!(first[HEADER_LEN..], second[HEADER_LEN..]);
+    }
+}
@@ -0,0 +1,90 @@
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+use tauri_plugin_dialog::DialogExt;
+use tokio::sync::oneshot;
+
+/// Prompts for a destination and writes `contents` there, plus `note` (if any)
+/// to a `<stem>.note.txt` file beside it. Resolves to the saved path, or
+/// `None` if the user cancelled the dialog.
+#[tauri::command]
+pub async fn save_export(
+    app: AppHandle,
+    file_name: String,
+    filter_name: String,
+    extension: String,
+    contents: String,
+    note: Option<String>,
+) -> Result<Option<String>, String> {
+    // The callback form keeps the dialog from blocking an async runtime thread
+    let (picked, chosen) = oneshot::channel();
+    app.dialog()
+        .file()
+        .set_file_name(&file_name)
+        .add_filter(&filter_name, &[extension.as_str()])
+        .save_file(move |file_path| {
+            let _ = picked.send(file_path);
+        });
+
+    let Some(file_path) = chosen.await.map_err(|e| e.to_string())? else {
+        return Ok(None);
+    };
+
+    let path = file_path.into_path().map_err(|e| e.to_string())?;
+    std::fs::write(&path, contents)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+
+    if let Some(note) = note {
+        let note_path = note_path(&path);
+        std::fs::write(&note_path, note)
+            .map_err(|e| format!("Failed to write {}: {}", note_path.display(), e))?;
+    }
+
+    Ok(Some(path.display().to_string()))
+}
+
+// The dialog only confirmed overwriting `path`, so the note never replaces an
+// existing file: `data.note.txt`, then `data.note-2.txt` and so on
+fn note_path(path: &Path) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    (1..)
+        .map(|n| {
+            let suffix = if n == 1 {
+                String::new()
+            } else {
+                format!("-{}", n)
+            };
+            path.with_file_name(format!("{}.note{}.txt", stem, suffix))
+        })
+        .find(|candidate| !candidate.exists())
+        .expect("ran out of note file names")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn note_path_never_replaces_existing_files() {
+        let dir = std::env::temp_dir().join(format!("arson-note-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let export = dir.join("events.csv");
+
+        // An unrelated `events.txt` is left alone
+        fs::write(dir.join("events.txt"), "mine").unwrap();
+        assert_eq!(note_path(&export), dir.join("events.note.txt"));
+
+        fs::write(dir.join("events.note.txt"), "").unwrap();
+        assert_eq!(note_path(&export), dir.join("events.note-2.txt"));
+        fs::write(dir.join("events.note-2.txt"), "").unwrap();
+        assert_eq!(note_path(&export), dir.join("events.note-3.txt"));
+
+        assert_eq!(note_path(&dir.join("map")), dir.join("map.note.txt"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
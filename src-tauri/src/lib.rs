@@ -1,11 +1,34 @@
+mod acled;
+mod credentials;
+mod export;
+mod logs;
+mod tiles;
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_dialog::init())
+        .manage(acled::AcledClient::default())
+        .manage(logs::LogFile::default())
+        .manage(tiles::OfflineTiles::default())
+        .register_asynchronous_uri_scheme_protocol(tiles::PROTOCOL, tiles::handle_request)
         .invoke_handler(tauri::generate_handler![
             minimize_window,
             maximize_window,
-            close_window
+            close_window,
+            acled::acled_query,
+            acled::cancel_acled_query,
+            acled::acled_token,
+            credentials::save_credentials,
+            credentials::load_credentials,
+            credentials::clear_credentials,
+            export::save_export,
+            logs::write_log,
+            logs::log_file_path,
+            tiles::choose_offline_tiles,
+            tiles::open_offline_tiles,
+            tiles::close_offline_tiles
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
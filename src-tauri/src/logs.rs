@@ -0,0 +1,148 @@
+//! Log entries from the frontend, appended to `arson.log` in the app's log
+//! directory. The file is rotated by size (`arson.log.1` .. `arson.log.3`) so it
+//! never grows without bound. Entries arrive already redacted.
+
+use serde::Deserialize;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+const FILE_NAME: &str = "arson.log";
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+const ROTATED_FILES: u32 = 3;
+
+#[derive(Deserialize)]
+pub struct LogRecord {
+    timestamp: String,
+    level: String,
+    context: String,
+    message: String,
+    #[serde(default)]
+    fields: Vec<(String, String)>,
+}
+
+impl LogRecord {
+    fn line(&self) -> String {
+        let mut line = format!("{} {:<5} ", self.timestamp, self.level.to_uppercase());
+        if !self.context.is_empty() {
+            line.push_str(&format!("[{}] ", self.context));
+        }
+        // One record per line, whatever the message contains
+        line.push_str(&self.message.replace('\n', "\\n"));
+        for (key, value) in &self.fields {
+            line.push_str(&format!(" {}={}", key, value.replace('\n', "\\n")));
+        }
+        line
+    }
+}
+
+// ISO 8601 UTC with milliseconds, matching the frontend's `Date.toISOString()`
+fn timestamp(since_epoch_ms: u64) -> String {
+    let (days, ms) = (since_epoch_ms / 86_400_000, since_epoch_ms % 86_400_000);
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1_000 % 60,
+        ms % 1_000
+    )
+}
+
+/// Serializes writes so that rotation never races an append.
+#[derive(Default)]
+pub struct LogFile(Mutex<()>);
+
+fn log_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_log_dir()
+        .map_err(|e| format!("No log directory: {}", e))?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    Ok(dir.join(FILE_NAME))
+}
+
+fn rotated(path: &Path, index: u32) -> PathBuf {
+    path.with_extension(format!("log.{}", index))
+}
+
+fn rotate(path: &Path) -> io::Result<()> {
+    for index in (1..ROTATED_FILES).rev() {
+        let from = rotated(path, index);
+        if from.exists() {
+            fs::rename(&from, rotated(path, index + 1))?;
+        }
+    }
+    fs::rename(path, rotated(path, 1))
+}
+
+fn append(path: &Path, records: &[LogRecord]) -> io::Result<()> {
+    if fs::metadata(path).is_ok_and(|metadata| metadata.len() >= MAX_FILE_BYTES) {
+        rotate(path)?;
+    }
+
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    for record in records {
+        writeln!(file, "{}", record.line())?;
+    }
+    Ok(())
+}
+
+/// Appends a message from the backend itself. Failures go to stderr, since
+/// there is nowhere else left to report them.
+pub fn record(app: &AppHandle, level: &str, context: &str, message: impl Into<String>) {
+    let since_epoch_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64);
+    let record = LogRecord {
+        timestamp: timestamp(since_epoch_ms),
+        level: level.to_string(),
+        context: context.to_string(),
+        message: message.into(),
+        fields: Vec::new(),
+    };
+
+    let written = log_path(app).and_then(|path| {
+        let log_file = app.state::<LogFile>();
+        let _guard = log_file.0.lock().unwrap();
+        append(&path, std::slice::from_ref(&record))
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    });
+    if let Err(e) = written {
+        eprintln!("{} ({})", record.line(), e);
+    }
+}
+
+#[tauri::command]
+pub async fn write_log(app: AppHandle, entries: Vec<LogRecord>) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = log_path(&app)?;
+        let log_file = app.state::<LogFile>();
+        let _guard = log_file.0.lock().unwrap();
+        append(&path, &entries).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub fn log_file_path(app: AppHandle) -> Result<String, String> {
+    log_path(&app).map(|path| path.display().to_string())
+}
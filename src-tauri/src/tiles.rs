@@ -0,0 +1,253 @@
+//! Offline basemap tiles, served to the webview over the `tiles://` protocol
+//! from either an MBTiles file or an XYZ directory (`{z}/{x}/{y}.png`), so the
+//! map keeps working without network access.
+
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::http::{header, Request, Response, StatusCode};
+use tauri::{AppHandle, Manager, Runtime, State, UriSchemeContext, UriSchemeResponder};
+use tauri_plugin_dialog::{DialogExt, FilePath};
+use tokio::sync::oneshot;
+
+pub const PROTOCOL: &str = "tiles";
+
+// Tried in order for each tile of a directory source
+const DIRECTORY_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "webp"];
+
+enum TileSource {
+    Mbtiles(Connection),
+    Directory(PathBuf),
+}
+
+/// The tile source currently being served, if any.
+#[derive(Default)]
+pub struct OfflineTiles(Mutex<Option<TileSource>>);
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TileSetInfo {
+    name: String,
+    format: Option<String>,
+    min_zoom: Option<u32>,
+    max_zoom: Option<u32>,
+}
+
+impl TileSource {
+    fn open(path: &Path) -> Result<(Self, TileSetInfo), String> {
+        if path.is_dir() {
+            Ok((
+                TileSource::Directory(path.to_path_buf()),
+                directory_info(path),
+            ))
+        } else {
+            let connection = Connection::open_with_flags(
+                path,
+                OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+            )
+            .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+            let info = mbtiles_info(&connection, path)?;
+            Ok((TileSource::Mbtiles(connection), info))
+        }
+    }
+
+    fn tile(&self, z: u32, x: u32, y: u32) -> Result<Option<Vec<u8>>, String> {
+        if z > 30 || x >= 1 << z || y >= 1 << z {
+            return Ok(None);
+        }
+
+        match self {
+            // MBTiles numbers rows from the south (TMS), XYZ from the north
+            TileSource::Mbtiles(connection) => connection
+                .query_row(
+                    "SELECT tile_data FROM tiles
+                     WHERE zoom_level = ?1 AND tile_column = ?2 AND tile_row = ?3",
+                    params![z, x, (1 << z) - 1 - y],
+                    |row| row.get(0),
+                )
+                .optional()
+                .map_err(|e| e.to_string()),
+            TileSource::Directory(root) => {
+                let column = root.join(z.to_string()).join(x.to_string());
+                for extension in DIRECTORY_EXTENSIONS {
+                    match fs::read(column.join(format!("{}.{}", y, extension))) {
+                        Ok(data) => return Ok(Some(data)),
+                        Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                        Err(e) => return Err(e.to_string()),
+                    }
+                }
+                Ok(None)
+            }
+        }
+    }
+}
+
+fn mbtiles_info(connection: &Connection, path: &Path) -> Result<TileSetInfo, String> {
+    let invalid = |e: rusqlite::Error| format!("{} is not an MBTiles file: {}", path.display(), e);
+
+    let metadata: HashMap<String, String> = connection
+        .prepare("SELECT name, value FROM metadata")
+        .and_then(|mut statement| {
+            statement
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect()
+        })
+        .map_err(invalid)?;
+    connection
+        .prepare("SELECT tile_data FROM tiles LIMIT 1")
+        .map_err(invalid)?;
+
+    let format = metadata.get("format").cloned();
+    if format.as_deref() == Some("pbf") {
+        return Err(format!(
+            "{} contains vector tiles; only raster (PNG, JPEG, WebP) MBTiles are supported",
+            path.display()
+        ));
+    }
+
+    Ok(TileSetInfo {
+        name: metadata
+            .get("name")
+            .cloned()
+            .unwrap_or_else(|| file_name(path)),
+        format,
+        min_zoom: metadata.get("minzoom").and_then(|zoom| zoom.parse().ok()),
+        max_zoom: metadata.get("maxzoom").and_then(|zoom| zoom.parse().ok()),
+    })
+}
+
+// Zoom levels are the numeric subdirectories
+fn directory_info(path: &Path) -> TileSetInfo {
+    let zooms: Vec<u32> = fs::read_dir(path)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().to_str()?.parse().ok())
+        .collect();
+
+    TileSetInfo {
+        name: file_name(path),
+        format: None,
+        min_zoom: zooms.iter().min().copied(),
+        max_zoom: zooms.iter().max().copied(),
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+/// Parses `/{z}/{x}/{y}` with an optional extension on `y`.
+fn parse_tile_path(path: &str) -> Option<(u32, u32, u32)> {
+    let mut parts = path.trim_matches('/').split('/');
+    let z = parts.next()?.parse().ok()?;
+    let x = parts.next()?.parse().ok()?;
+    let y = parts.next()?.split('.').next()?.parse().ok()?;
+    parts.next().is_none().then_some((z, x, y))
+}
+
+fn content_type(data: &[u8]) -> &'static str {
+    match data {
+        [0x89, b'P', b'N', b'G', ..] => "image/png",
+        [0xFF, 0xD8, ..] => "image/jpeg",
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => "image/webp",
+        _ => "application/octet-stream",
+    }
+}
+
+fn tile_response(tiles: &OfflineTiles, path: &str) -> Response<Vec<u8>> {
+    let response = Response::builder().header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*");
+
+    let Some((z, x, y)) = parse_tile_path(path) else {
+        return response
+            .status(StatusCode::BAD_REQUEST)
+            .body(Vec::new())
+            .unwrap();
+    };
+
+    let tile = match tiles.0.lock().unwrap().as_ref() {
+        Some(source) => source.tile(z, x, y),
+        None => Ok(None),
+    };
+
+    match tile {
+        // Revalidate so that switching sources never shows stale tiles
+        Ok(Some(data)) => response
+            .header(header::CONTENT_TYPE, content_type(&data))
+            .header(header::CACHE_CONTROL, "no-cache")
+            .body(data)
+            .unwrap(),
+        Ok(None) => response
+            .status(StatusCode::NOT_FOUND)
+            .body(Vec::new())
+            .unwrap(),
+        Err(e) => response
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .body(e.into_bytes())
+            .unwrap(),
+    }
+}
+
+/// Handler for the `tiles://` protocol; reads happen off the main thread.
+pub fn handle_request<R: Runtime>(
+    ctx: UriSchemeContext<'_, R>,
+    request: Request<Vec<u8>>,
+    responder: UriSchemeResponder,
+) {
+    let app = ctx.app_handle().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let tiles = app.state::<OfflineTiles>();
+        responder.respond(tile_response(&tiles, request.uri().path()));
+    });
+}
+
+/// Prompts for an MBTiles file, or a tile directory when `directory` is set.
+/// Resolves to `None` if the user cancelled the dialog.
+#[tauri::command]
+pub async fn choose_offline_tiles(
+    app: AppHandle,
+    directory: bool,
+) -> Result<Option<String>, String> {
+    // The callback form keeps the dialog from blocking an async runtime thread
+    let (send, picked) = oneshot::channel();
+    let on_pick = move |path: Option<FilePath>| {
+        let _ = send.send(path);
+    };
+    let dialog = app.dialog().file();
+    if directory {
+        dialog.pick_folder(on_pick);
+    } else {
+        dialog
+            .add_filter("MBTiles", &["mbtiles"])
+            .pick_file(on_pick);
+    }
+
+    let Some(picked) = picked.await.map_err(|e| e.to_string())? else {
+        return Ok(None);
+    };
+    let path = picked.into_path().map_err(|e| e.to_string())?;
+    Ok(Some(path.display().to_string()))
+}
+
+/// Starts serving tiles from `path`, replacing any previous source.
+#[tauri::command]
+pub async fn open_offline_tiles(
+    tiles: State<'_, OfflineTiles>,
+    path: String,
+) -> Result<TileSetInfo, String> {
+    let (source, info) = TileSource::open(Path::new(&path))?;
+    *tiles.0.lock().unwrap() = Some(source);
+    Ok(info)
+}
+
+#[tauri::command]
+pub fn close_offline_tiles(tiles: State<'_, OfflineTiles>) {
+    *tiles.0.lock().unwrap() = None;
+}
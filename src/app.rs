@@ -1,21 +1,122 @@
-use crate::components::Titlebar;
-use crate::services::StorageService;
+use crate::components::{report_error, NotificationContext, NotificationProvider, Titlebar};
+use crate::errors::AppError;
+use crate::logging::console;
+use crate::services::{StorageService, TauriService, TileService};
 use crate::states::{dashboard::Dashboard, login::Login};
-use crate::types::{AppState, DashboardView, Theme};
+use crate::types::{
+    AppState, DashboardView, PreferencesAction, ProfileStore, Theme, UserPreferences,
+};
+use std::rc::Rc;
+use wasm_bindgen_futures::spawn_local;
 use yew::prelude::*;
 
+impl Reducible for UserPreferences {
+    type Action = PreferencesAction;
+
+    fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
+        let mut next = (*self).clone();
+        next.apply(action);
+        if next == *self {
+            self
+        } else {
+            Rc::new(next)
+        }
+    }
+}
+
+/// Gives the whole app, including startup, somewhere to report errors.
+#[function_component(Root)]
+pub fn root() -> Html {
+    html! {
+        <NotificationProvider>
+            <App />
+        </NotificationProvider>
+    }
+}
+
 #[function_component(App)]
 pub fn app() -> Html {
+    let notifications = use_context::<NotificationContext>();
     let app_state = use_state(|| AppState::Login);
-    let dashboard_view = use_state(DashboardView::default);
-    let current_theme = use_state(Theme::default);
+    let preferences_error = use_mut_ref(|| Option::<AppError>::None);
+    let preferences = {
+        let preferences_error = preferences_error.clone();
+        use_reducer(move || {
+            StorageService::load_preferences().unwrap_or_else(|e| {
+                console::error_with_context!("APP", "Failed to load preferences: {}", e);
+                *preferences_error.borrow_mut() = Some(e);
+                UserPreferences::default()
+            })
+        })
+    };
+    let dashboard_view = use_state(|| preferences.default_view.clone());
+    let profiles = use_state(ProfileStore::default);
 
-    // Try to load saved credentials on app startup
+    // Load saved profiles on app startup and open the default one
     {
         let app_state = app_state.clone();
+        let profiles = profiles.clone();
+        let notifications = notifications.clone();
         use_effect_with((), move |_| {
-            if let Ok(Some(user_data)) = StorageService::load_user_data() {
-                app_state.set(AppState::Dashboard(user_data));
+            if let (Some(notifications), Some(e)) = (
+                notifications.as_ref(),
+                preferences_error.borrow_mut().take(),
+            ) {
+                report_error(notifications, "Loading preferences", e);
+            }
+
+            spawn_local(async move {
+                match StorageService::load_profiles().await {
+                    Ok(store) => {
+                        if let Some(profile) = store.default_profile() {
+                            app_state.set(AppState::Dashboard(profile.clone()));
+                        }
+                        profiles.set(store);
+                    }
+                    Err(e) => {
+                        console::error_with_context!("APP", "Failed to load credentials: {}", e);
+                        if let Some(notifications) = notifications.as_ref() {
+                            report_error(notifications, "Loading saved profiles", e);
+                        }
+                    }
+                }
+            });
+            || {}
+        });
+    }
+
+    // Resume serving the offline basemap chosen in an earlier session
+    {
+        let offline_tiles = preferences.offline_tiles.clone();
+        let notifications = notifications.clone();
+        use_effect_with((), move |_| {
+            if let (Some(path), true) = (offline_tiles, TauriService::is_available()) {
+                spawn_local(async move {
+                    if let Err(e) = TileService::open(&path).await {
+                        console::error_with_context!("APP", "Failed to open offline tiles: {}", e);
+                        if let Some(notifications) = notifications.as_ref() {
+                            report_error(notifications, "Opening offline tiles", e);
+                        }
+                    }
+                });
+            }
+            || {}
+        });
+    }
+
+    // Persist every change; the initial value was just loaded
+    {
+        let saved = use_mut_ref(|| (*preferences).clone());
+        let notifications = notifications.clone();
+        use_effect_with((*preferences).clone(), move |preferences| {
+            if *saved.borrow() != *preferences {
+                *saved.borrow_mut() = preferences.clone();
+                if let Err(e) = StorageService::save_preferences(preferences) {
+                    console::error_with_context!("APP", "Failed to save preferences: {}", e);
+                    if let Some(notifications) = notifications.as_ref() {
+                        report_error(notifications, "Saving preferences", e);
+                    }
+                }
             }
             || {}
         });
@@ -23,8 +124,7 @@ pub fn app() -> Html {
 
     // Apply theme to body class
     {
-        let current_theme = current_theme.clone();
-        use_effect_with((*current_theme).clone(), move |theme| {
+        use_effect_with(preferences.theme.clone(), move |theme| {
             let window = web_sys::window().unwrap();
             let document = window.document().unwrap();
             let body = document.body().unwrap();
@@ -32,16 +132,16 @@ pub fn app() -> Html {
             // Set theme class
             match theme {
                 Theme::Light => {
-                    let _ = body.set_class_name("theme-light");
+                    body.set_class_name("theme-light");
                 }
                 Theme::Blue => {
-                    let _ = body.set_class_name("theme-blue");
+                    body.set_class_name("theme-blue");
                 }
                 Theme::Dark => {
-                    let _ = body.set_class_name("theme-dark");
+                    body.set_class_name("theme-dark");
                 }
                 Theme::Terminal => {
-                    let _ = body.set_class_name("theme-terminal");
+                    body.set_class_name("theme-terminal");
                 }
             }
 
@@ -56,6 +156,13 @@ pub fn app() -> Html {
         })
     };
 
+    let on_profiles_change = {
+        let profiles = profiles.clone();
+        Callback::from(move |store: ProfileStore| {
+            profiles.set(store);
+        })
+    };
+
     let on_view_change = {
         let dashboard_view = dashboard_view.clone();
         Callback::from(move |new_view: DashboardView| {
@@ -63,10 +170,10 @@ pub fn app() -> Html {
         })
     };
 
-    let on_theme_change = {
-        let current_theme = current_theme.clone();
-        Callback::from(move |new_theme: Theme| {
-            current_theme.set(new_theme);
+    let on_preferences_change = {
+        let preferences = preferences.clone();
+        Callback::from(move |action: PreferencesAction| {
+            preferences.dispatch(action);
         })
     };
 
@@ -75,21 +182,31 @@ pub fn app() -> Html {
             <Titlebar
                 app_state={(*app_state).clone()}
                 dashboard_view={(*dashboard_view).clone()}
-                current_theme={(*current_theme).clone()}
+                preferences={(*preferences).clone()}
+                profiles={(*profiles).clone()}
                 on_state_change={on_state_change.clone()}
+                on_profiles_change={on_profiles_change.clone()}
                 on_view_change={on_view_change.clone()}
-                on_theme_change={on_theme_change.clone()}
+                on_preferences_change={on_preferences_change.clone()}
             />
             <main class="container">
                 {match (*app_state).clone() {
                     AppState::Login => html! {
-                        <Login on_state_change={on_state_change} />
+                        <Login
+                            profiles={(*profiles).clone()}
+                            on_state_change={on_state_change}
+                            on_profiles_change={on_profiles_change}
+                        />
                     },
-                    AppState::Dashboard(user_data) => html! {
+                    // Keyed so that switching profiles starts a fresh dashboard
+                    AppState::Dashboard(profile) => html! {
                         <Dashboard
-                            user_data={user_data}
+                            key={profile.name.clone()}
+                            profile={profile.clone()}
                             current_view={(*dashboard_view).clone()}
+                            preferences={(*preferences).clone()}
                             on_state_change={on_state_change}
+                            on_preferences_change={on_preferences_change}
                         />
                     }
                 }}
@@ -0,0 +1,83 @@
+use wasm_bindgen::JsCast;
+use web_sys::{HtmlSelectElement, MouseEvent};
+use yew::prelude::*;
+
+#[derive(Properties, PartialEq)]
+pub struct ChipPickerProps {
+    pub id: AttrValue,
+    pub options: &'static [&'static str],
+    pub selected: Vec<String>,
+    pub on_change: Callback<Vec<String>>,
+    #[prop_or(AttrValue::from("Add..."))]
+    pub placeholder: AttrValue,
+    /// Shown in place of the chips when nothing is selected.
+    #[prop_or_default]
+    pub empty_label: Option<AttrValue>,
+}
+
+#[function_component(ChipPicker)]
+pub fn chip_picker(props: &ChipPickerProps) -> Html {
+    let on_add = {
+        let selected = props.selected.clone();
+        let on_change = props.on_change.clone();
+        Callback::from(move |e: Event| {
+            let target = e.target().unwrap();
+            let select = target.unchecked_into::<HtmlSelectElement>();
+            let value = select.value();
+            select.set_value("");
+
+            if !value.is_empty() && !selected.contains(&value) {
+                let mut new_selected = selected.clone();
+                new_selected.push(value);
+                on_change.emit(new_selected);
+            }
+        })
+    };
+
+    let remove_chip = |value: String| {
+        let selected = props.selected.clone();
+        let on_change = props.on_change.clone();
+        Callback::from(move |_: MouseEvent| {
+            let new_selected = selected
+                .iter()
+                .filter(|item| **item != value)
+                .cloned()
+                .collect();
+            on_change.emit(new_selected);
+        })
+    };
+
+    html! {
+        <div class="chip-picker">
+            <div class="chip-list">
+                {for props.selected.iter().map(|value| {
+                    html! {
+                        <span class="chip" key={value.clone()}>
+                            {value}
+                            <button
+                                class="chip-remove"
+                                title="Remove"
+                                onclick={remove_chip(value.clone())}
+                            >
+                                {"×"}
+                            </button>
+                        </span>
+                    }
+                })}
+                if props.selected.is_empty() {
+                    if let Some(empty_label) = &props.empty_label {
+                        <span class="chip-empty">{empty_label}</span>
+                    }
+                }
+            </div>
+            <select id={props.id.clone()} onchange={on_add}>
+                <option value="" selected=true>{&props.placeholder}</option>
+                {for props.options.iter()
+                    .filter(|&&option| !props.selected.iter().any(|value| value == option))
+                    .map(|&option| html! {
+                        <option value={option}>{option}</option>
+                    })}
+            </select>
+        </div>
+    }
+}
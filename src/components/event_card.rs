@@ -1,4 +1,5 @@
 use crate::components::AcledEvent;
+use crate::utils::text::{escape_html, match_ranges};
 use yew::prelude::*;
 
 #[derive(Properties, PartialEq)]
@@ -6,42 +7,126 @@ pub struct EventCardProps {
     pub event: AcledEvent,
     #[prop_or(false)]
     pub compact: bool,
+    /// Search tokens to highlight in the notes, actors and location.
+    #[prop_or_default]
+    pub highlight: Vec<String>,
+}
+
+// Truncate on a char boundary; notes routinely contain non-ASCII text
+fn truncate_notes(notes: &str, max_chars: usize) -> String {
+    match notes.char_indices().nth(max_chars) {
+        Some((index, _)) => format!("{}...", &notes[..index]),
+        None => notes.to_string(),
+    }
+}
+
+fn with_associates(actor: &str, associates: &str) -> String {
+    if associates.is_empty() {
+        actor.to_string()
+    } else {
+        format!("{} (with {})", actor, associates)
+    }
+}
+
+// Wraps search matches in <mark>
+fn highlighted(text: &str, tokens: &[String]) -> Html {
+    if tokens.is_empty() {
+        return html! { {text} };
+    }
+
+    let mut parts = Vec::new();
+    let mut cursor = 0;
+    for range in match_ranges(text, tokens) {
+        parts.push(html! { {&text[cursor..range.start]} });
+        parts.push(html! { <mark class="search-match">{&text[range.clone()]}</mark> });
+        cursor = range.end;
+    }
+    parts.push(html! { {&text[cursor..]} });
+    parts.into_iter().collect()
 }
 
 #[function_component(EventCard)]
 pub fn event_card(props: &EventCardProps) -> Html {
-    let notes = if props.compact && props.event.notes.len() > 100 {
-        format!("{}...", &props.event.notes[..100])
+    let event = &props.event;
+    let notes = if props.compact {
+        truncate_notes(&event.notes, 100)
     } else {
-        props.event.notes.clone()
+        event.notes.clone()
     };
+    let highlight = &props.highlight;
 
     html! {
         <div class="event-card">
             <div class="event-header">
-                <span class="event-type">{&props.event.event_type}</span>
-                <span class="event-date">{&props.event.event_date}</span>
+                <span class="event-type">
+                    {&event.event_type}
+                    if !event.sub_event_type.is_empty() {
+                        <span class="event-subtype">{format!(" / {}", event.sub_event_type)}</span>
+                    }
+                </span>
+                <span class="event-date">{&event.event_date}</span>
             </div>
             <div class="event-location">
-                <strong>{"Location: "}</strong>{&props.event.location}
+                <strong>{"Location: "}</strong>{highlighted(&event.full_location(), highlight)}
             </div>
             <div class="event-actors">
-                <div><strong>{"Actor 1: "}</strong>{&props.event.actor1}</div>
-                <div><strong>{"Actor 2: "}</strong>{&props.event.actor2}</div>
+                <div><strong>{"Actor 1: "}</strong>{highlighted(&with_associates(&event.actor1, &event.assoc_actor_1), highlight)}</div>
+                <div><strong>{"Actor 2: "}</strong>{highlighted(&with_associates(&event.actor2, &event.assoc_actor_2), highlight)}</div>
+                if !event.interaction.is_empty() {
+                    <div><strong>{"Interaction: "}</strong>{&event.interaction}</div>
+                }
             </div>
-            if let Some(fatalities) = props.event.fatalities {
+            if let Some(fatalities) = event.fatalities {
                 <div class="event-fatalities">
                     <strong>{"Fatalities: "}</strong>{fatalities}
                 </div>
             }
+            if event.targets_civilians() {
+                <div class="event-civilian-targeting">{&event.civilian_targeting}</div>
+            }
             <div class="event-notes">
-                <strong>{"Notes: "}</strong>{notes}
+                <strong>{"Notes: "}</strong>{highlighted(&notes, highlight)}
             </div>
+            if !props.compact {
+                <div class="event-details">
+                    if !event.disorder_type.is_empty() {
+                        <div><strong>{"Disorder Type: "}</strong>{&event.disorder_type}</div>
+                    }
+                    if !event.region.is_empty() {
+                        <div><strong>{"Region: "}</strong>{&event.region}</div>
+                    }
+                    if let Some(precision) = event.geo_precision_label() {
+                        <div><strong>{"Geo Precision: "}</strong>{precision}</div>
+                    }
+                    if let Some(precision) = event.time_precision_label() {
+                        <div><strong>{"Time Precision: "}</strong>{precision}</div>
+                    }
+                    if !event.source.is_empty() {
+                        <div>
+                            <strong>{"Source: "}</strong>{&event.source}
+                            if !event.source_scale.is_empty() {
+                                {format!(" ({})", event.source_scale)}
+                            }
+                        </div>
+                    }
+                    if !event.event_id_cnty.is_empty() {
+                        <div><strong>{"Event ID: "}</strong>{&event.event_id_cnty}</div>
+                    }
+                </div>
+                if !event.tags.is_empty() {
+                    <div class="event-tags">
+                        {for event.tag_list().into_iter().map(|tag| html! {
+                            <span class="event-tag">{tag}</span>
+                        })}
+                    </div>
+                }
+            }
         </div>
     }
 }
 
-// Function to generate HTML popup content for Leaflet markers
+// Function to generate HTML popup content for Leaflet markers. Every field is
+// API-supplied text, so all of it is escaped.
 pub fn generate_popup_content(event: &AcledEvent) -> String {
     let fatalities_html = event.fatalities.map_or(String::new(), |f| {
         format!(
@@ -50,28 +135,154 @@ pub fn generate_popup_content(event: &AcledEvent) -> String {
         )
     });
 
-    let notes = if event.notes.len() > 100 {
-        format!("{}...", &event.notes[..100])
+    let sub_event_html = if event.sub_event_type.is_empty() {
+        String::new()
     } else {
-        event.notes.clone()
+        format!(
+            "<p style='margin: 4px 0;'><strong>Sub-type:</strong> {}</p>",
+            escape_html(&event.sub_event_type)
+        )
+    };
+
+    let civilian_html = if event.targets_civilians() {
+        format!(
+            "<p style='margin: 4px 0; color: #ffaa00;'>{}</p>",
+            escape_html(&event.civilian_targeting)
+        )
+    } else {
+        String::new()
     };
 
+    let precision_html = event.geo_precision_label().map_or(String::new(), |label| {
+        format!(
+            "<p style='margin: 4px 0; font-size: 0.85em;'><strong>Precision:</strong> {}</p>",
+            label
+        )
+    });
+
+    let source_html = if event.source.is_empty() {
+        String::new()
+    } else {
+        format!(
+            "<p style='margin: 4px 0; font-size: 0.85em;'><strong>Source:</strong> {}</p>",
+            escape_html(&event.source)
+        )
+    };
+
+    let notes = truncate_notes(&event.notes, 100);
+
     format!(
         "<div style='color: #00ff00; background: #0a0a0a; font-family: \"Pixelify Sans\";'>
             <h4 style='margin: 0 0 8px 0; color: #00ff00;'>{}</h4>
+            {}
             <p style='margin: 4px 0;'><strong>Date:</strong> {}</p>
             <p style='margin: 4px 0;'><strong>Location:</strong> {}</p>
             <p style='margin: 4px 0;'><strong>Actor 1:</strong> {}</p>
             <p style='margin: 4px 0;'><strong>Actor 2:</strong> {}</p>
             {}
+            {}
             <p style='margin: 4px 0; font-style: italic; font-size: 0.9em;'>{}</p>
+            {}
+            {}
         </div>",
-        event.event_type,
-        event.event_date,
-        event.location,
-        event.actor1,
-        event.actor2,
+        escape_html(&event.event_type),
+        sub_event_html,
+        escape_html(&event.event_date),
+        escape_html(&event.full_location()),
+        escape_html(&with_associates(&event.actor1, &event.assoc_actor_1)),
+        escape_html(&with_associates(&event.actor2, &event.assoc_actor_2)),
         fatalities_html,
-        notes
+        civilian_html,
+        escape_html(&notes),
+        precision_html,
+        source_html
+    )
+}
+
+// Popup for a cluster whose events all share one coordinate
+pub fn generate_cluster_popup_content(events: &[&AcledEvent]) -> String {
+    const MAX_LISTED: usize = 20;
+
+    let location = events
+        .first()
+        .map(|event| escape_html(&event.full_location()))
+        .unwrap_or_default();
+
+    let rows: String = events
+        .iter()
+        .take(MAX_LISTED)
+        .map(|event| {
+            format!(
+                "<p style='margin: 4px 0;'><strong>{}</strong> {} ({} fatalities)</p>",
+                escape_html(&event.event_date),
+                escape_html(&event.event_type),
+                event.fatality_count()
+            )
+        })
+        .collect();
+
+    let more = if events.len() > MAX_LISTED {
+        format!(
+            "<p style='margin: 4px 0; font-style: italic;'>...and {} more</p>",
+            events.len() - MAX_LISTED
+        )
+    } else {
+        String::new()
+    };
+
+    format!(
+        "<div style='color: #00ff00; background: #0a0a0a; font-family: \"Pixelify Sans\"; max-height: 240px; overflow-y: auto;'>
+            <h4 style='margin: 0 0 8px 0; color: #00ff00;'>{} events at {}</h4>
+            {}
+            {}
+        </div>",
+        events.len(),
+        location,
+        rows,
+        more
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn popup_escapes_api_text() {
+        let event = AcledEvent {
+            event_type: "Riots".to_string(),
+            actor1: "<b>Rioters</b>".to_string(),
+            assoc_actor_1: "Students & \"Youth\"".to_string(),
+            location: "Kano".to_string(),
+            source: "<img src=x onerror=alert(1)>".to_string(),
+            notes: "<script>alert('notes')</script>".to_string(),
+            ..Default::default()
+        };
+        let popup = generate_popup_content(&event);
+
+        assert!(!popup.contains("<script>"));
+        assert!(!popup.contains("<img"));
+        assert!(!popup.contains("<b>"));
+        assert!(popup.contains("&lt;script&gt;alert(&#39;notes&#39;)&lt;/script&gt;"));
+        assert!(popup.contains("&lt;img src=x onerror=alert(1)&gt;"));
+        assert!(
+            popup.contains("&lt;b&gt;Rioters&lt;/b&gt; (with Students &amp; &quot;Youth&quot;)")
+        );
+    }
+
+    #[test]
+    fn cluster_popup_escapes_api_text() {
+        let event = AcledEvent {
+            event_date: "2024-01-01<script>".to_string(),
+            event_type: "<img src=x onerror=alert(1)>".to_string(),
+            location: "Kano</h4><script>alert(2)</script>".to_string(),
+            ..Default::default()
+        };
+        let popup = generate_cluster_popup_content(&[&event, &event]);
+
+        assert!(!popup.contains("<script>"));
+        assert!(!popup.contains("<img"));
+        assert!(popup.contains("2 events at Kano&lt;/h4&gt;&lt;script&gt;"));
+        assert!(popup.contains("&lt;img src=x onerror=alert(1)&gt;"));
+    }
+}
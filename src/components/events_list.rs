@@ -8,6 +8,8 @@ pub struct EventsListProps {
     pub compact: bool,
     #[prop_or("Events".to_string())]
     pub title: String,
+    #[prop_or_default]
+    pub highlight: Vec<String>,
 }
 
 #[function_component(EventsList)]
@@ -25,7 +27,11 @@ pub fn events_list(props: &EventsListProps) -> Html {
             <h4>{&props.title}</h4>
             {props.events.iter().map(|event| {
                 html! {
-                    <EventCard event={event.clone()} compact={props.compact} />
+                    <EventCard
+                        event={event.clone()}
+                        compact={props.compact}
+                        highlight={props.highlight.clone()}
+                    />
                 }
             }).collect::<Html>()}
         </div>
@@ -0,0 +1,458 @@
+use crate::components::{AcledEvent, ExportMenu};
+use crate::types::{AcledParams, EventField, FieldKind};
+use crate::utils::date::{format_day, parse_day};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use web_sys::{Element, HtmlInputElement, MouseEvent};
+use yew::prelude::*;
+
+const ROW_HEIGHT_PX: f64 = 32.0;
+const OVERSCAN_ROWS: usize = 10;
+const DEFAULT_VIEWPORT_PX: f64 = 600.0;
+
+const DEFAULT_COLUMNS: &[EventField] = &[
+    EventField::EventDate,
+    EventField::EventType,
+    EventField::SubEventType,
+    EventField::Actor1,
+    EventField::Actor2,
+    EventField::Country,
+    EventField::Admin1,
+    EventField::Location,
+    EventField::Fatalities,
+    EventField::Notes,
+];
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum SortDirection {
+    Ascending,
+    Descending,
+}
+
+/// Per-column filter; which variant applies follows the column's [`FieldKind`].
+/// Number bounds keep the text as typed so partial input like `-` or `1.`
+/// survives re-rendering; it is parsed when filtering.
+#[derive(Clone, PartialEq, Debug, Default)]
+struct ColumnFilter {
+    contains: String,
+    min: String,
+    max: String,
+    from_day: Option<i64>,
+    to_day: Option<i64>,
+}
+
+impl ColumnFilter {
+    fn is_empty(&self) -> bool {
+        self.contains.is_empty()
+            && self.min.is_empty()
+            && self.max.is_empty()
+            && self.from_day.is_none()
+            && self.to_day.is_none()
+    }
+
+    fn matches(&self, field: EventField, event: &AcledEvent) -> bool {
+        match field.kind() {
+            FieldKind::Text => {
+                self.contains.is_empty()
+                    || field
+                        .text(event)
+                        .to_lowercase()
+                        .contains(&self.contains.to_lowercase())
+            }
+            FieldKind::Number => {
+                let (min, max) = (parse_bound(&self.min), parse_bound(&self.max));
+                match field.number(event) {
+                    Some(value) => {
+                        min.is_none_or(|min| value >= min) && max.is_none_or(|max| value <= max)
+                    }
+                    None => min.is_none() && max.is_none(),
+                }
+            }
+            FieldKind::Date => match parse_day(&event.event_date) {
+                Some(day) => {
+                    self.from_day.is_none_or(|from| day >= from)
+                        && self.to_day.is_none_or(|to| day <= to)
+                }
+                None => self.from_day.is_none() && self.to_day.is_none(),
+            },
+        }
+    }
+}
+
+// Bounds that don't parse yet (`-`, `1e`) don't filter
+fn parse_bound(text: &str) -> Option<f64> {
+    text.trim().parse().ok()
+}
+
+fn compare(field: EventField, a: &AcledEvent, b: &AcledEvent) -> Ordering {
+    match field.kind() {
+        // Events without a value sort after those with one
+        FieldKind::Number => match (field.number(a), field.number(b)) {
+            (Some(a), Some(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        },
+        FieldKind::Date => a.event_date.cmp(&b.event_date),
+        FieldKind::Text => field
+            .text(a)
+            .to_lowercase()
+            .cmp(&field.text(b).to_lowercase()),
+    }
+}
+
+fn column_class(field: EventField) -> &'static str {
+    match field {
+        EventField::Notes => "col-wide",
+        _ if field.kind() == FieldKind::Number => "col-number",
+        _ => "col-text",
+    }
+}
+
+#[derive(Properties, PartialEq)]
+struct VirtualRowsProps {
+    events: Rc<Vec<AcledEvent>>,
+    /// Indices into `events`, in display order.
+    rows: Rc<Vec<usize>>,
+    columns: Vec<EventField>,
+    /// Header rows, kept inside the scroll container so they can stick.
+    children: Html,
+}
+
+/// Scrollable table body that only renders the rows intersecting the viewport
+/// (plus overscan); spacer rows keep the scrollbar proportional to the full
+/// result set. Scrolling re-renders this component alone, not the toolbar.
+#[function_component(VirtualRows)]
+fn virtual_rows(props: &VirtualRowsProps) -> Html {
+    let scroll_top = use_state(|| 0.0_f64);
+    let viewport_height = use_state(|| DEFAULT_VIEWPORT_PX);
+    let body_ref = use_node_ref();
+
+    {
+        let body_ref = body_ref.clone();
+        let viewport_height = viewport_height.clone();
+        use_effect_with((), move |_| {
+            if let Some(body) = body_ref.cast::<Element>() {
+                viewport_height.set(f64::from(body.client_height()).max(ROW_HEIGHT_PX));
+            }
+            || ()
+        });
+    }
+
+    let on_scroll = {
+        let scroll_top = scroll_top.clone();
+        let viewport_height = viewport_height.clone();
+        Callback::from(move |e: Event| {
+            let body: Element = e.target_unchecked_into();
+            scroll_top.set(f64::from(body.scroll_top()));
+            viewport_height.set(f64::from(body.client_height()).max(ROW_HEIGHT_PX));
+        })
+    };
+
+    let total_rows = props.rows.len();
+    let first_row = ((*scroll_top / ROW_HEIGHT_PX) as usize)
+        .saturating_sub(OVERSCAN_ROWS)
+        .min(total_rows);
+    let visible_rows = (*viewport_height / ROW_HEIGHT_PX).ceil() as usize + 2 * OVERSCAN_ROWS;
+    let last_row = (first_row + visible_rows).min(total_rows);
+    let top_spacer = first_row as f64 * ROW_HEIGHT_PX;
+    let bottom_spacer = (total_rows - last_row) as f64 * ROW_HEIGHT_PX;
+
+    html! {
+        <div class="table-body" ref={body_ref} onscroll={on_scroll}>
+            <table>
+                {props.children.clone()}
+                <tbody>
+                    if top_spacer > 0.0 {
+                        <tr style={format!("height: {}px", top_spacer)}></tr>
+                    }
+                    {for props.rows[first_row..last_row].iter().map(|&index| {
+                        let event = &props.events[index];
+                        html! {
+                            <tr key={index} style={format!("height: {}px", ROW_HEIGHT_PX)}>
+                                {for props.columns.iter().map(|&field| {
+                                    let text = field.text(event);
+                                    html! {
+                                        <td class={column_class(field)} title={text.clone()}>{text}</td>
+                                    }
+                                })}
+                            </tr>
+                        }
+                    })}
+                    if bottom_spacer > 0.0 {
+                        <tr style={format!("height: {}px", bottom_spacer)}></tr>
+                    }
+                </tbody>
+            </table>
+            if total_rows == 0 {
+                <div class="no-data">{"No events match the current filters."}</div>
+            }
+        </div>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct EventsTableProps {
+    /// Loaded events matching `search` and `selection`.
+    pub events: Vec<AcledEvent>,
+    #[prop_or_default]
+    pub params: Option<AcledParams>,
+    /// Number of loaded events before the search and selection were applied.
+    #[prop_or_default]
+    pub total_events: Option<usize>,
+    #[prop_or_default]
+    pub search: String,
+    #[prop_or_default]
+    pub on_clear_search: Callback<()>,
+    /// Description of the area drawn on the map, if events are limited to one.
+    #[prop_or_default]
+    pub selection: Option<String>,
+    #[prop_or_default]
+    pub on_clear_selection: Callback<()>,
+}
+
+#[function_component(EventsTable)]
+pub fn events_table(props: &EventsTableProps) -> Html {
+    let columns = use_state(|| DEFAULT_COLUMNS.to_vec());
+    let sort = use_state(|| Option::<(EventField, SortDirection)>::None);
+    let filters = use_state(BTreeMap::<EventField, ColumnFilter>::new);
+    let show_column_picker = use_state(|| false);
+
+    // Indices of matching events in display order
+    let rows = use_memo(
+        (props.events.clone(), (*filters).clone(), *sort),
+        |(events, filters, sort)| {
+            let mut rows: Vec<usize> = (0..events.len())
+                .filter(|&index| {
+                    filters
+                        .iter()
+                        .all(|(field, filter)| filter.matches(*field, &events[index]))
+                })
+                .collect();
+
+            if let Some((field, direction)) = sort {
+                rows.sort_by(|&a, &b| {
+                    let ordering = compare(*field, &events[a], &events[b]);
+                    match direction {
+                        SortDirection::Ascending => ordering,
+                        SortDirection::Descending => ordering.reverse(),
+                    }
+                });
+            }
+
+            rows
+        },
+    );
+
+    let events = use_memo(props.events.clone(), |events| events.clone());
+
+    let on_sort = |field: EventField| {
+        let sort = sort.clone();
+        Callback::from(move |_: MouseEvent| {
+            // Cycle ascending -> descending -> unsorted
+            sort.set(match *sort {
+                Some((current, SortDirection::Ascending)) if current == field => {
+                    Some((field, SortDirection::Descending))
+                }
+                Some((current, SortDirection::Descending)) if current == field => None,
+                _ => Some((field, SortDirection::Ascending)),
+            });
+        })
+    };
+
+    let update_filter = |field: EventField, apply: fn(&mut ColumnFilter, String)| {
+        let filters = filters.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            let mut new_filters = (*filters).clone();
+            let filter = new_filters.entry(field).or_default();
+            apply(filter, input.value());
+            if filter.is_empty() {
+                new_filters.remove(&field);
+            }
+            filters.set(new_filters);
+        })
+    };
+
+    let toggle_column = |field: EventField| {
+        let columns = columns.clone();
+        let filters = filters.clone();
+        Callback::from(move |_: Event| {
+            let mut new_columns = (*columns).clone();
+            if new_columns.contains(&field) {
+                new_columns.retain(|column| *column != field);
+                // A hidden column's filter would silently keep hiding rows
+                let mut new_filters = (*filters).clone();
+                new_filters.remove(&field);
+                filters.set(new_filters);
+            } else {
+                new_columns.push(field);
+                new_columns.sort();
+            }
+            columns.set(new_columns);
+        })
+    };
+
+    let toggle_column_picker = {
+        let show_column_picker = show_column_picker.clone();
+        Callback::from(move |_: MouseEvent| show_column_picker.set(!*show_column_picker))
+    };
+
+    let clear_filters = {
+        let filters = filters.clone();
+        Callback::from(move |_: MouseEvent| filters.set(BTreeMap::new()))
+    };
+
+    let filter_cell = |field: EventField| {
+        let filter = filters.get(&field).cloned().unwrap_or_default();
+        let date_text = |day: Option<i64>| day.map(format_day).unwrap_or_default();
+
+        match field.kind() {
+            FieldKind::Text => html! {
+                <input
+                    type="text"
+                    placeholder="contains..."
+                    value={filter.contains.clone()}
+                    oninput={update_filter(field, |filter, value| filter.contains = value)}
+                />
+            },
+            FieldKind::Number => html! {
+                <div class="table-range-filter">
+                    <input
+                        type="number"
+                        placeholder="min"
+                        value={filter.min.clone()}
+                        oninput={update_filter(field, |filter, value| filter.min = value)}
+                    />
+                    <input
+                        type="number"
+                        placeholder="max"
+                        value={filter.max.clone()}
+                        oninput={update_filter(field, |filter, value| filter.max = value)}
+                    />
+                </div>
+            },
+            FieldKind::Date => html! {
+                <div class="table-range-filter">
+                    <input
+                        type="date"
+                        value={date_text(filter.from_day)}
+                        oninput={update_filter(field, |filter, value| filter.from_day = parse_day(&value))}
+                    />
+                    <input
+                        type="date"
+                        value={date_text(filter.to_day)}
+                        oninput={update_filter(field, |filter, value| filter.to_day = parse_day(&value))}
+                    />
+                </div>
+            },
+        }
+    };
+
+    let clear_search = {
+        let on_clear_search = props.on_clear_search.clone();
+        Callback::from(move |_: MouseEvent| on_clear_search.emit(()))
+    };
+
+    let clear_selection = {
+        let on_clear_selection = props.on_clear_selection.clone();
+        Callback::from(move |_: MouseEvent| on_clear_selection.emit(()))
+    };
+
+    let total_rows = rows.len();
+    let search = props.search.trim();
+    let narrowed = !search.is_empty() || props.selection.is_some();
+
+    html! {
+        <div class="events-table">
+            // The table and its export only hold what the search and map
+            // selection let through, so say so
+            if narrowed {
+                <div class="table-notice">
+                    <span class="search-count">
+                        {format!(
+                            "Showing {} of {} loaded events",
+                            props.events.len(),
+                            props.total_events.unwrap_or(props.events.len())
+                        )}
+                    </span>
+                    if !search.is_empty() {
+                        <span class="chip">
+                            {format!("Matching \"{}\"", search)}
+                            <button class="chip-remove" onclick={clear_search} title="Clear search">
+                                {"×"}
+                            </button>
+                        </span>
+                    }
+                    if let Some(selection) = &props.selection {
+                        <span class="chip selection-chip">
+                            {format!("Within {}", selection)}
+                            <button class="chip-remove" onclick={clear_selection} title="Clear selection">
+                                {"×"}
+                            </button>
+                        </span>
+                    }
+                </div>
+            }
+            <div class="table-toolbar">
+                <span class="table-count">
+                    {if total_rows == props.events.len() {
+                        format!("{} events", total_rows)
+                    } else {
+                        format!("{} of {} events", total_rows, props.events.len())
+                    }}
+                </span>
+                <button class="button" onclick={clear_filters} disabled={filters.is_empty()}>
+                    {"Clear Filters"}
+                </button>
+                <div class="table-column-picker">
+                    <button class="button" onclick={toggle_column_picker}>{"Columns"}</button>
+                    if *show_column_picker {
+                        <div class="table-column-menu">
+                            {for EventField::ALL.iter().map(|&field| html! {
+                                <label>
+                                    <input
+                                        type="checkbox"
+                                        checked={columns.contains(&field)}
+                                        onchange={toggle_column(field)}
+                                    />
+                                    {field.label()}
+                                </label>
+                            })}
+                        </div>
+                    }
+                </div>
+                <ExportMenu
+                    events={events.clone()}
+                    rows={Some(rows.clone())}
+                    params={props.params.clone()}
+                />
+            </div>
+
+            <VirtualRows events={events} rows={rows} columns={(*columns).clone()}>
+                <thead>
+                    <tr>
+                        {for columns.iter().map(|&field| {
+                            let indicator = match *sort {
+                                Some((current, SortDirection::Ascending)) if current == field => " ▲",
+                                Some((current, SortDirection::Descending)) if current == field => " ▼",
+                                _ => "",
+                            };
+                            html! {
+                                <th class={classes!("sortable", column_class(field))} onclick={on_sort(field)}>
+                                    {field.label()}{indicator}
+                                </th>
+                            }
+                        })}
+                    </tr>
+                    <tr class="table-filters">
+                        {for columns.iter().map(|&field| html! {
+                            <th class={column_class(field)}>{filter_cell(field)}</th>
+                        })}
+                    </tr>
+                </thead>
+            </VirtualRows>
+        </div>
+    }
+}
@@ -0,0 +1,89 @@
+use crate::components::AcledEvent;
+use crate::logging::console;
+use crate::services::{ExportFormat, ExportService};
+use crate::types::AcledParams;
+use std::rc::Rc;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::MouseEvent;
+use yew::prelude::*;
+
+#[derive(Properties, PartialEq)]
+pub struct ExportMenuProps {
+    /// The events as currently displayed, i.e. after any client-side filtering.
+    pub events: Rc<Vec<AcledEvent>>,
+    /// Indices into `events` to export, in display order; every event if unset.
+    /// Only copied out when an export runs.
+    #[prop_or_default]
+    pub rows: Option<Rc<Vec<usize>>>,
+    #[prop_or_default]
+    pub params: Option<AcledParams>,
+}
+
+#[function_component(ExportMenu)]
+pub fn export_menu(props: &ExportMenuProps) -> Html {
+    let exporting = use_state(|| false);
+    let status = use_state(|| Option::<Result<String, String>>::None);
+
+    let count = props
+        .rows
+        .as_ref()
+        .map_or(props.events.len(), |rows| rows.len());
+
+    let export = |format: ExportFormat| {
+        let events = props.events.clone();
+        let rows = props.rows.clone();
+        let params = props.params.clone();
+        let exporting = exporting.clone();
+        let status = status.clone();
+
+        Callback::from(move |_: MouseEvent| {
+            console::log_user_action!("Export {} ({} events)", format.label(), count);
+            exporting.set(true);
+            status.set(None);
+
+            let events = match &rows {
+                Some(rows) => Rc::new(rows.iter().map(|&index| events[index].clone()).collect()),
+                None => events.clone(),
+            };
+            let params = params.clone();
+            let exporting = exporting.clone();
+            let status = status.clone();
+
+            spawn_local(async move {
+                match ExportService::export(format, &events, params.as_ref()).await {
+                    Ok(true) => status.set(Some(Ok(format!(
+                        "Exported {} events as {}",
+                        events.len(),
+                        format.label()
+                    )))),
+                    Ok(false) => status.set(None),
+                    Err(e) => {
+                        console::error_with_context!("EXPORT", "Export failed: {}", e);
+                        status.set(Some(Err(e.to_string())));
+                    }
+                }
+                exporting.set(false);
+            });
+        })
+    };
+
+    let disabled = *exporting || count == 0;
+
+    html! {
+        <div class="export-menu">
+            <button class="button" onclick={export(ExportFormat::Csv)} {disabled}>
+                {"Export CSV"}
+            </button>
+            <button class="button" onclick={export(ExportFormat::GeoJson)} {disabled}>
+                {"Export GeoJSON"}
+            </button>
+            {match (*status).as_ref() {
+                Some(Ok(message)) => html! { <span class="export-status">{message}</span> },
+                Some(Err(error)) => html! {
+                    <span class="export-status export-error">{error}</span>
+                },
+                None => html! {},
+            }}
+        </div>
+    }
+}
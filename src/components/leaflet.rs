@@ -0,0 +1,159 @@
+//! wasm-bindgen bindings for the subset of Leaflet used by the map components.
+
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = L)]
+    #[derive(Clone)]
+    pub type LeafletMap;
+
+    #[wasm_bindgen(js_namespace = L, js_name = map)]
+    pub fn leaflet_map(id: &str, options: &JsValue) -> LeafletMap;
+
+    #[wasm_bindgen(js_namespace = L, method)]
+    pub fn setView(this: &LeafletMap, latlng: &JsValue, zoom: f64) -> LeafletMap;
+
+    #[wasm_bindgen(js_namespace = L, method)]
+    pub fn getZoom(this: &LeafletMap) -> f64;
+
+    #[wasm_bindgen(js_namespace = L, method)]
+    pub fn getCenter(this: &LeafletMap) -> LatLng;
+
+    #[wasm_bindgen(js_namespace = L, method)]
+    pub fn fitBounds(this: &LeafletMap, bounds: &JsValue, options: &JsValue) -> LeafletMap;
+
+    #[wasm_bindgen(js_namespace = L, method)]
+    pub fn on(this: &LeafletMap, event: &str, handler: &js_sys::Function) -> LeafletMap;
+
+    #[wasm_bindgen(js_namespace = L, method)]
+    pub fn invalidateSize(this: &LeafletMap) -> LeafletMap;
+
+    #[wasm_bindgen(js_namespace = L, method)]
+    pub fn remove(this: &LeafletMap) -> LeafletMap;
+
+    #[wasm_bindgen(js_namespace = L, method, getter)]
+    pub fn doubleClickZoom(this: &LeafletMap) -> Handler;
+
+    #[wasm_bindgen(js_namespace = L)]
+    pub type Handler;
+
+    #[wasm_bindgen(js_namespace = L, method)]
+    pub fn enable(this: &Handler) -> Handler;
+
+    #[wasm_bindgen(js_namespace = L, method)]
+    pub fn disable(this: &Handler) -> Handler;
+
+    #[wasm_bindgen(js_namespace = L)]
+    pub type LatLng;
+
+    #[wasm_bindgen(js_namespace = L, method, getter)]
+    pub fn lat(this: &LatLng) -> f64;
+
+    #[wasm_bindgen(js_namespace = L, method, getter)]
+    pub fn lng(this: &LatLng) -> f64;
+
+    #[wasm_bindgen(js_namespace = L)]
+    #[derive(Clone)]
+    pub type Layer;
+
+    #[wasm_bindgen(js_namespace = L, method)]
+    pub fn bindPopup(this: &Layer, content: &str) -> Layer;
+
+    #[wasm_bindgen(js_namespace = L, method)]
+    pub fn addTo(this: &Layer, map: &LeafletMap) -> Layer;
+
+    #[wasm_bindgen(js_namespace = L, method)]
+    pub fn remove(this: &Layer) -> Layer;
+
+    #[wasm_bindgen(js_namespace = L, js_name = layerGroup)]
+    pub fn layer_group() -> Layer;
+
+    #[wasm_bindgen(js_namespace = L, extends = Layer)]
+    pub type TileLayer;
+
+    #[wasm_bindgen(js_namespace = L, js_name = tileLayer)]
+    pub fn tile_layer(url: &str, options: &JsValue) -> TileLayer;
+
+    #[wasm_bindgen(js_namespace = L)]
+    pub type Control;
+
+    #[wasm_bindgen(js_namespace = ["L", "control"], js_name = layers)]
+    pub fn layers_control(base_layers: &JsValue, overlays: &JsValue, options: &JsValue) -> Control;
+
+    #[wasm_bindgen(js_namespace = L, method)]
+    pub fn addTo(this: &Control, map: &LeafletMap) -> Control;
+
+    #[wasm_bindgen(js_namespace = L, extends = Layer)]
+    pub type Marker;
+
+    #[wasm_bindgen(js_namespace = L, js_name = marker)]
+    pub fn leaflet_marker(latlng: &JsValue, options: &JsValue) -> Marker;
+
+    #[wasm_bindgen(js_namespace = L, extends = Layer)]
+    pub type CircleMarker;
+
+    #[wasm_bindgen(js_namespace = L, js_name = circleMarker)]
+    pub fn circle_marker(latlng: &JsValue, options: &JsValue) -> CircleMarker;
+
+    #[wasm_bindgen(js_namespace = L, extends = Layer)]
+    pub type Circle;
+
+    #[wasm_bindgen(js_namespace = L, js_name = circle)]
+    pub fn leaflet_circle(latlng: &JsValue, options: &JsValue) -> Circle;
+
+    #[wasm_bindgen(js_namespace = L, extends = Layer)]
+    pub type Polygon;
+
+    #[wasm_bindgen(js_namespace = L, js_name = polygon)]
+    pub fn leaflet_polygon(latlngs: &JsValue, options: &JsValue) -> Polygon;
+
+    #[wasm_bindgen(js_namespace = L, js_name = divIcon)]
+    pub fn div_icon(options: &JsValue) -> JsValue;
+
+    #[wasm_bindgen(js_namespace = L, extends = Layer)]
+    #[derive(Clone)]
+    pub type FeatureGroup;
+
+    #[wasm_bindgen(js_namespace = L, js_name = featureGroup)]
+    pub fn feature_group() -> FeatureGroup;
+
+    #[wasm_bindgen(js_namespace = L, method)]
+    pub fn addLayer(this: &FeatureGroup, layer: &Layer) -> FeatureGroup;
+
+    #[wasm_bindgen(js_namespace = L, method)]
+    pub fn addTo(this: &FeatureGroup, map: &LeafletMap) -> FeatureGroup;
+
+    #[wasm_bindgen(js_namespace = L, method)]
+    pub fn clearLayers(this: &FeatureGroup) -> FeatureGroup;
+
+    #[wasm_bindgen(js_namespace = L, method)]
+    pub fn on(this: &FeatureGroup, event: &str, handler: &js_sys::Function) -> FeatureGroup;
+}
+
+pub fn lat_lng(lat: f64, lng: f64) -> JsValue {
+    let latlng = js_sys::Array::new();
+    latlng.push(&lat.into());
+    latlng.push(&lng.into());
+    latlng.into()
+}
+
+/// `[[south, west], [north, east]]`, accepted anywhere Leaflet takes a `LatLngBounds`.
+pub fn lat_lng_bounds(south: f64, west: f64, north: f64, east: f64) -> JsValue {
+    let bounds = js_sys::Array::new();
+    bounds.push(&lat_lng(south, west));
+    bounds.push(&lat_lng(north, east));
+    bounds.into()
+}
+
+pub fn lat_lng_path(points: &[(f64, f64)]) -> JsValue {
+    points
+        .iter()
+        .map(|&(lat, lng)| lat_lng(lat, lng))
+        .collect::<js_sys::Array>()
+        .into()
+}
+
+pub fn set_option(options: &js_sys::Object, key: &str, value: &JsValue) {
+    js_sys::Reflect::set(options, &key.into(), value).unwrap();
+}
@@ -0,0 +1,181 @@
+use crate::logging::{self, console, LogEntry, LogLevel};
+use crate::services::{ExportService, TauriService};
+use gloo_timers::callback::Interval;
+use serde_json::json;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{HtmlInputElement, HtmlSelectElement, MouseEvent};
+use yew::prelude::*;
+
+// How often an open viewer checks the buffer for new entries
+const REFRESH_INTERVAL_MS: u32 = 500;
+
+#[derive(Properties, PartialEq)]
+pub struct LogViewerProps {
+    pub show: bool,
+    pub on_close: Callback<()>,
+}
+
+#[function_component(LogViewer)]
+pub fn log_viewer(props: &LogViewerProps) -> Html {
+    let revision = use_state(logging::revision);
+    let min_level = use_state(|| LogLevel::Info);
+    let filter = use_state(String::new);
+    let log_file = use_state(|| Option::<String>::None);
+
+    // Poll while open; re-rendering only when the buffer changed
+    {
+        let revision = revision.clone();
+        use_effect_with(props.show, move |show| {
+            let interval = show.then(|| {
+                Interval::new(REFRESH_INTERVAL_MS, move || {
+                    let latest = logging::revision();
+                    if *revision != latest {
+                        revision.set(latest);
+                    }
+                })
+            });
+            move || drop(interval)
+        });
+    }
+
+    // The desktop build also writes a log file; show where
+    {
+        let log_file = log_file.clone();
+        use_effect_with(props.show, move |show| {
+            if *show && log_file.is_none() && TauriService::is_available() {
+                spawn_local(async move {
+                    match TauriService::invoke::<_, String>("log_file_path", &json!({})).await {
+                        Ok(path) => log_file.set(Some(path)),
+                        Err(e) => console::warn_with_context!("LOGS", "No log file path: {}", e),
+                    }
+                });
+            }
+            || {}
+        });
+    }
+
+    let entries = use_memo(
+        (*revision, *min_level, (*filter).clone()),
+        |(_, min_level, filter)| {
+            let filter = filter.to_lowercase();
+            logging::entries()
+                .into_iter()
+                .rev()
+                .filter(|entry| entry.level >= *min_level)
+                .filter(|entry| {
+                    filter.is_empty() || entry.to_string().to_lowercase().contains(&filter)
+                })
+                .collect::<Vec<LogEntry>>()
+        },
+    );
+
+    let close = {
+        let on_close = props.on_close.clone();
+        Callback::from(move |_: MouseEvent| on_close.emit(()))
+    };
+
+    let on_level_change = {
+        let min_level = min_level.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            if let Some(level) = LogLevel::ALL
+                .into_iter()
+                .find(|level| level.label() == select.value())
+            {
+                min_level.set(level);
+            }
+        })
+    };
+
+    let on_filter_input = {
+        let filter = filter.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            filter.set(input.value());
+        })
+    };
+
+    let on_clear = {
+        let revision = revision.clone();
+        Callback::from(move |_: MouseEvent| {
+            logging::clear();
+            revision.set(logging::revision());
+        })
+    };
+
+    let on_export = Callback::from(|_: MouseEvent| {
+        let contents = logging::entries()
+            .iter()
+            .map(|entry| format!("{}\n", entry))
+            .collect::<String>();
+        let file_name = format!(
+            "arson-log-{}.log",
+            String::from(js_sys::Date::new_0().to_iso_string())
+                .get(..10)
+                .unwrap_or("session")
+        );
+        spawn_local(async move {
+            if let Err(e) =
+                ExportService::save_file(&file_name, "Log", "log", "text/plain", &contents).await
+            {
+                console::error_with_context!("LOGS", "Failed to export log: {}", e);
+            }
+        });
+    });
+
+    if !props.show {
+        return html! {};
+    }
+
+    html! {
+        <div class="modal-overlay" onclick={close.clone()}>
+            <div class="modal-content log-viewer" onclick={Callback::from(|e: MouseEvent| e.stop_propagation())}>
+                <div class="modal-header">
+                    <h3>{"Logs"}</h3>
+                    <button class="modal-close" onclick={close}>{"×"}</button>
+                </div>
+                <div class="modal-body">
+                    <div class="log-toolbar">
+                        <select class="log-level-select" onchange={on_level_change}>
+                            {for LogLevel::ALL.iter().map(|level| html! {
+                                <option value={level.label()} selected={*level == *min_level}>
+                                    {format!("{}+", level.label())}
+                                </option>
+                            })}
+                        </select>
+                        <input
+                            type="search"
+                            class="log-filter"
+                            placeholder="Filter..."
+                            value={(*filter).clone()}
+                            oninput={on_filter_input}
+                        />
+                        <button class="button" onclick={on_export}>{"Export"}</button>
+                        <button class="button" onclick={on_clear}>{"Clear"}</button>
+                    </div>
+                    if let Some(path) = (*log_file).as_ref() {
+                        <p class="log-file-path">{"Log file: "}<code>{path}</code></p>
+                    }
+                    <div class="log-entries">
+                        if entries.is_empty() {
+                            <p class="no-data">{"No log entries."}</p>
+                        }
+                        {for entries.iter().map(|entry| html! {
+                            <div class={classes!("log-entry", format!("log-{}", entry.level.label()))}>
+                                <span class="log-time">{entry.timestamp.get(11..23).unwrap_or(&entry.timestamp)}</span>
+                                <span class="log-level">{entry.level.label()}</span>
+                                if !entry.context.is_empty() {
+                                    <span class="log-context">{&entry.context}</span>
+                                }
+                                <span class="log-message">{&entry.message}</span>
+                                {for entry.fields.iter().map(|(key, value)| html! {
+                                    <span class="log-field">{format!("{}={}", key, value)}</span>
+                                })}
+                            </div>
+                        })}
+                    </div>
+                </div>
+            </div>
+        </div>
+    }
+}
@@ -1,69 +1,231 @@
-use crate::config::{AVAILABLE_COUNTRIES, AVAILABLE_EVENT_TYPES, MAX_EVENTS_LIMIT};
-use crate::errors::AppError;
+use crate::components::{
+    report_error, report_notice, ChipPicker, NotificationContext, SavedQueryPicker,
+};
+use crate::config::{
+    AVAILABLE_COUNTRIES, AVAILABLE_EVENT_TYPES, MAX_EVENTS_LIMIT, MAX_FETCH_PAGES,
+};
+use crate::errors::{AppError, ErrorSeverity};
+use crate::geo::Bounds;
 use crate::logging::console;
-use crate::services::AcledService;
-use crate::types::{AcledEvent, AcledParams, UserData};
+use crate::services::{AcledService, FetchHandle};
+use crate::types::{AcledEvent, AcledParams, FetchProgress, PagedEvents, RetryStatus, UserData};
 use wasm_bindgen::JsCast;
-use web_sys::{HtmlInputElement, HtmlSelectElement, MouseEvent};
+use web_sys::{HtmlInputElement, MouseEvent};
 use yew::prelude::*;
 
 #[derive(Properties, PartialEq)]
 pub struct MapDataProps {
     pub user_data: UserData,
+    /// Parameters the form starts with, such as those of the last session.
+    #[prop_or_default]
+    pub initial_params: Option<AcledParams>,
     pub on_data_change: Callback<Option<Vec<AcledEvent>>>,
+    /// Emitted with the parameters of each fetch as it starts.
+    #[prop_or_default]
+    pub on_params_change: Callback<AcledParams>,
+    /// Box to fetch within, set from a shape drawn on the map; overrides the
+    /// form's own `area`.
+    #[prop_or_default]
+    pub area: Option<Bounds>,
+    #[prop_or_default]
+    pub on_area_change: Callback<Option<Bounds>>,
 }
 
 #[function_component(MapData)]
 pub fn map_data(props: &MapDataProps) -> Html {
-    let params = use_state(AcledParams::default);
+    let params = use_state(|| props.initial_params.clone().unwrap_or_default());
     let loading = use_state(|| false);
-    let error = use_state(|| Option::<String>::None);
+    let progress = use_state(|| Option::<(u32, usize)>::None);
+    let retry = use_state(|| Option::<RetryStatus>::None);
+    let notifications = use_context::<NotificationContext>();
+    // Only the latest fetch may deliver results
+    let current_fetch = use_mut_ref(|| Option::<FetchHandle>::None);
+    // Pages of a paginated fetch so far, and how many of them the dashboard shows
+    let partial = use_mut_ref(|| (Vec::<AcledEvent>::new(), 0usize));
+
+    let cancel_fetch = {
+        let current_fetch = current_fetch.clone();
+        let loading = loading.clone();
+        let progress = progress.clone();
+        let retry = retry.clone();
+        Callback::from(move |_: ()| {
+            if let Some(fetch) = current_fetch.borrow_mut().take() {
+                if !fetch.is_cancelled() {
+                    console::log_with_context!("API", "Cancelled fetch");
+                }
+                fetch.cancel();
+                loading.set(false);
+                progress.set(None);
+                retry.set(None);
+            }
+        })
+    };
+
+    // Leaving the dashboard (logout, profile switch) abandons the fetch
+    {
+        let current_fetch = current_fetch.clone();
+        use_effect_with((), move |_| {
+            move || {
+                if let Some(fetch) = current_fetch.borrow_mut().take() {
+                    fetch.cancel();
+                }
+            }
+        });
+    }
 
     let update_param = {
         let params = params.clone();
-        let error = error.clone();
+        let cancel_fetch = cancel_fetch.clone();
         Callback::from(move |new_params: AcledParams| {
+            // Results for the old parameters are no longer wanted
+            cancel_fetch.emit(());
             params.set(new_params);
-            error.set(None); // Clear error when params change
         })
     };
 
+    let current_params = AcledParams {
+        area: props.area,
+        ..(*params).clone()
+    };
+
+    // Saved queries carry their area back to the map
+    let load_query = {
+        let update_param = update_param.clone();
+        let on_area_change = props.on_area_change.clone();
+        Callback::from(move |loaded: AcledParams| {
+            on_area_change.emit(loaded.area);
+            update_param.emit(loaded);
+        })
+    };
+
+    let clear_area = {
+        let on_area_change = props.on_area_change.clone();
+        Callback::from(move |_: MouseEvent| on_area_change.emit(None))
+    };
+
     let fetch_acled_data = {
         let user_data = props.user_data.clone();
-        let params = params.clone();
+        let params = current_params.clone();
         let loading = loading.clone();
-        let error = error.clone();
+        let progress = progress.clone();
+        let retry = retry.clone();
+        let notifications = notifications.clone();
         let on_data_change = props.on_data_change.clone();
+        let on_params_change = props.on_params_change.clone();
+        let current_fetch = current_fetch.clone();
+        let partial = partial.clone();
 
         Callback::from(move |_: MouseEvent| {
             console::log_user_action!("Fetch button clicked");
+            if let Some(previous) = current_fetch.borrow_mut().take() {
+                previous.cancel();
+            }
+            on_params_change.emit(params.clone());
 
             loading.set(true);
-            error.set(None);
+            progress.set(None);
+            retry.set(None);
+
+            let on_retry = {
+                let retry = retry.clone();
+                Callback::from(move |status: RetryStatus| retry.set(Some(status)))
+            };
 
             let on_success = {
                 let loading = loading.clone();
+                let retry = retry.clone();
                 let on_data_change = on_data_change.clone();
                 Callback::from(move |events: Vec<AcledEvent>| {
                     console::log!("Successfully fetched {} events", events.len());
                     loading.set(false);
+                    retry.set(None);
                     on_data_change.emit(Some(events));
                 })
             };
 
             let on_error = {
                 let loading = loading.clone();
-                let error = error.clone();
+                let retry = retry.clone();
+                let notifications = notifications.clone();
                 let on_data_change = on_data_change.clone();
                 Callback::from(move |app_error: AppError| {
                     console::error_with_context!("API", "Failed to fetch data: {}", app_error);
                     loading.set(false);
-                    error.set(Some(app_error.to_string()));
+                    retry.set(None);
+                    if let Some(notifications) = notifications.as_ref() {
+                        report_error(notifications, "Fetching events", app_error);
+                    }
                     on_data_change.emit(None);
                 })
             };
 
-            AcledService::fetch_events(&user_data, &*params, on_success, on_error);
+            if params.fetch_all_pages {
+                *partial.borrow_mut() = (Vec::new(), 0);
+
+                // Partial results reach the dashboard each time they double, so
+                // copying them stays linear in the total
+                let on_progress = {
+                    let progress = progress.clone();
+                    let retry = retry.clone();
+                    let on_data_change = on_data_change.clone();
+                    let partial = partial.clone();
+                    Callback::from(move |update: FetchProgress| {
+                        console::log!(
+                            "Fetched page {} ({} events so far)",
+                            update.page,
+                            update.total_events
+                        );
+                        progress.set(Some((update.page, update.total_events)));
+                        retry.set(None);
+
+                        let mut partial = partial.borrow_mut();
+                        let (events, shown) = &mut *partial;
+                        events.extend(update.page_events);
+                        if events.len() >= 2 * *shown && !events.is_empty() {
+                            *shown = events.len();
+                            on_data_change.emit(Some(events.clone()));
+                        }
+                    })
+                };
+
+                let on_paged_success = {
+                    let notifications = notifications.clone();
+                    let partial = partial.clone();
+                    Callback::from(move |result: PagedEvents| {
+                        *partial.borrow_mut() = (Vec::new(), 0);
+                        if result.truncated {
+                            // The fetch succeeded, so this is a notice rather than an error
+                            if let Some(notifications) = notifications.as_ref() {
+                                report_notice(
+                                    notifications,
+                                    "Fetching events",
+                                    format!(
+                                        "Stopped after {} pages; only the first {} events are shown. Narrow the query or raise the page size to get the rest.",
+                                        MAX_FETCH_PAGES,
+                                        result.events.len()
+                                    ),
+                                    ErrorSeverity::Warning,
+                                );
+                            }
+                        }
+                        on_success.emit(result.events);
+                    })
+                };
+
+                let fetch = AcledService::fetch_all_events(
+                    &user_data,
+                    &params,
+                    on_progress,
+                    on_retry,
+                    on_paged_success,
+                    on_error,
+                );
+                *current_fetch.borrow_mut() = Some(fetch);
+            } else {
+                let fetch =
+                    AcledService::fetch_events(&user_data, &params, on_retry, on_success, on_error);
+                *current_fetch.borrow_mut() = Some(fetch);
+            }
         })
     };
 
@@ -71,6 +233,8 @@ pub fn map_data(props: &MapDataProps) -> Html {
                     <div class="panel parameters-panel">
             <h3>{"API Parameters"}</h3>
 
+            <SavedQueryPicker current={current_params.clone()} on_load={load_query} />
+
             <div class="param-group">
                 <label for="start-date">{"Start Date:"}</label>
                 <input
@@ -112,61 +276,48 @@ pub fn map_data(props: &MapDataProps) -> Html {
             </div>
 
             <div class="param-group">
-                <label for="country">{"Country:"}</label>
-                <select
+                <label for="country">{"Countries:"}</label>
+                <ChipPicker
                     id="country"
-                    value={params.country.clone()}
-                    onchange={
+                    options={AVAILABLE_COUNTRIES}
+                    selected={params.countries.clone()}
+                    placeholder="Add country..."
+                    on_change={
                         let update_param = update_param.clone();
                         let params = params.clone();
-                        Callback::from(move |e: Event| {
-                            let target = e.target().unwrap();
-                            let select = target.unchecked_into::<HtmlSelectElement>();
+                        Callback::from(move |countries: Vec<String>| {
                             let mut new_params = (*params).clone();
-                            new_params.country = select.value();
+                            new_params.countries = countries;
                             update_param.emit(new_params);
                         })
                     }
-                >
-                    {for AVAILABLE_COUNTRIES.iter().map(|&country| {
-                        html! {
-                            <option value={country} selected={params.country == country}>
-                                {country}
-                            </option>
-                        }
-                    })}
-                </select>
+                />
             </div>
 
             <div class="param-group">
-                <label for="event-type">{"Event Type:"}</label>
-                <select
+                <label for="event-type">{"Event Types:"}</label>
+                <ChipPicker
                     id="event-type"
-                    value={params.event_type.clone()}
-                    onchange={
+                    options={AVAILABLE_EVENT_TYPES}
+                    selected={params.event_types.clone()}
+                    placeholder="Add event type..."
+                    empty_label="All event types"
+                    on_change={
                         let update_param = update_param.clone();
                         let params = params.clone();
-                        Callback::from(move |e: Event| {
-                            let target = e.target().unwrap();
-                            let select = target.unchecked_into::<HtmlSelectElement>();
+                        Callback::from(move |event_types: Vec<String>| {
                             let mut new_params = (*params).clone();
-                            new_params.event_type = select.value();
+                            new_params.event_types = event_types;
                             update_param.emit(new_params);
                         })
                     }
-                >
-                    {for AVAILABLE_EVENT_TYPES.iter().map(|&event_type| {
-                        html! {
-                            <option value={event_type} selected={params.event_type == event_type}>
-                                {event_type}
-                            </option>
-                        }
-                    })}
-                </select>
+                />
             </div>
 
             <div class="param-group">
-                <label for="limit">{"Limit:"}</label>
+                <label for="limit">
+                    {if params.fetch_all_pages { "Page Size:" } else { "Limit:" }}
+                </label>
                 <input
                     type="number"
                     id="limit"
@@ -189,27 +340,86 @@ pub fn map_data(props: &MapDataProps) -> Html {
                 />
             </div>
 
+            if let Some(area) = props.area {
+                <div class="param-group">
+                    <label>{"Area:"}</label>
+                    <div class="param-area">
+                        <span>
+                            {format!(
+                                "Lat {:.2} to {:.2}, lng {:.2} to {:.2}",
+                                area.south, area.north, area.west, area.east
+                            )}
+                        </span>
+                        <button class="button" onclick={clear_area}>{"Clear"}</button>
+                    </div>
+                </div>
+            }
+
+            <div class="param-group param-checkbox">
+                <label for="fetch-all-pages">
+                    <input
+                        type="checkbox"
+                        id="fetch-all-pages"
+                        checked={params.fetch_all_pages}
+                        onchange={
+                            let update_param = update_param.clone();
+                            let params = params.clone();
+                            Callback::from(move |e: Event| {
+                                let target = e.target().unwrap();
+                                let input = target.unchecked_into::<HtmlInputElement>();
+                                let mut new_params = (*params).clone();
+                                new_params.fetch_all_pages = input.checked();
+                                update_param.emit(new_params);
+                            })
+                        }
+                    />
+                    {"Fetch all pages"}
+                </label>
+            </div>
+
             <button
                 class="button"
                 onclick={fetch_acled_data}
                 disabled={*loading}
             >
-                {if *loading {
-                    "Loading..."
-                } else {
-                    "Fetch Data"
+                {match (*loading, *progress, (*retry).as_ref()) {
+                    (true, _, Some(status)) => {
+                        format!("Retrying ({}/{})...", status.attempt, status.max_attempts)
+                    }
+                    (true, Some((page, count)), None) => {
+                        format!("Loading page {}... ({} events)", page + 1, count)
+                    }
+                    (true, None, None) => "Loading...".to_string(),
+                    (false, _, _) => "Fetch Data".to_string(),
                 }}
             </button>
 
-            {if let Some(error_msg) = (*error).as_ref() {
-                html! {
-                    <div class="error-message">
-                        <strong>{"Error: "}</strong>{error_msg}
-                    </div>
-                }
-            } else {
-                html! {}
-            }}
+            if *loading {
+                <button
+                    class="button cancel-fetch"
+                    onclick={
+                        let cancel_fetch = cancel_fetch.clone();
+                        Callback::from(move |_: MouseEvent| {
+                            console::log_user_action!("Cancel fetch clicked");
+                            cancel_fetch.emit(());
+                        })
+                    }
+                >
+                    {"Cancel"}
+                </button>
+            }
+
+            if let (true, Some(status)) = (*loading, (*retry).as_ref()) {
+                <div class="retry-status">
+                    {format!(
+                        "Attempt {} of {} in {}s after: {}",
+                        status.attempt,
+                        status.max_attempts,
+                        (status.delay_ms as f64 / 1000.0).ceil(),
+                        status.reason
+                    )}
+                </div>
+            }
         </div>
     }
 }
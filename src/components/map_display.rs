@@ -1,156 +1,891 @@
-use crate::components::{generate_popup_content, AcledEvent};
+use crate::components::leaflet::{
+    circle_marker, div_icon, feature_group, lat_lng, lat_lng_bounds, lat_lng_path, layer_group,
+    layers_control, leaflet_circle, leaflet_map, leaflet_marker, leaflet_polygon, set_option,
+    tile_layer, FeatureGroup, LatLng, Layer, LeafletMap, Marker, Polygon,
+};
+use crate::components::{
+    generate_cluster_popup_content, generate_popup_content, report_error, AcledEvent, DateWindow,
+    ExportMenu, MapLegend, NotificationContext, RegionLegend, Timeline,
+};
+use crate::config::{
+    CHOROPLETH_CLASSES, DEFAULT_MAP_CENTER, DEFAULT_MAP_ZOOM, DENSITY_HEX_RADIUS_PX,
+    FIT_BOUNDS_MAX_ZOOM, FIT_BOUNDS_PADDING_PX,
+};
+use crate::geo::{
+    class_breaks, class_index, clusters_at_zoom, countries_bounds, distance_m, events_bounds,
+    hex_bin, region_totals, Boundaries, Bounds, ClassBreaks, Cluster, DensityWeight, HexBin,
+    RegionLevel, RegionSummary, RegionTotal, Selection,
+};
 use crate::logging::console;
+use crate::services::{ExportService, TauriService, TileService};
+use crate::types::{
+    fatality_radius, precision_opacity, AcledParams, Basemap, MapView, MarkerStyle, Symbology,
+};
+use crate::utils::date::parse_day;
+use crate::utils::text::escape_html;
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::rc::Rc;
 use wasm_bindgen::prelude::*;
-use web_sys::HtmlElement;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{HtmlElement, HtmlInputElement, HtmlSelectElement};
 use yew::prelude::*;
 
-#[wasm_bindgen]
-extern "C" {
-    #[wasm_bindgen(js_namespace = L)]
-    type LeafletMap;
+// Outline of drawn selections
+const SELECTION_COLOR: &str = "#3b82f6";
 
-    #[wasm_bindgen(js_namespace = L, js_name = map)]
-    fn leaflet_map(id: &str, options: &JsValue) -> LeafletMap;
+/// Shapes that can be drawn to select events.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum DrawTool {
+    Rectangle,
+    Polygon,
+    Circle,
+}
+
+impl DrawTool {
+    const ALL: [DrawTool; 3] = [DrawTool::Rectangle, DrawTool::Polygon, DrawTool::Circle];
 
-    #[wasm_bindgen(js_namespace = L, method)]
-    fn setView(this: &LeafletMap, latlng: &JsValue, zoom: f64) -> LeafletMap;
+    fn label(self) -> &'static str {
+        match self {
+            DrawTool::Rectangle => "Rectangle",
+            DrawTool::Polygon => "Polygon",
+            DrawTool::Circle => "Circle",
+        }
+    }
 
-    #[wasm_bindgen(js_namespace = L)]
-    type TileLayer;
+    fn hint(self) -> &'static str {
+        match self {
+            DrawTool::Rectangle => "Click two opposite corners",
+            DrawTool::Polygon => "Click each corner, then double-click to finish",
+            DrawTool::Circle => "Click the center, then a point on the edge",
+        }
+    }
+}
+
+/// Shape being drawn, shared with the map's mouse listeners.
+#[derive(Default)]
+struct Drawing {
+    tool: Option<DrawTool>,
+    points: Vec<(f64, f64)>,
+}
 
-    #[wasm_bindgen(js_namespace = L, js_name = tileLayer)]
-    fn tile_layer(url: &str, options: &JsValue) -> TileLayer;
+struct MapHandles {
+    map: LeafletMap,
+    basemaps: Vec<(Basemap, Layer)>,
+    /// Shown basemap, also switched by Leaflet's layer control.
+    basemap: Rc<Cell<Option<Basemap>>>,
+    regions: FeatureGroup,
+    density: FeatureGroup,
+    markers: FeatureGroup,
+    drawing: Rc<RefCell<Drawing>>,
+    /// Outline of the shape being drawn.
+    sketch: FeatureGroup,
+    /// Outline of the current selection.
+    selection: FeatureGroup,
+    _listeners: Vec<Closure<dyn FnMut(JsValue)>>,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct MapDisplayProps {
+    pub events: Option<Vec<AcledEvent>>,
+    /// Parameters of the fetch that produced `events`; bounds the timeline.
+    #[prop_or_default]
+    pub params: Option<AcledParams>,
+    /// Free-text search `events` were filtered by, shown in the stats overlay.
+    #[prop_or_default]
+    pub search: String,
+    #[prop_or_default]
+    pub basemap: Basemap,
+    /// Emitted when the user picks a basemap from the map's layer control.
+    #[prop_or_default]
+    pub on_basemap_change: Callback<Basemap>,
+    /// Where the map opens, such as where it was left last session.
+    #[prop_or_default]
+    pub initial_view: Option<MapView>,
+    /// Emitted when the user finishes panning or zooming.
+    #[prop_or_default]
+    pub on_view_change: Callback<MapView>,
+    #[prop_or_default]
+    pub symbology: Symbology,
+    /// Area drawn on the map; `events` are already limited to it.
+    #[prop_or_default]
+    pub selection: Option<Selection>,
+    /// Emitted when the user finishes drawing a shape or clears it.
+    #[prop_or_default]
+    pub on_selection_change: Callback<Option<Selection>>,
+    /// Emitted to limit the next fetch to the selection's bounding box.
+    #[prop_or_default]
+    pub on_query_area_change: Callback<Option<Bounds>>,
+}
 
-    #[wasm_bindgen(js_namespace = L, method)]
-    fn addTo(this: &TileLayer, map: &LeafletMap) -> TileLayer;
+// Requested date range, falling back to the extent of the loaded events
+fn timeline_range(params: Option<&AcledParams>, events: &[AcledEvent]) -> Option<(i64, i64)> {
+    let requested = params
+        .and_then(|params| Some((parse_day(&params.start_date)?, parse_day(&params.end_date)?)));
 
-    #[wasm_bindgen(js_namespace = L)]
-    type Marker;
+    requested.or_else(|| {
+        let days = events
+            .iter()
+            .filter_map(|event| parse_day(&event.event_date));
+        days.fold(None, |range, day| match range {
+            None => Some((day, day)),
+            Some((start, end)) => Some((day.min(start), day.max(end))),
+        })
+    })
+}
 
-    #[wasm_bindgen(js_namespace = L, js_name = marker)]
-    fn leaflet_marker(latlng: &JsValue, options: &JsValue) -> Marker;
+fn fit_to(map: &LeafletMap, bounds: &Bounds) {
+    if bounds.is_point() {
+        map.setView(&lat_lng(bounds.south, bounds.west), FIT_BOUNDS_MAX_ZOOM);
+        return;
+    }
 
-    #[wasm_bindgen(js_namespace = L, method)]
-    fn addTo(this: &Marker, map: &LeafletMap) -> Marker;
+    let padding = FIT_BOUNDS_PADDING_PX;
+    let fit_options = js_sys::Object::new();
+    set_option(
+        &fit_options,
+        "padding",
+        &js_sys::Array::of2(&padding.into(), &padding.into()).into(),
+    );
+    set_option(&fit_options, "maxZoom", &FIT_BOUNDS_MAX_ZOOM.into());
+    map.fitBounds(
+        &lat_lng_bounds(bounds.south, bounds.west, bounds.north, bounds.east),
+        &fit_options.into(),
+    );
+}
 
-    #[wasm_bindgen(js_namespace = L, method)]
-    fn bindPopup(this: &Marker, content: &str) -> Marker;
+// The loaded events, else the queried area or countries, else the configured default
+fn frame_data(map: &LeafletMap, events: Option<&[AcledEvent]>, params: Option<&AcledParams>) {
+    let bounds = events.and_then(events_bounds).or_else(|| {
+        params.and_then(|params| params.area.or_else(|| countries_bounds(&params.countries)))
+    });
 
-    #[wasm_bindgen(js_namespace = L)]
-    type LayerGroup;
+    match bounds {
+        Some(bounds) => fit_to(map, &bounds),
+        None => {
+            let (lat, lng) = DEFAULT_MAP_CENTER;
+            map.setView(&lat_lng(lat, lng), DEFAULT_MAP_ZOOM);
+        }
+    }
+}
 
-    #[wasm_bindgen(js_namespace = L, js_name = layerGroup)]
-    fn layer_group() -> LayerGroup;
+// Offline tiles are only served by the desktop backend
+fn available_basemaps() -> Vec<Basemap> {
+    Basemap::ALL
+        .into_iter()
+        .filter(|basemap| *basemap != Basemap::Offline || TauriService::is_available())
+        .collect()
+}
 
-    #[wasm_bindgen(js_namespace = L, method)]
-    fn addLayer(this: &LayerGroup, layer: &Marker) -> LayerGroup;
+fn basemap_layer(basemap: Basemap) -> Layer {
+    let url = match basemap {
+        Basemap::Blank => return layer_group(),
+        Basemap::Offline => TileService::url_template(),
+        online => online.tile_url().unwrap_or_default().to_string(),
+    };
 
-    #[wasm_bindgen(js_namespace = L, method)]
-    fn addTo(this: &LayerGroup, map: &LeafletMap) -> LayerGroup;
+    let tile_options = js_sys::Object::new();
+    set_option(&tile_options, "attribution", &basemap.attribution().into());
+    set_option(&tile_options, "maxZoom", &basemap.max_zoom().into());
+    tile_layer(&url, &tile_options.into()).into()
+}
 
-    #[wasm_bindgen(js_namespace = L, method)]
-    fn clearLayers(this: &LayerGroup) -> LayerGroup;
+// Map pin drawn in a 24x34 box with its point at the bottom centre
+fn pin_svg(color: &str, opacity: f64) -> String {
+    format!(
+        "<svg viewBox=\"0 0 24 34\" width=\"100%\" height=\"100%\">\
+         <path d=\"M12 1C5.9 1 1 5.9 1 12c0 8.3 11 21 11 21s11-12.7 11-21C23 5.9 18.1 1 12 1z\" \
+         fill=\"{}\" fill-opacity=\"{}\" stroke=\"#ffffff\" stroke-width=\"1.5\"/>\
+         <circle cx=\"12\" cy=\"12\" r=\"4\" fill=\"#ffffff\"/></svg>",
+        color, opacity
+    )
 }
 
-#[derive(Properties, PartialEq)]
-pub struct MapDisplayProps {
-    pub events: Option<Vec<AcledEvent>>,
+// Colored by event type, sized by fatalities, faded by location precision
+fn event_marker(event: &AcledEvent, symbology: &Symbology) -> Option<Layer> {
+    let (lat, lng) = event.coordinates()?;
+    let color = symbology.color(&event.event_type);
+    let radius = fatality_radius(event.fatality_count());
+    let opacity = precision_opacity(event.geo_precision);
+
+    let marker: Layer = match symbology.style {
+        MarkerStyle::Circle => {
+            let options = js_sys::Object::new();
+            set_option(&options, "radius", &radius.into());
+            set_option(&options, "color", &color.into());
+            set_option(&options, "weight", &1.5.into());
+            set_option(&options, "opacity", &opacity.into());
+            set_option(&options, "fillColor", &color.into());
+            set_option(&options, "fillOpacity", &(opacity * 0.8).into());
+            circle_marker(&lat_lng(lat, lng), &options.into()).into()
+        }
+        MarkerStyle::Pin => {
+            let width = (radius * 2.0).round();
+            let height = (width * 34.0 / 24.0).round();
+
+            let icon_options = js_sys::Object::new();
+            set_option(&icon_options, "html", &pin_svg(color, opacity).into());
+            set_option(&icon_options, "className", &"event-pin".into());
+            set_option(
+                &icon_options,
+                "iconSize",
+                &js_sys::Array::of2(&width.into(), &height.into()).into(),
+            );
+            set_option(
+                &icon_options,
+                "iconAnchor",
+                &js_sys::Array::of2(&(width / 2.0).into(), &height.into()).into(),
+            );
+            set_option(
+                &icon_options,
+                "popupAnchor",
+                &js_sys::Array::of2(&0.into(), &(-height).into()).into(),
+            );
+
+            let marker_options = js_sys::Object::new();
+            set_option(&marker_options, "icon", &div_icon(&icon_options.into()));
+            leaflet_marker(&lat_lng(lat, lng), &marker_options.into()).into()
+        }
+    };
+    marker.bindPopup(&generate_popup_content(event));
+    Some(marker)
+}
+
+fn cluster_marker(cluster: &Cluster, events: &[AcledEvent]) -> Marker {
+    let size = match cluster.count() {
+        0..=9 => 32,
+        10..=99 => 40,
+        100..=999 => 48,
+        _ => 56,
+    };
+
+    let icon_size = js_sys::Array::of2(&size.into(), &size.into());
+    let icon_options = js_sys::Object::new();
+    set_option(
+        &icon_options,
+        "html",
+        &format!("<div><span>{}</span></div>", cluster.count()).into(),
+    );
+    set_option(
+        &icon_options,
+        "className",
+        &format!("event-cluster {}", cluster.fatality_class()).into(),
+    );
+    set_option(&icon_options, "iconSize", &icon_size.into());
+
+    let marker_options = js_sys::Object::new();
+    set_option(&marker_options, "icon", &div_icon(&icon_options.into()));
+    set_option(
+        &marker_options,
+        "title",
+        &format!(
+            "{} events, {} fatalities",
+            cluster.count(),
+            cluster.fatalities
+        )
+        .into(),
+    );
+
+    // Co-located events never split apart by zooming, so list them instead
+    if !cluster.bounds.is_point() {
+        let bounds = &cluster.bounds;
+        set_option(
+            &marker_options,
+            "clusterBounds",
+            &lat_lng_bounds(bounds.south, bounds.west, bounds.north, bounds.east),
+        );
+    }
+
+    let marker = leaflet_marker(&lat_lng(cluster.lat, cluster.lng), &marker_options.into());
+
+    if cluster.bounds.is_point() {
+        let members: Vec<&AcledEvent> = cluster.members.iter().map(|&i| &events[i]).collect();
+        marker.bindPopup(&generate_cluster_popup_content(&members));
+    }
+
+    marker
+}
+
+// Yellow -> orange -> deep red ramp for density intensity in 0.0..=1.0
+fn density_color(intensity: f64) -> String {
+    const STOPS: [(f64, (f64, f64, f64)); 3] = [
+        (0.0, (255.0, 237.0, 160.0)),
+        (0.5, (253.0, 141.0, 60.0)),
+        (1.0, (189.0, 0.0, 38.0)),
+    ];
+
+    let t = intensity.clamp(0.0, 1.0);
+    let (lower, upper) = if t <= STOPS[1].0 {
+        (STOPS[0], STOPS[1])
+    } else {
+        (STOPS[1], STOPS[2])
+    };
+
+    let local = (t - lower.0) / (upper.0 - lower.0);
+    let mix = |a: f64, b: f64| (a + (b - a) * local).round() as u8;
+    format!(
+        "rgb({}, {}, {})",
+        mix(lower.1 .0, upper.1 .0),
+        mix(lower.1 .1, upper.1 .1),
+        mix(lower.1 .2, upper.1 .2)
+    )
+}
+
+fn density_polygon(bin: &HexBin) -> Polygon {
+    let color = density_color(bin.intensity);
+
+    let options = js_sys::Object::new();
+    set_option(&options, "stroke", &true.into());
+    set_option(&options, "color", &color.clone().into());
+    set_option(&options, "weight", &1.into());
+    set_option(&options, "opacity", &0.4.into());
+    set_option(&options, "fillColor", &color.into());
+    set_option(
+        &options,
+        "fillOpacity",
+        &(0.25 + 0.5 * bin.intensity).into(),
+    );
+    set_option(&options, "interactive", &false.into());
+
+    leaflet_polygon(&lat_lng_path(&bin.vertices), &options.into())
+}
+
+fn selection_layer(selection: &Selection, sketch: bool) -> Layer {
+    let options = js_sys::Object::new();
+    set_option(&options, "color", &SELECTION_COLOR.into());
+    set_option(&options, "weight", &2.into());
+    set_option(&options, "fillOpacity", &0.08.into());
+    set_option(&options, "interactive", &false.into());
+    if sketch {
+        set_option(&options, "dashArray", &"6 4".into());
+    }
+
+    match selection {
+        Selection::Rectangle(bounds) => {
+            let corners = [
+                (bounds.south, bounds.west),
+                (bounds.north, bounds.west),
+                (bounds.north, bounds.east),
+                (bounds.south, bounds.east),
+            ];
+            leaflet_polygon(&lat_lng_path(&corners), &options.into()).into()
+        }
+        Selection::Polygon(vertices) => {
+            leaflet_polygon(&lat_lng_path(vertices), &options.into()).into()
+        }
+        Selection::Circle { lat, lng, radius_m } => {
+            set_option(&options, "radius", &(*radius_m).into());
+            leaflet_circle(&lat_lng(*lat, *lng), &options.into()).into()
+        }
+    }
+}
+
+// The shape the clicked `points` describe, taking `next` as the following point
+fn sketch_selection(tool: DrawTool, points: &[(f64, f64)], next: (f64, f64)) -> Option<Selection> {
+    match tool {
+        DrawTool::Rectangle => {
+            let &(lat, lng) = points.first()?;
+            let mut bounds = Bounds::from_point(lat, lng);
+            bounds.extend(next.0, next.1);
+            (!bounds.is_point()).then_some(Selection::Rectangle(bounds))
+        }
+        DrawTool::Polygon => {
+            let mut vertices = points.to_vec();
+            if vertices.last() != Some(&next) {
+                vertices.push(next);
+            }
+            (vertices.len() >= 3).then_some(Selection::Polygon(vertices))
+        }
+        DrawTool::Circle => {
+            let &(lat, lng) = points.first()?;
+            let radius_m = distance_m(lat, lng, next.0, next.1);
+            (radius_m > 0.0).then_some(Selection::Circle { lat, lng, radius_m })
+        }
+    }
+}
+
+fn mouse_lat_lng(event: &JsValue) -> Option<(f64, f64)> {
+    let latlng: LatLng = js_sys::Reflect::get(event, &"latlng".into())
+        .ok()
+        .filter(JsValue::is_object)?
+        .unchecked_into();
+    Some((latlng.lat(), latlng.lng()))
+}
+
+/// Region totals with the class breaks of their values.
+#[derive(PartialEq)]
+struct RegionClasses {
+    summary: RegionSummary,
+    /// Over regions with a non-zero value; the rest are only outlined.
+    breaks: Vec<f64>,
+}
+
+fn class_color(class: usize, classes: usize) -> String {
+    density_color((class + 1) as f64 / classes as f64)
+}
+
+fn region_polygon(
+    boundaries: &Boundaries,
+    total: &RegionTotal,
+    weight: DensityWeight,
+    breaks: &[f64],
+) -> Option<Polygon> {
+    let feature = boundaries.features.get(total.feature?)?;
+    let value = total.value(weight);
+
+    let options = js_sys::Object::new();
+    set_option(&options, "color", &"#555555".into());
+    set_option(&options, "weight", &1.into());
+    set_option(&options, "opacity", &0.7.into());
+    if value > 0.0 {
+        let color = class_color(class_index(value, breaks), breaks.len());
+        set_option(&options, "fillColor", &color.into());
+        set_option(&options, "fillOpacity", &0.6.into());
+    } else {
+        set_option(&options, "fillOpacity", &0.0.into());
+    }
+
+    // Holes and extra parts are further rings; the even-odd fill sorts them out
+    let rings: js_sys::Array = feature
+        .rings
+        .iter()
+        .map(|ring| lat_lng_path(ring))
+        .collect();
+    let polygon = leaflet_polygon(&rings.into(), &options.into());
+    polygon.bindPopup(&format!(
+        "<strong>{}</strong><br>{} events, {} fatalities",
+        escape_html(&total.name),
+        total.count,
+        total.fatalities
+    ));
+    Some(polygon)
+}
+
+// Click handler shared by every marker in the group; only cluster markers carry bounds
+fn zoom_to_cluster(map: &LeafletMap, event: &JsValue) {
+    let get = |target: &JsValue, key: &str| {
+        js_sys::Reflect::get(target, &key.into())
+            .ok()
+            .filter(|value| !value.is_undefined() && !value.is_null())
+    };
+
+    let bounds = get(event, "propagatedFrom")
+        .or_else(|| get(event, "layer"))
+        .and_then(|layer| get(&layer, "options"))
+        .and_then(|options| get(&options, "clusterBounds"));
+
+    if let Some(bounds) = bounds {
+        let padding = FIT_BOUNDS_PADDING_PX;
+        let fit_options = js_sys::Object::new();
+        set_option(
+            &fit_options,
+            "padding",
+            &js_sys::Array::of2(&padding.into(), &padding.into()).into(),
+        );
+        map.fitBounds(&bounds, &fit_options.into());
+    }
 }
 
 #[function_component(MapDisplay)]
 pub fn map_display(props: &MapDisplayProps) -> Html {
     let map_ref = use_node_ref();
-    let map_instance = use_state(|| Option::<LeafletMap>::None);
-    let markers_layer = use_state(|| Option::<LayerGroup>::None);
+    let handles = use_mut_ref(|| Option::<MapHandles>::None);
+    let map_ready = use_state(|| false);
+    let zoom = use_state(|| DEFAULT_MAP_ZOOM);
+    let clustering = use_state(|| true);
+    let show_markers = use_state(|| true);
+    let show_density = use_state(|| false);
+    let show_legend = use_state(|| true);
+    let draw_tool = use_state(|| Option::<DrawTool>::None);
+    let show_regions = use_state(|| false);
+    let region_level = use_state(RegionLevel::default);
+    let region_weight = use_state(|| DensityWeight::Count);
+    let region_breaks = use_state(ClassBreaks::default);
+    let boundaries = use_state(|| Option::<Rc<Boundaries>>::None);
+    let notifications = use_context::<NotificationContext>();
+    let density_weight = use_state(|| DensityWeight::Count);
+    let date_window = use_state(|| Option::<DateWindow>::None);
+    // Set by each new fetch; the next events to arrive reframe the map
+    let fit_pending = use_mut_ref(|| false);
+
+    let visible_events = use_memo(
+        (props.events.clone(), *date_window),
+        |(events, date_window)| {
+            events.as_ref().map(|events| match date_window {
+                Some(window) => events
+                    .iter()
+                    .filter(|event| {
+                        parse_day(&event.event_date).is_some_and(|day| window.contains(day))
+                    })
+                    .cloned()
+                    .collect::<Vec<_>>(),
+                None => events.clone(),
+            })
+        },
+    );
+
+    // Event types on the map, most frequent first, for the legend
+    let event_type_counts = use_memo(visible_events.clone(), |events| {
+        let mut counts = BTreeMap::<String, usize>::new();
+        for event in events.as_ref().iter().flatten() {
+            *counts.entry(event.event_type.clone()).or_default() += 1;
+        }
+        let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+        counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        counts
+    });
+
+    let region_classes = use_memo(
+        (
+            visible_events.clone(),
+            *show_regions,
+            *region_level,
+            *region_weight,
+            *region_breaks,
+            (*boundaries).clone(),
+        ),
+        |(events, show_regions, level, weight, method, boundaries)| {
+            let events = events.as_ref().as_ref().filter(|_| *show_regions)?;
+            let summary = region_totals(events, *level, boundaries.as_deref());
+            let values: Vec<f64> = summary
+                .totals
+                .iter()
+                .map(|total| total.value(*weight))
+                .filter(|value| *value > 0.0)
+                .collect();
+            let breaks = class_breaks(&values, *method, CHOROPLETH_CLASSES);
+            Some(RegionClasses { summary, breaks })
+        },
+    );
 
     // Initialize map
     {
         let map_ref = map_ref.clone();
-        let map_instance = map_instance.clone();
+        let handles = handles.clone();
+        let map_ready = map_ready.clone();
+        let zoom = zoom.clone();
+        let initial_view = props.initial_view;
+        let on_view_change = props.on_view_change.clone();
+        let on_basemap_change = props.on_basemap_change.clone();
+        let on_selection_change = props.on_selection_change.clone();
+        let draw_tool = draw_tool.clone();
 
         use_effect_with((), move |_| {
             if let Some(map_element) = map_ref.cast::<HtmlElement>() {
                 map_element.set_id("leaflet-map");
 
                 let map_options = js_sys::Object::new();
-                js_sys::Reflect::set(&map_options, &"zoomControl".into(), &true.into()).unwrap();
-                js_sys::Reflect::set(&map_options, &"attributionControl".into(), &true.into())
-                    .unwrap();
+                set_option(&map_options, "zoomControl", &true.into());
+                set_option(&map_options, "attributionControl", &true.into());
 
                 let leaflet_map = leaflet_map("leaflet-map", &map_options.into());
+                match initial_view {
+                    Some(view) => leaflet_map.setView(&lat_lng(view.lat, view.lng), view.zoom),
+                    None => {
+                        let (lat, lng) = DEFAULT_MAP_CENTER;
+                        leaflet_map.setView(&lat_lng(lat, lng), DEFAULT_MAP_ZOOM)
+                    }
+                };
+                zoom.set(leaflet_map.getZoom());
 
-                let latlng = js_sys::Array::new();
-                latlng.push(&33.8869.into());
-                latlng.push(&35.5131.into());
-                leaflet_map.setView(&latlng.into(), 7.0);
-
-                let tile_options = js_sys::Object::new();
-                js_sys::Reflect::set(
-                    &tile_options,
-                    &"attribution".into(),
-                    &"Tiles &copy; Esri &mdash; Source: Esri, i-cubed, USDA, USGS, AEX, GeoEye, Getmapping, Aerogrid, IGN, IGP, UPR-EGP, and the GIS User Community".into()
-                ).unwrap();
-                js_sys::Reflect::set(&tile_options, &"maxZoom".into(), &18.into()).unwrap();
-
-                let tile_layer = tile_layer(
-                    "https://server.arcgisonline.com/ArcGIS/rest/services/World_Imagery/MapServer/tile/{z}/{y}/{x}",
-                    &tile_options.into(),
-                );
-                tile_layer.addTo(&leaflet_map);
+                // Layers are added by the basemap effect below; the control
+                // only needs to know about them
+                let basemaps: Vec<(Basemap, Layer)> = available_basemaps()
+                    .into_iter()
+                    .map(|basemap| (basemap, basemap_layer(basemap)))
+                    .collect();
+                let base_layers = js_sys::Object::new();
+                for (basemap, layer) in &basemaps {
+                    set_option(&base_layers, basemap.label(), layer);
+                }
+                layers_control(
+                    &base_layers.into(),
+                    &js_sys::Object::new().into(),
+                    &js_sys::Object::new().into(),
+                )
+                .addTo(&leaflet_map);
+
+                // Added first so regions and hexagons stay beneath the markers
+                let regions = feature_group();
+                regions.addTo(&leaflet_map);
 
-                let markers = layer_group();
+                let density = feature_group();
+                density.addTo(&leaflet_map);
+
+                let markers = feature_group();
                 markers.addTo(&leaflet_map);
 
-                map_instance.set(Some(leaflet_map));
-                console::log_with_context!(
-                    "MAP_DISPLAY",
-                    "Leaflet satellite map initialized successfully"
+                let selection = feature_group();
+                selection.addTo(&leaflet_map);
+
+                let sketch = feature_group();
+                sketch.addTo(&leaflet_map);
+
+                let on_zoom_end = {
+                    let map = leaflet_map.clone();
+                    Closure::<dyn FnMut(JsValue)>::new(move |_| zoom.set(map.getZoom()))
+                };
+                leaflet_map.on("zoomend", on_zoom_end.as_ref().unchecked_ref());
+
+                let on_move_end = {
+                    let map = leaflet_map.clone();
+                    Closure::<dyn FnMut(JsValue)>::new(move |_| {
+                        let center = map.getCenter();
+                        on_view_change.emit(MapView {
+                            lat: center.lat(),
+                            lng: center.lng(),
+                            zoom: map.getZoom(),
+                        });
+                    })
+                };
+                leaflet_map.on("moveend", on_move_end.as_ref().unchecked_ref());
+
+                let basemap = Rc::new(Cell::new(None));
+                let on_base_layer_change = {
+                    let basemap = basemap.clone();
+                    Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+                        let name = js_sys::Reflect::get(&event, &"name".into())
+                            .ok()
+                            .and_then(|name| name.as_string());
+                        if let Some(selected) = Basemap::ALL
+                            .into_iter()
+                            .find(|basemap| Some(basemap.label()) == name.as_deref())
+                        {
+                            basemap.set(Some(selected));
+                            on_basemap_change.emit(selected);
+                        }
+                    })
+                };
+                leaflet_map.on(
+                    "baselayerchange",
+                    on_base_layer_change.as_ref().unchecked_ref(),
                 );
+
+                let on_marker_click = {
+                    let map = leaflet_map.clone();
+                    Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+                        zoom_to_cluster(&map, &event)
+                    })
+                };
+                markers.on("click", on_marker_click.as_ref().unchecked_ref());
+
+                // Drawing: clicks place points, the cursor previews the next one
+                let drawing = Rc::new(RefCell::new(Drawing::default()));
+                let finish = {
+                    let draw_tool = draw_tool.clone();
+                    move |selection: Selection| {
+                        on_selection_change.emit(Some(selection));
+                        draw_tool.set(None);
+                    }
+                };
+
+                let on_draw_click = {
+                    let drawing = drawing.clone();
+                    let finish = finish.clone();
+                    Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+                        let Some(point) = mouse_lat_lng(&event) else {
+                            return;
+                        };
+                        let finished = {
+                            let mut drawing = drawing.borrow_mut();
+                            match drawing.tool {
+                                Some(DrawTool::Polygon) => {
+                                    // The clicks of a double-click land on one point
+                                    if drawing.points.last() != Some(&point) {
+                                        drawing.points.push(point);
+                                    }
+                                    None
+                                }
+                                Some(tool) if !drawing.points.is_empty() => {
+                                    sketch_selection(tool, &drawing.points, point)
+                                }
+                                Some(_) => {
+                                    drawing.points.push(point);
+                                    None
+                                }
+                                None => None,
+                            }
+                        };
+                        if let Some(selection) = finished {
+                            finish(selection);
+                        }
+                    })
+                };
+                leaflet_map.on("click", on_draw_click.as_ref().unchecked_ref());
+
+                let on_draw_double_click = {
+                    let drawing = drawing.clone();
+                    Closure::<dyn FnMut(JsValue)>::new(move |_: JsValue| {
+                        let finished = {
+                            let drawing = drawing.borrow();
+                            match (drawing.tool, drawing.points.last()) {
+                                (Some(DrawTool::Polygon), Some(&last)) => {
+                                    sketch_selection(DrawTool::Polygon, &drawing.points, last)
+                                }
+                                _ => None,
+                            }
+                        };
+                        if let Some(selection) = finished {
+                            finish(selection);
+                        }
+                    })
+                };
+                leaflet_map.on("dblclick", on_draw_double_click.as_ref().unchecked_ref());
+
+                let on_draw_move = {
+                    let drawing = drawing.clone();
+                    let sketch = sketch.clone();
+                    Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+                        let drawing = drawing.borrow();
+                        if let (Some(tool), Some(point)) = (drawing.tool, mouse_lat_lng(&event)) {
+                            sketch.clearLayers();
+                            let shape = sketch_selection(tool, &drawing.points, point);
+                            if let Some(shape) = shape {
+                                sketch.addLayer(&selection_layer(&shape, true));
+                            }
+                        }
+                    })
+                };
+                leaflet_map.on("mousemove", on_draw_move.as_ref().unchecked_ref());
+
+                *handles.borrow_mut() = Some(MapHandles {
+                    map: leaflet_map,
+                    basemaps,
+                    basemap,
+                    regions,
+                    density,
+                    markers,
+                    drawing,
+                    sketch,
+                    selection,
+                    _listeners: vec![
+                        on_zoom_end,
+                        on_move_end,
+                        on_base_layer_change,
+                        on_marker_click,
+                        on_draw_click,
+                        on_draw_double_click,
+                        on_draw_move,
+                    ],
+                });
+                map_ready.set(true);
+
+                console::log_with_context!("MAP_DISPLAY", "Leaflet map initialized successfully");
             } else {
                 console::error_with_context!("MAP_DISPLAY", "Failed to get map element reference");
             }
 
+            move || {
+                // Remove the map before its listeners are dropped
+                if let Some(handles) = handles.borrow_mut().take() {
+                    handles.map.remove();
+                }
+            }
+        });
+    }
+
+    // Show the preferred basemap, unless the layer control already did
+    {
+        let handles = handles.clone();
+
+        use_effect_with((props.basemap, *map_ready), move |(basemap, _)| {
+            if let Some(handles) = handles.borrow().as_ref() {
+                let shown = handles.basemap.get();
+                let layer = |wanted: Basemap| {
+                    handles
+                        .basemaps
+                        .iter()
+                        .find(|(basemap, _)| *basemap == wanted)
+                        .map(|(_, layer)| layer)
+                };
+
+                // Falls back to the default when offline tiles aren't served
+                let next = layer(*basemap).map(|_| *basemap).unwrap_or_default();
+                if shown != Some(next) {
+                    if let Some(previous) = shown.and_then(layer) {
+                        previous.remove();
+                    }
+                    handles.basemap.set(Some(next));
+                    if let Some(layer) = layer(next) {
+                        layer.addTo(&handles.map);
+                    }
+                }
+            }
+
             || ()
         });
     }
 
-    // Update markers when events change
+    // Start (or abandon) drawing a shape
     {
-        let map_instance = map_instance.clone();
-        let markers_layer = markers_layer.clone();
-        let events = props.events.clone();
+        let handles = handles.clone();
+
+        use_effect_with((*draw_tool, *map_ready), move |(tool, _)| {
+            if let Some(handles) = handles.borrow().as_ref() {
+                *handles.drawing.borrow_mut() = Drawing {
+                    tool: *tool,
+                    points: Vec::new(),
+                };
+                handles.sketch.clearLayers();
 
-        use_effect_with(events.clone(), move |events| {
-            if let (Some(map), Some(events)) = ((*map_instance).as_ref(), events.as_ref()) {
-                if let Some(markers) = (*markers_layer).as_ref() {
-                    markers.clearLayers();
+                // Double-clicks finish polygons rather than zooming in
+                let double_click_zoom = handles.map.doubleClickZoom();
+                if tool.is_some() {
+                    double_click_zoom.disable();
                 } else {
-                    let new_markers = layer_group();
-                    new_markers.addTo(map);
-                    markers_layer.set(Some(new_markers));
+                    double_click_zoom.enable();
                 }
+            }
 
-                if let Some(markers) = (*markers_layer).as_ref() {
-                    for event in events {
-                        if let (Some(lat), Some(lng)) = (event.latitude, event.longitude) {
-                            let latlng = js_sys::Array::new();
-                            latlng.push(&lat.into());
-                            latlng.push(&lng.into());
+            || ()
+        });
+    }
 
-                            let marker_options = js_sys::Object::new();
-                            let marker = leaflet_marker(&latlng.into(), &marker_options.into());
+    // Outline the current selection
+    {
+        let handles = handles.clone();
 
-                            let popup_content = generate_popup_content(event);
-                            marker.bindPopup(&popup_content);
-                            markers.addLayer(&marker);
-                        }
+        use_effect_with(
+            (props.selection.clone(), *map_ready),
+            move |(selection, _)| {
+                if let Some(handles) = handles.borrow().as_ref() {
+                    handles.selection.clearLayers();
+                    if let Some(selection) = selection {
+                        handles
+                            .selection
+                            .addLayer(&selection_layer(selection, false));
                     }
+                }
+
+                || ()
+            },
+        );
+    }
+
+    // A new fetch has started (or the map opened on an earlier one)
+    {
+        let fit_pending = fit_pending.clone();
+
+        use_effect_with(props.params.clone(), move |params| {
+            if params.is_some() {
+                *fit_pending.borrow_mut() = true;
+            }
+
+            || ()
+        });
+    }
+
+    // Frame the results of that fetch once they arrive
+    {
+        let handles = handles.clone();
+        let fit_pending = fit_pending.clone();
+        let params = props.params.clone();
 
-                    console::log_with_context!(
-                        "MAP_DISPLAY",
-                        "Added {} markers to map",
-                        events.len()
-                    );
+        use_effect_with((props.events.clone(), *map_ready), move |(events, _)| {
+            if let (Some(handles), Some(events)) = (handles.borrow().as_ref(), events) {
+                if fit_pending.replace(false) {
+                    handles.map.invalidateSize();
+                    frame_data(&handles.map, Some(events), params.as_ref());
                 }
             }
 
@@ -158,17 +893,538 @@ pub fn map_display(props: &MapDisplayProps) -> Html {
         });
     }
 
+    // Update markers when events, zoom, clustering mode or symbology change
+    {
+        let handles = handles.clone();
+
+        use_effect_with(
+            (
+                visible_events.clone(),
+                *zoom,
+                *clustering,
+                *show_markers,
+                props.symbology.clone(),
+                *map_ready,
+            ),
+            move |(events, zoom, clustering, show_markers, symbology, _)| {
+                if let Some(handles) = handles.borrow().as_ref() {
+                    // The timeline bar may have just appeared and shrunk the map
+                    handles.map.invalidateSize();
+                    handles.markers.clearLayers();
+
+                    if let (true, Some(events)) = (*show_markers, events.as_ref().as_ref()) {
+                        let mut marker_count = 0;
+
+                        if *clustering {
+                            for cluster in clusters_at_zoom(events, *zoom) {
+                                let marker = if cluster.count() == 1 {
+                                    event_marker(&events[cluster.members[0]], symbology)
+                                } else {
+                                    Some(cluster_marker(&cluster, events).into())
+                                };
+
+                                if let Some(marker) = marker {
+                                    handles.markers.addLayer(&marker);
+                                    marker_count += 1;
+                                }
+                            }
+                        } else {
+                            for marker in events
+                                .iter()
+                                .filter_map(|event| event_marker(event, symbology))
+                            {
+                                handles.markers.addLayer(&marker);
+                                marker_count += 1;
+                            }
+                        }
+
+                        console::log_with_context!(
+                            "MAP_DISPLAY",
+                            "Added {} markers for {} events at zoom {}",
+                            marker_count,
+                            events.len(),
+                            zoom
+                        );
+                    }
+                }
+
+                || ()
+            },
+        );
+    }
+
+    // Recompute the density hexagons for the current zoom
+    {
+        let handles = handles.clone();
+
+        use_effect_with(
+            (
+                visible_events.clone(),
+                *zoom,
+                *show_density,
+                *density_weight,
+                *map_ready,
+            ),
+            move |(events, zoom, show_density, density_weight, _)| {
+                if let Some(handles) = handles.borrow().as_ref() {
+                    handles.density.clearLayers();
+
+                    if let (true, Some(events)) = (*show_density, events.as_ref().as_ref()) {
+                        let bins = hex_bin(events, *zoom, DENSITY_HEX_RADIUS_PX, *density_weight);
+                        for bin in &bins {
+                            handles.density.addLayer(&density_polygon(bin));
+                        }
+
+                        console::log_with_context!(
+                            "MAP_DISPLAY",
+                            "Rendered {} density bins at zoom {}",
+                            bins.len(),
+                            zoom
+                        );
+                    }
+                }
+
+                || ()
+            },
+        );
+    }
+
+    // Shade the boundary polygons by their region's total
+    {
+        let handles = handles.clone();
+
+        use_effect_with(
+            (
+                region_classes.clone(),
+                (*boundaries).clone(),
+                *region_weight,
+                *map_ready,
+            ),
+            move |(region_classes, boundaries, weight, _)| {
+                if let Some(handles) = handles.borrow().as_ref() {
+                    handles.regions.clearLayers();
+
+                    if let (Some(classes), Some(boundaries)) =
+                        (region_classes.as_ref().as_ref(), boundaries.as_ref())
+                    {
+                        let polygons: Vec<Polygon> = classes
+                            .summary
+                            .totals
+                            .iter()
+                            .filter_map(|total| {
+                                region_polygon(boundaries, total, *weight, &classes.breaks)
+                            })
+                            .collect();
+                        for polygon in &polygons {
+                            handles.regions.addLayer(polygon);
+                        }
+
+                        console::log_with_context!(
+                            "MAP_DISPLAY",
+                            "Rendered {} regions from {}",
+                            polygons.len(),
+                            boundaries.name
+                        );
+                    }
+                }
+
+                || ()
+            },
+        );
+    }
+
+    let toggle = |state: &UseStateHandle<bool>| {
+        let state = state.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            state.set(input.checked());
+        })
+    };
+
+    let on_density_weight_change = {
+        let density_weight = density_weight.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            density_weight.set(match select.value().as_str() {
+                "fatalities" => DensityWeight::Fatalities,
+                _ => DensityWeight::Count,
+            });
+        })
+    };
+
+    let on_region_level_change = {
+        let region_level = region_level.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            if let Some(level) = RegionLevel::ALL
+                .into_iter()
+                .find(|level| level.label() == select.value())
+            {
+                region_level.set(level);
+            }
+        })
+    };
+
+    let on_region_breaks_change = {
+        let region_breaks = region_breaks.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            if let Some(method) = ClassBreaks::ALL
+                .into_iter()
+                .find(|method| method.label() == select.value())
+            {
+                region_breaks.set(method);
+            }
+        })
+    };
+
+    let on_region_weight_change = {
+        let region_weight = region_weight.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            region_weight.set(match select.value().as_str() {
+                "fatalities" => DensityWeight::Fatalities,
+                _ => DensityWeight::Count,
+            });
+        })
+    };
+
+    let on_boundaries_load = {
+        let boundaries = boundaries.clone();
+        let notifications = notifications.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            let Some(file) = input.files().and_then(|files| files.get(0)) else {
+                return;
+            };
+            // Allow loading the same file again later
+            input.set_value("");
+
+            let boundaries = boundaries.clone();
+            let notifications = notifications.clone();
+            spawn_local(async move {
+                let name = file.name();
+                let loaded = ExportService::read_file(file)
+                    .await
+                    .and_then(|json| Boundaries::from_geojson(&name, &json));
+                match loaded {
+                    Ok(loaded) => {
+                        console::log_with_context!(
+                            "MAP_DISPLAY",
+                            "Loaded {} boundary polygons from {}",
+                            loaded.features.len(),
+                            name
+                        );
+                        boundaries.set(Some(Rc::new(loaded)));
+                    }
+                    Err(e) => {
+                        console::error_with_context!(
+                            "MAP_DISPLAY",
+                            "Failed to load boundaries: {}",
+                            e
+                        );
+                        if let Some(notifications) = notifications.as_ref() {
+                            report_error(notifications, "Loading boundaries", e);
+                        }
+                    }
+                }
+            });
+        })
+    };
+
+    let clear_boundaries = {
+        let boundaries = boundaries.clone();
+        Callback::from(move |_: MouseEvent| boundaries.set(None))
+    };
+
+    let region_legend = region_classes.as_ref().as_ref().map(|classes| {
+        let weight = *region_weight;
+        let summary = &classes.summary;
+
+        let mut top: Vec<(String, f64)> = summary
+            .totals
+            .iter()
+            .map(|total| (total.name.clone(), total.value(weight)))
+            .filter(|(_, value)| *value > 0.0)
+            .collect();
+        top.sort_by(|a, b| b.1.total_cmp(&a.1));
+        top.truncate(5);
+
+        let mut notes = Vec::new();
+        match (boundaries.as_ref(), *region_level) {
+            (None, RegionLevel::Polygons) => {
+                notes.push("Load a boundary GeoJSON to group events by polygon".to_string())
+            }
+            (None, _) => notes.push("Load a boundary GeoJSON to map these regions".to_string()),
+            (Some(boundaries), _) => {
+                let unmatched = summary.unmatched();
+                if unmatched > 0 {
+                    notes.push(format!(
+                        "{} regions not found in {}",
+                        unmatched, boundaries.name
+                    ));
+                }
+                notes.push(
+                    match weight {
+                        DensityWeight::Count => "Outlined regions have no events",
+                        DensityWeight::Fatalities => "Outlined regions have no fatalities",
+                    }
+                    .to_string(),
+                );
+            }
+        }
+        if summary.unassigned > 0 {
+            notes.push(format!("{} events without a region", summary.unassigned));
+        }
+
+        html! {
+            <RegionLegend
+                title={format!(
+                    "{} per {} ({})",
+                    match weight {
+                        DensityWeight::Count => "Events",
+                        DensityWeight::Fatalities => "Fatalities",
+                    },
+                    match *region_level {
+                        RegionLevel::Polygons => "polygon",
+                        level => level.label(),
+                    },
+                    region_breaks.label()
+                )}
+                classes={classes
+                    .breaks
+                    .iter()
+                    .enumerate()
+                    .map(|(class, upper)| (*upper, class_color(class, classes.breaks.len())))
+                    .collect::<Vec<_>>()}
+                min={summary
+                    .totals
+                    .iter()
+                    .map(|total| total.value(weight))
+                    .filter(|value| *value > 0.0)
+                    .fold(f64::INFINITY, f64::min)}
+                {top}
+                {notes}
+            />
+        }
+    });
+
+    let on_date_window_change = {
+        let date_window = date_window.clone();
+        Callback::from(move |window: Option<DateWindow>| date_window.set(window))
+    };
+
+    let reset_view = {
+        let handles = handles.clone();
+        let events = props.events.clone();
+        let params = props.params.clone();
+        Callback::from(move |_: MouseEvent| {
+            if let Some(handles) = handles.borrow().as_ref() {
+                frame_data(&handles.map, events.as_deref(), params.as_ref());
+            }
+        })
+    };
+
+    let select_tool = |tool: DrawTool| {
+        let draw_tool = draw_tool.clone();
+        Callback::from(move |_: MouseEvent| {
+            draw_tool.set((*draw_tool != Some(tool)).then_some(tool));
+        })
+    };
+
+    let clear_selection = {
+        let on_selection_change = props.on_selection_change.clone();
+        Callback::from(move |_: MouseEvent| on_selection_change.emit(None))
+    };
+
+    let fetch_within_selection = {
+        let on_query_area_change = props.on_query_area_change.clone();
+        let selection = props.selection.clone();
+        Callback::from(move |_: MouseEvent| {
+            if let Some(selection) = &selection {
+                on_query_area_change.emit(Some(selection.bounds()));
+            }
+        })
+    };
+
+    let range = props
+        .events
+        .as_ref()
+        .and_then(|events| timeline_range(props.params.as_ref(), events));
+
     html! {
         <>
-            <div class="map-content">
+            <div class={classes!("map-content", draw_tool.is_some().then_some("drawing"))}>
                 <div ref={map_ref} class="leaflet-map-wrapper">
                 </div>
+                <button class="map-reset-view" onclick={reset_view} title="Fit the map to the loaded data">
+                    {"Reset view"}
+                </button>
+                <div class="map-draw-tools">
+                    {for DrawTool::ALL.into_iter().map(|tool| html! {
+                        <button
+                            class={classes!("map-draw-button", (*draw_tool == Some(tool)).then_some("active"))}
+                            onclick={select_tool(tool)}
+                            title={format!("Select events in a {}", tool.label().to_lowercase())}
+                        >
+                            {tool.label()}
+                        </button>
+                    })}
+                    if props.selection.is_some() {
+                        <button class="map-draw-button" onclick={clear_selection}>
+                            {"Clear"}
+                        </button>
+                        <button
+                            class="map-draw-button"
+                            onclick={fetch_within_selection}
+                            title="Limit the next fetch to the selection's bounding box"
+                        >
+                            {"Fetch within"}
+                        </button>
+                    }
+                </div>
+                if let Some(tool) = *draw_tool {
+                    <div class="map-draw-hint">{tool.hint()}</div>
+                }
+                <div class="map-legends">
+                    if *show_markers && *show_legend && !event_type_counts.is_empty() {
+                        <MapLegend
+                            event_types={(*event_type_counts).clone()}
+                            symbology={props.symbology.clone()}
+                        />
+                    }
+                    {region_legend.unwrap_or_default()}
+                </div>
+                if let Some((start_day, end_day)) = range {
+                    <Timeline {start_day} {end_day} on_change={on_date_window_change} />
+                }
             </div>
 
             if let Some(events) = &props.events {
                 <div class="map-stats">
-                    <p><strong>{"Events on Map: "}</strong>{events.len()}</p>
+                    <p>
+                        <strong>{"Events on Map: "}</strong>
+                        {match (*visible_events).as_ref() {
+                            Some(visible) if visible.len() != events.len() => {
+                                format!("{} / {}", visible.len(), events.len())
+                            }
+                            _ => events.len().to_string(),
+                        }}
+                    </p>
+                    if !props.search.trim().is_empty() {
+                        <p class="map-search-note">
+                            {format!("Matching \"{}\"", props.search.trim())}
+                        </p>
+                    }
+                    if let Some(selection) = &props.selection {
+                        <p class="map-search-note">
+                            {format!("Within {}", selection.describe())}
+                        </p>
+                    }
+                    <label class="map-toggle">
+                        <input
+                            type="checkbox"
+                            checked={*show_markers}
+                            onchange={toggle(&show_markers)}
+                        />
+                        {"Show markers"}
+                    </label>
+                    <label class="map-toggle">
+                        <input
+                            type="checkbox"
+                            checked={*clustering}
+                            disabled={!*show_markers}
+                            onchange={toggle(&clustering)}
+                        />
+                        {"Cluster markers"}
+                    </label>
+                    <label class="map-toggle">
+                        <input
+                            type="checkbox"
+                            checked={*show_legend}
+                            disabled={!*show_markers}
+                            onchange={toggle(&show_legend)}
+                        />
+                        {"Legend"}
+                    </label>
+                    <label class="map-toggle">
+                        <input
+                            type="checkbox"
+                            checked={*show_density}
+                            onchange={toggle(&show_density)}
+                        />
+                        {"Density layer"}
+                    </label>
+                    if *show_density {
+                        <select class="map-select" onchange={on_density_weight_change}>
+                            <option value="count" selected={*density_weight == DensityWeight::Count}>
+                                {"Weight: event count"}
+                            </option>
+                            <option value="fatalities" selected={*density_weight == DensityWeight::Fatalities}>
+                                {"Weight: fatalities"}
+                            </option>
+                        </select>
+                    }
+                    <label class="map-toggle">
+                        <input
+                            type="checkbox"
+                            checked={*show_regions}
+                            onchange={toggle(&show_regions)}
+                        />
+                        {"Region totals"}
+                    </label>
+                    if *show_regions {
+                        <select class="map-select" onchange={on_region_level_change}>
+                            {for RegionLevel::ALL.into_iter().map(|level| html! {
+                                <option value={level.label()} selected={*region_level == level}>
+                                    {format!("By {}", level.label().to_lowercase())}
+                                </option>
+                            })}
+                        </select>
+                        <select class="map-select" onchange={on_region_weight_change}>
+                            <option value="count" selected={*region_weight == DensityWeight::Count}>
+                                {"Total: event count"}
+                            </option>
+                            <option value="fatalities" selected={*region_weight == DensityWeight::Fatalities}>
+                                {"Total: fatalities"}
+                            </option>
+                        </select>
+                        <select class="map-select" onchange={on_region_breaks_change}>
+                            {for ClassBreaks::ALL.into_iter().map(|method| html! {
+                                <option value={method.label()} selected={*region_breaks == method}>
+                                    {method.label()}
+                                </option>
+                            })}
+                        </select>
+                        <div class="map-boundaries">
+                            <span title={boundaries.as_ref().map(|b| b.name.clone())}>
+                                {match boundaries.as_ref() {
+                                    Some(boundaries) => format!(
+                                        "{} ({} polygons)",
+                                        boundaries.name,
+                                        boundaries.features.len()
+                                    ),
+                                    None => "No boundaries loaded".to_string(),
+                                }}
+                            </span>
+                            <label class="button map-boundaries-load">
+                                {"Load GeoJSON..."}
+                                <input
+                                    type="file"
+                                    accept=".geojson,.json,application/geo+json,application/json"
+                                    onchange={on_boundaries_load}
+                                />
+                            </label>
+                            if boundaries.is_some() {
+                                <button class="button" onclick={clear_boundaries}>{"Clear"}</button>
+                            }
+                        </div>
+                    }
                     <p style="font-size: 0.9em; color: #888888;">{"Click markers for event details"}</p>
+                    <ExportMenu
+                        events={Rc::new((*visible_events).clone().unwrap_or_default())}
+                        params={props.params.clone()}
+                    />
                 </div>
             }
         </>
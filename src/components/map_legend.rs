@@ -0,0 +1,123 @@
+use crate::types::{fatality_radius, precision_opacity, Symbology};
+use yew::prelude::*;
+
+// Fatality counts drawn as sample sizes
+const SIZE_SAMPLES: [u32; 3] = [0, 10, 100];
+
+#[derive(Properties, PartialEq)]
+pub struct MapLegendProps {
+    /// Event types on the map with their counts, in display order.
+    pub event_types: Vec<(String, usize)>,
+    pub symbology: Symbology,
+}
+
+fn swatch(color: &str, radius: f64, opacity: f64) -> Html {
+    let diameter = radius * 2.0;
+    html! {
+        <span
+            class="map-legend-swatch"
+            style={format!(
+                "width: {0}px; height: {0}px; background: {1}; opacity: {2};",
+                diameter, color, opacity
+            )}
+        />
+    }
+}
+
+/// Key for the event markers: what the colors, sizes and opacities mean.
+#[function_component(MapLegend)]
+pub fn map_legend(props: &MapLegendProps) -> Html {
+    let neutral = "var(--text-color)";
+
+    html! {
+        <div class="map-legend">
+            <div class="map-legend-group">
+                {for props.event_types.iter().map(|(event_type, count)| html! {
+                    <div class="map-legend-row">
+                        {swatch(props.symbology.color(event_type), 6.0, 0.9)}
+                        <span class="map-legend-label">{event_type}</span>
+                        <span class="map-legend-count">{count}</span>
+                    </div>
+                })}
+            </div>
+            <div class="map-legend-group">
+                <div class="map-legend-title">{"Fatalities"}</div>
+                <div class="map-legend-sizes">
+                    {for SIZE_SAMPLES.iter().map(|&fatalities| html! {
+                        <div class="map-legend-size">
+                            {swatch(neutral, fatality_radius(fatalities), 0.6)}
+                            <span>{fatalities}</span>
+                        </div>
+                    })}
+                </div>
+            </div>
+            <div class="map-legend-group">
+                <div class="map-legend-title">{"Location precision"}</div>
+                {for [(Some(1), "Exact"), (Some(2), "Nearby"), (Some(3), "Region")]
+                    .into_iter()
+                    .map(|(precision, label)| html! {
+                        <div class="map-legend-row">
+                            {swatch(neutral, 6.0, precision_opacity(precision))}
+                            <span class="map-legend-label">{label}</span>
+                        </div>
+                    })}
+            </div>
+        </div>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct RegionLegendProps {
+    pub title: String,
+    /// Upper bound and fill color of each class, ascending.
+    pub classes: Vec<(f64, String)>,
+    /// Smallest value in the first class.
+    pub min: f64,
+    /// Regions with the highest values, with those values.
+    #[prop_or_default]
+    pub top: Vec<(String, f64)>,
+    /// Caveats, such as events that fell outside every region.
+    #[prop_or_default]
+    pub notes: Vec<String>,
+}
+
+/// Key for the region totals layer: the class breaks and their colors.
+#[function_component(RegionLegend)]
+pub fn region_legend(props: &RegionLegendProps) -> Html {
+    let lower_bounds =
+        std::iter::once(props.min).chain(props.classes.iter().map(|(upper, _)| *upper));
+
+    html! {
+        <div class="map-legend">
+            <div class="map-legend-group">
+                <div class="map-legend-title">{&props.title}</div>
+                {for props.classes.iter().zip(lower_bounds).map(|((upper, color), lower)| html! {
+                    <div class="map-legend-row">
+                        <span class="map-legend-class" style={format!("background: {};", color)} />
+                        <span class="map-legend-label">
+                            {if (upper - lower).abs() < 0.5 {
+                                format!("{:.0}", upper)
+                            } else {
+                                format!("{:.0} – {:.0}", lower, upper)
+                            }}
+                        </span>
+                    </div>
+                })}
+            </div>
+            if !props.top.is_empty() {
+                <div class="map-legend-group">
+                    <div class="map-legend-title">{"Top regions"}</div>
+                    {for props.top.iter().map(|(name, value)| html! {
+                        <div class="map-legend-row">
+                            <span class="map-legend-label" title={name.clone()}>{name}</span>
+                            <span class="map-legend-count">{format!("{:.0}", value)}</span>
+                        </div>
+                    })}
+                </div>
+            }
+            {for props.notes.iter().map(|note| html! {
+                <p class="map-legend-note">{note}</p>
+            })}
+        </div>
+    }
+}
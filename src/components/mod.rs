@@ -1,20 +1,39 @@
+pub mod chip_picker;
 pub mod event_card;
 pub mod events_list;
+pub mod events_table;
+pub mod export_menu;
+pub mod leaflet;
+pub mod log_viewer;
 pub mod map_data;
 pub mod map_display;
+pub mod map_legend;
+pub mod notifications;
 pub mod preferences;
 pub mod response_display;
 pub mod response_info;
+pub mod saved_query_picker;
+pub mod timeline;
 pub mod titlebar;
 
 // Re-export components for easier access
-pub use event_card::{generate_popup_content, EventCard};
+pub use chip_picker::ChipPicker;
+pub use event_card::{generate_cluster_popup_content, generate_popup_content, EventCard};
 pub use events_list::EventsList;
+pub use events_table::EventsTable;
+pub use export_menu::ExportMenu;
+pub use log_viewer::LogViewer;
 pub use map_data::MapData;
 pub use map_display::MapDisplay;
+pub use map_legend::{MapLegend, RegionLegend};
+pub use notifications::{
+    report_error, report_notice, ErrorHistory, NotificationContext, NotificationProvider,
+};
 pub use preferences::Preferences;
 pub use response_display::ResponseDisplay;
 pub use response_info::ResponseInfo;
+pub use saved_query_picker::SavedQueryPicker;
+pub use timeline::{DateWindow, Timeline};
 pub use titlebar::Titlebar;
 
 // Re-export commonly used types from the types module
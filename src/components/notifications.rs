@@ -0,0 +1,276 @@
+use crate::config::{MAX_ERROR_HISTORY, TOAST_DURATION_MS};
+use crate::errors::{AppError, ErrorSeverity};
+use gloo_timers::callback::Timeout;
+use std::rc::Rc;
+use web_sys::MouseEvent;
+use yew::prelude::*;
+
+/// An error or notice reported during this session.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Notification {
+    pub id: u32,
+    /// What the app was doing, e.g. "Fetching events".
+    pub source: String,
+    pub message: String,
+    pub hint: Option<&'static str>,
+    pub severity: ErrorSeverity,
+    /// Milliseconds since the epoch.
+    pub time: f64,
+}
+
+impl Notification {
+    fn time_label(&self) -> String {
+        js_sys::Date::new(&self.time.into())
+            .to_locale_time_string("default")
+            .into()
+    }
+}
+
+#[derive(Clone, PartialEq, Default)]
+pub struct Notifications {
+    /// Toasts currently on screen.
+    pub active: Vec<Notification>,
+    /// Every error this session, newest first. Notices are not kept.
+    pub history: Vec<Notification>,
+    next_id: u32,
+}
+
+pub enum NotificationAction {
+    Report {
+        source: String,
+        error: AppError,
+    },
+    /// Something worth telling the user that is not an error.
+    Notice {
+        source: String,
+        message: String,
+        severity: ErrorSeverity,
+    },
+    Dismiss(u32),
+    ClearHistory,
+}
+
+impl Notifications {
+    fn notification(
+        &mut self,
+        source: String,
+        message: String,
+        hint: Option<&'static str>,
+        severity: ErrorSeverity,
+    ) -> Notification {
+        let id = self.next_id;
+        self.next_id += 1;
+        Notification {
+            id,
+            source,
+            message,
+            hint,
+            severity,
+            time: js_sys::Date::now(),
+        }
+    }
+
+    // The same message repeated replaces its toast instead of stacking
+    fn show(&mut self, notification: Notification) {
+        self.active.retain(|active| {
+            active.source != notification.source || active.message != notification.message
+        });
+        self.active.push(notification);
+    }
+}
+
+impl Reducible for Notifications {
+    type Action = NotificationAction;
+
+    fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
+        let mut next = (*self).clone();
+
+        match action {
+            NotificationAction::Report { source, error } => {
+                let notification =
+                    next.notification(source, error.to_string(), error.hint(), error.severity());
+                next.show(notification.clone());
+                next.history.insert(0, notification);
+                next.history.truncate(MAX_ERROR_HISTORY);
+            }
+            NotificationAction::Notice {
+                source,
+                message,
+                severity,
+            } => {
+                let notification = next.notification(source, message, None, severity);
+                next.show(notification);
+            }
+            NotificationAction::Dismiss(id) => {
+                next.active.retain(|notification| notification.id != id);
+            }
+            NotificationAction::ClearHistory => {
+                next.history.clear();
+            }
+        }
+
+        Rc::new(next)
+    }
+}
+
+/// Shared by every component that reports or lists errors.
+pub type NotificationContext = UseReducerHandle<Notifications>;
+
+/// Reports `error` as a toast and records it in the session history.
+pub fn report_error(context: &NotificationContext, source: &str, error: AppError) {
+    context.dispatch(NotificationAction::Report {
+        source: source.to_string(),
+        error,
+    });
+}
+
+/// Shows `message` as a toast without recording it as an error.
+pub fn report_notice(
+    context: &NotificationContext,
+    source: &str,
+    message: impl Into<String>,
+    severity: ErrorSeverity,
+) {
+    context.dispatch(NotificationAction::Notice {
+        source: source.to_string(),
+        message: message.into(),
+        severity,
+    });
+}
+
+#[derive(Properties, PartialEq)]
+pub struct NotificationProviderProps {
+    pub children: Html,
+}
+
+/// Provides [`NotificationContext`] to `children` and renders the toasts.
+#[function_component(NotificationProvider)]
+pub fn notification_provider(props: &NotificationProviderProps) -> Html {
+    let notifications = use_reducer(Notifications::default);
+
+    html! {
+        <ContextProvider<NotificationContext> context={notifications.clone()}>
+            {props.children.clone()}
+            <div class="toast-stack">
+                {for notifications.active.iter().map(|notification| html! {
+                    <Toast
+                        key={notification.id}
+                        notification={notification.clone()}
+                        on_dismiss={
+                            let notifications = notifications.clone();
+                            Callback::from(move |id| {
+                                notifications.dispatch(NotificationAction::Dismiss(id))
+                            })
+                        }
+                    />
+                })}
+            </div>
+        </ContextProvider<NotificationContext>>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+struct ToastProps {
+    notification: Notification,
+    on_dismiss: Callback<u32>,
+}
+
+#[function_component(Toast)]
+fn toast(props: &ToastProps) -> Html {
+    let notification = &props.notification;
+
+    // Minor problems fade on their own; errors wait for the user
+    {
+        let on_dismiss = props.on_dismiss.clone();
+        let id = notification.id;
+        let severity = notification.severity;
+        use_effect_with(id, move |_| {
+            let timeout = matches!(severity, ErrorSeverity::Info | ErrorSeverity::Warning)
+                .then(|| Timeout::new(TOAST_DURATION_MS, move || on_dismiss.emit(id)));
+            move || drop(timeout)
+        });
+    }
+
+    let dismiss = {
+        let on_dismiss = props.on_dismiss.clone();
+        let id = notification.id;
+        Callback::from(move |_: MouseEvent| on_dismiss.emit(id))
+    };
+
+    html! {
+        <div
+            class={classes!("toast", format!("toast-{}", notification.severity.label()))}
+            role="alert"
+        >
+            <div class="toast-header">
+                <strong>{&notification.source}</strong>
+                <button class="toast-dismiss" onclick={dismiss} title="Dismiss">{"×"}</button>
+            </div>
+            <p class="toast-message">{&notification.message}</p>
+            if let Some(hint) = notification.hint {
+                <p class="toast-hint">{hint}</p>
+            }
+        </div>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct ErrorHistoryProps {
+    pub show: bool,
+    pub on_close: Callback<()>,
+}
+
+/// Every error reported this session, newest first.
+#[function_component(ErrorHistory)]
+pub fn error_history(props: &ErrorHistoryProps) -> Html {
+    let notifications = use_context::<NotificationContext>();
+
+    let close = {
+        let on_close = props.on_close.clone();
+        Callback::from(move |_: MouseEvent| on_close.emit(()))
+    };
+
+    let Some(notifications) = notifications.filter(|_| props.show) else {
+        return html! {};
+    };
+
+    let clear = {
+        let notifications = notifications.clone();
+        Callback::from(move |_: MouseEvent| {
+            notifications.dispatch(NotificationAction::ClearHistory)
+        })
+    };
+
+    html! {
+        <div class="modal-overlay" onclick={close.clone()}>
+            <div class="modal-content error-history" onclick={Callback::from(|e: MouseEvent| e.stop_propagation())}>
+                <div class="modal-header">
+                    <h3>{"Error History"}</h3>
+                    <button class="modal-close" onclick={close}>{"×"}</button>
+                </div>
+                <div class="modal-body">
+                    if notifications.history.is_empty() {
+                        <p class="no-data">{"No errors this session."}</p>
+                    } else {
+                        <ul class="error-history-list">
+                            {for notifications.history.iter().map(|notification| html! {
+                                <li class={classes!("error-history-item", format!("toast-{}", notification.severity.label()))}>
+                                    <div class="error-history-meta">
+                                        <span class="error-history-time">{notification.time_label()}</span>
+                                        <strong>{&notification.source}</strong>
+                                        <span class="error-history-severity">{notification.severity.label()}</span>
+                                    </div>
+                                    <p class="toast-message">{&notification.message}</p>
+                                    if let Some(hint) = notification.hint {
+                                        <p class="toast-hint">{hint}</p>
+                                    }
+                                </li>
+                            })}
+                        </ul>
+                        <button class="button" onclick={clear}>{"Clear History"}</button>
+                    }
+                </div>
+            </div>
+        </div>
+    }
+}
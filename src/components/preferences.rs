@@ -1,17 +1,32 @@
+use crate::config::AVAILABLE_EVENT_TYPES;
+use crate::errors::AppResult;
+use crate::logging::console;
+use crate::services::{OfflineTileSet, StorageService, TauriService, TileService};
 use crate::states::UserData;
-use crate::types::Theme;
-use web_sys::MouseEvent;
+use crate::types::{
+    AppState, Basemap, DashboardView, MarkerStyle, PreferencesAction, Profile, ProfileStore,
+    Symbology, Theme, UserPreferences,
+};
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{HtmlInputElement, MouseEvent};
 use yew::prelude::*;
 
 #[derive(Properties, PartialEq)]
 pub struct PreferencesProps {
-    pub user_data: UserData,
+    pub profile: Profile,
+    pub profiles: ProfileStore,
     pub show: bool,
-    pub current_theme: Theme,
+    pub preferences: UserPreferences,
     pub on_close: Callback<()>,
-    pub on_theme_change: Callback<Theme>,
+    pub on_preferences_change: Callback<PreferencesAction>,
+    pub on_state_change: Callback<AppState>,
+    pub on_profiles_change: Callback<ProfileStore>,
 }
 
+type ProfileChange = Box<dyn FnOnce(&mut ProfileStore) -> AppResult<()>>;
+type AfterUpdate = Box<dyn FnOnce(&ProfileStore)>;
+
 #[function_component(Preferences)]
 pub fn preferences(props: &PreferencesProps) -> Html {
     let close_preferences = {
@@ -22,33 +37,259 @@ pub fn preferences(props: &PreferencesProps) -> Html {
     };
 
     let select_light_theme = {
-        let on_theme_change = props.on_theme_change.clone();
+        let on_preferences_change = props.on_preferences_change.clone();
         Callback::from(move |_: MouseEvent| {
-            on_theme_change.emit(Theme::Light);
+            on_preferences_change.emit(PreferencesAction::SetTheme(Theme::Light));
         })
     };
 
     let select_blue_theme = {
-        let on_theme_change = props.on_theme_change.clone();
+        let on_preferences_change = props.on_preferences_change.clone();
         Callback::from(move |_: MouseEvent| {
-            on_theme_change.emit(Theme::Blue);
+            on_preferences_change.emit(PreferencesAction::SetTheme(Theme::Blue));
         })
     };
 
     let select_dark_theme = {
-        let on_theme_change = props.on_theme_change.clone();
+        let on_preferences_change = props.on_preferences_change.clone();
         Callback::from(move |_: MouseEvent| {
-            on_theme_change.emit(Theme::Dark);
+            on_preferences_change.emit(PreferencesAction::SetTheme(Theme::Dark));
         })
     };
 
     let select_terminal_theme = {
-        let on_theme_change = props.on_theme_change.clone();
+        let on_preferences_change = props.on_preferences_change.clone();
+        Callback::from(move |_: MouseEvent| {
+            on_preferences_change.emit(PreferencesAction::SetTheme(Theme::Terminal));
+        })
+    };
+
+    let select_default_view = |view: &DashboardView| {
+        let on_preferences_change = props.on_preferences_change.clone();
+        let view = view.clone();
+        Callback::from(move |_: MouseEvent| {
+            on_preferences_change.emit(PreferencesAction::SetDefaultView(view.clone()));
+        })
+    };
+
+    let select_basemap = |basemap: Basemap| {
+        let on_preferences_change = props.on_preferences_change.clone();
+        Callback::from(move |_: MouseEvent| {
+            on_preferences_change.emit(PreferencesAction::SetBasemap(basemap));
+        })
+    };
+
+    let offline_tiles = use_state(|| Option::<OfflineTileSet>::None);
+    let offline_error = use_state(|| Option::<String>::None);
+
+    // Serves the picked source right away and switches the map to it
+    let choose_offline_tiles = |directory: bool| {
+        let on_preferences_change = props.on_preferences_change.clone();
+        let offline_tiles = offline_tiles.clone();
+        let offline_error = offline_error.clone();
+        Callback::from(move |_: MouseEvent| {
+            let on_preferences_change = on_preferences_change.clone();
+            let offline_tiles = offline_tiles.clone();
+            let offline_error = offline_error.clone();
+            spawn_local(async move {
+                let opened = match TileService::choose(directory).await {
+                    Ok(Some(path)) => TileService::open(&path)
+                        .await
+                        .map(|tile_set| Some((path, tile_set))),
+                    Ok(None) => Ok(None),
+                    Err(e) => Err(e),
+                };
+
+                match opened {
+                    Ok(Some((path, tile_set))) => {
+                        console::log_user_action!("Opened offline tiles {}", tile_set.name);
+                        offline_error.set(None);
+                        offline_tiles.set(Some(tile_set));
+                        on_preferences_change.emit(PreferencesAction::SetOfflineTiles(Some(path)));
+                        on_preferences_change.emit(PreferencesAction::SetBasemap(Basemap::Offline));
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        console::error_with_context!(
+                            "TILES",
+                            "Failed to open offline tiles: {}",
+                            e
+                        );
+                        offline_error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    let clear_offline_tiles = {
+        let on_preferences_change = props.on_preferences_change.clone();
+        let offline_tiles = offline_tiles.clone();
+        let offline_error = offline_error.clone();
+        let showing_offline = props.preferences.basemap == Basemap::Offline;
+        Callback::from(move |_: MouseEvent| {
+            let offline_error = offline_error.clone();
+            spawn_local(async move {
+                if let Err(e) = TileService::close().await {
+                    console::error_with_context!("TILES", "Failed to close offline tiles: {}", e);
+                    offline_error.set(Some(e.to_string()));
+                }
+            });
+            offline_tiles.set(None);
+            on_preferences_change.emit(PreferencesAction::SetOfflineTiles(None));
+            if showing_offline {
+                on_preferences_change.emit(PreferencesAction::SetBasemap(Basemap::default()));
+            }
+        })
+    };
+
+    let select_marker_style = |style: MarkerStyle| {
+        let on_preferences_change = props.on_preferences_change.clone();
+        Callback::from(move |_: MouseEvent| {
+            on_preferences_change.emit(PreferencesAction::SetMarkerStyle(style));
+        })
+    };
+
+    let change_event_color = |event_type: &'static str| {
+        let on_preferences_change = props.on_preferences_change.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            on_preferences_change.emit(PreferencesAction::SetEventColor {
+                event_type: event_type.to_string(),
+                color: input.value(),
+            });
+        })
+    };
+
+    let reset_event_colors = {
+        let on_preferences_change = props.on_preferences_change.clone();
+        Callback::from(move |_: MouseEvent| {
+            on_preferences_change.emit(PreferencesAction::ResetEventColors);
+        })
+    };
+
+    let forget_map_view = {
+        let on_preferences_change = props.on_preferences_change.clone();
+        Callback::from(move |_: MouseEvent| {
+            on_preferences_change.emit(PreferencesAction::ForgetMapView);
+        })
+    };
+
+    let forget_last_params = {
+        let on_preferences_change = props.on_preferences_change.clone();
+        Callback::from(move |_: MouseEvent| {
+            on_preferences_change.emit(PreferencesAction::ForgetLastParams);
+        })
+    };
+
+    let profile_error = use_state(|| Option::<String>::None);
+
+    // Applies a change to the stored profiles, then hands the result to
+    // `after` to decide where the app goes next
+    let update_profiles = {
+        let on_profiles_change = props.on_profiles_change.clone();
+        let profile_error = profile_error.clone();
+        move |change: ProfileChange, after: AfterUpdate| {
+            let on_profiles_change = on_profiles_change.clone();
+            let profile_error = profile_error.clone();
+            spawn_local(async move {
+                match StorageService::update_profiles(change).await {
+                    Ok(store) => {
+                        profile_error.set(None);
+                        after(&store);
+                        on_profiles_change.emit(store);
+                    }
+                    Err(e) => {
+                        console::error_with_context!(
+                            "PROFILES",
+                            "Failed to update profiles: {}",
+                            e
+                        );
+                        profile_error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        }
+    };
+
+    let switch_profile = |profile: &Profile| {
+        let on_state_change = props.on_state_change.clone();
+        let on_close = props.on_close.clone();
+        let profile = profile.clone();
         Callback::from(move |_: MouseEvent| {
-            on_theme_change.emit(Theme::Terminal);
+            console::log_user_action!("Switched to profile {}", profile.name);
+            on_state_change.emit(AppState::Dashboard(profile.clone()));
+            on_close.emit(());
         })
     };
 
+    let set_default = |name: &str| {
+        let update_profiles = update_profiles.clone();
+        let name = name.to_string();
+        Callback::from(move |_: MouseEvent| {
+            let name = name.clone();
+            update_profiles(
+                Box::new(move |store| store.set_default(&name)),
+                Box::new(|_| {}),
+            );
+        })
+    };
+
+    let remove_profile = |name: &str| {
+        let update_profiles = update_profiles.clone();
+        let on_state_change = props.on_state_change.clone();
+        let on_close = props.on_close.clone();
+        let active = props.profile.name.clone();
+        let name = name.to_string();
+        Callback::from(move |_: MouseEvent| {
+            let confirmed = web_sys::window()
+                .and_then(|window| {
+                    window
+                        .confirm_with_message(&format!("Remove profile \"{}\"?", name))
+                        .ok()
+                })
+                .unwrap_or(false);
+            if !confirmed {
+                return;
+            }
+
+            let removed = name.clone();
+            let on_state_change = on_state_change.clone();
+            let on_close = on_close.clone();
+            let removing_active = name == active;
+            update_profiles(
+                Box::new(move |store| {
+                    store.remove(&removed);
+                    Ok(())
+                }),
+                // Removing the active profile moves to the default one, or
+                // back to the login screen when none is left
+                Box::new(move |store| {
+                    if removing_active {
+                        let next = store.default_profile().or(store.profiles.first());
+                        on_state_change.emit(match next {
+                            Some(profile) => AppState::Dashboard(profile.clone()),
+                            None => AppState::Login,
+                        });
+                        on_close.emit(());
+                    }
+                }),
+            );
+        })
+    };
+
+    let add_profile = {
+        let on_state_change = props.on_state_change.clone();
+        let on_close = props.on_close.clone();
+        Callback::from(move |_: MouseEvent| {
+            on_state_change.emit(AppState::Login);
+            on_close.emit(());
+        })
+    };
+
+    let user_data = &props.profile.user_data;
+    let desktop = TauriService::is_available();
+
     if props.show {
         html! {
             <div class="modal-overlay" onclick={close_preferences.clone()}>
@@ -61,48 +302,257 @@ pub fn preferences(props: &PreferencesProps) -> Html {
                         <div class="preferences-section">
                             <h4>{"Theme"}</h4>
                             <div class="theme-selector">
-                                <button 
-                                    class={if props.current_theme == Theme::Light { "theme-button active" } else { "theme-button" }}
+                                <button
+                                    class={if props.preferences.theme == Theme::Light { "theme-button active" } else { "theme-button" }}
                                     onclick={select_light_theme}
                                 >
                                     {"Light"}
                                 </button>
-                                <button 
-                                    class={if props.current_theme == Theme::Blue { "theme-button active" } else { "theme-button" }}
+                                <button
+                                    class={if props.preferences.theme == Theme::Blue { "theme-button active" } else { "theme-button" }}
                                     onclick={select_blue_theme}
                                 >
                                     {"Blue"}
                                 </button>
-                                <button 
-                                    class={if props.current_theme == Theme::Dark { "theme-button active" } else { "theme-button" }}
+                                <button
+                                    class={if props.preferences.theme == Theme::Dark { "theme-button active" } else { "theme-button" }}
                                     onclick={select_dark_theme}
                                 >
                                     {"Dark"}
                                 </button>
-                                <button 
-                                    class={if props.current_theme == Theme::Terminal { "theme-button active" } else { "theme-button" }}
+                                <button
+                                    class={if props.preferences.theme == Theme::Terminal { "theme-button active" } else { "theme-button" }}
                                     onclick={select_terminal_theme}
                                 >
                                     {"Terminal"}
                                 </button>
                             </div>
                         </div>
-                        
+
+                        <div class="preferences-section">
+                            <h4>{"Default View"}</h4>
+                            <div class="theme-selector">
+                                {for DashboardView::ALL.iter().map(|view| html! {
+                                    <button
+                                        class={classes!("theme-button", (props.preferences.default_view == *view).then_some("active"))}
+                                        onclick={select_default_view(view)}
+                                    >
+                                        {view.label()}
+                                    </button>
+                                })}
+                            </div>
+                        </div>
+
+                        <div class="preferences-section">
+                            <h4>{"Map"}</h4>
+                            <div class="theme-selector">
+                                {for Basemap::ALL
+                                    .into_iter()
+                                    .filter(|basemap| *basemap != Basemap::Offline || desktop)
+                                    .map(|basemap| html! {
+                                        <button
+                                            class={classes!("theme-button", (props.preferences.basemap == basemap).then_some("active"))}
+                                            onclick={select_basemap(basemap)}
+                                            disabled={basemap == Basemap::Offline && props.preferences.offline_tiles.is_none()}
+                                        >
+                                            {basemap.label()}
+                                        </button>
+                                    })}
+                            </div>
+                            if desktop {
+                                if let Some(error) = (*offline_error).as_ref() {
+                                    <div class="error-message">{error}</div>
+                                }
+                                <div class="user-info-item offline-tiles">
+                                    <strong>{"Offline Tiles: "}</strong>
+                                    <span title={props.preferences.offline_tiles.clone().unwrap_or_default()}>
+                                        {match (&*offline_tiles, &props.preferences.offline_tiles) {
+                                            (Some(tile_set), _) => match (tile_set.min_zoom, tile_set.max_zoom) {
+                                                (Some(min), Some(max)) => format!("{} (zoom {}–{})", tile_set.name, min, max),
+                                                _ => tile_set.name.clone(),
+                                            },
+                                            (None, Some(path)) => path.clone(),
+                                            (None, None) => "Not configured".to_string(),
+                                        }}
+                                    </span>
+                                    <div class="offline-tiles-actions">
+                                        <button class="button" onclick={choose_offline_tiles(false)}>
+                                            {"MBTiles File..."}
+                                        </button>
+                                        <button class="button" onclick={choose_offline_tiles(true)}>
+                                            {"Tile Folder..."}
+                                        </button>
+                                        <button
+                                            class="button"
+                                            onclick={clear_offline_tiles}
+                                            disabled={props.preferences.offline_tiles.is_none()}
+                                        >
+                                            {"Clear"}
+                                        </button>
+                                    </div>
+                                </div>
+                            }
+                            <div class="user-info-item">
+                                <strong>{"Saved View: "}</strong>
+                                <span>
+                                    {match props.preferences.map_view {
+                                        Some(view) => format!("{:.4}, {:.4} at zoom {}", view.lat, view.lng, view.zoom),
+                                        None => "Default".to_string(),
+                                    }}
+                                </span>
+                                <button
+                                    class="button"
+                                    onclick={forget_map_view}
+                                    disabled={props.preferences.map_view.is_none()}
+                                >
+                                    {"Reset"}
+                                </button>
+                            </div>
+                            <div class="user-info-item">
+                                <strong>{"Last Query: "}</strong>
+                                <span>
+                                    {match &props.preferences.last_params {
+                                        Some(params) => format!(
+                                            "{} · {} to {}",
+                                            params.countries.join(", "),
+                                            params.start_date,
+                                            params.end_date
+                                        ),
+                                        None => "None".to_string(),
+                                    }}
+                                </span>
+                                <button
+                                    class="button"
+                                    onclick={forget_last_params}
+                                    disabled={props.preferences.last_params.is_none()}
+                                >
+                                    {"Forget"}
+                                </button>
+                            </div>
+                        </div>
+
+                        <div class="preferences-section">
+                            <h4>{"Markers"}</h4>
+                            <div class="theme-selector">
+                                {for MarkerStyle::ALL.into_iter().map(|style| html! {
+                                    <button
+                                        class={classes!("theme-button", (props.preferences.symbology.style == style).then_some("active"))}
+                                        onclick={select_marker_style(style)}
+                                    >
+                                        {style.label()}
+                                    </button>
+                                })}
+                            </div>
+                            <div class="event-colors">
+                                {for AVAILABLE_EVENT_TYPES.iter().map(|&event_type| html! {
+                                    <label class="event-color">
+                                        <input
+                                            type="color"
+                                            value={props.preferences.symbology.color(event_type).to_string()}
+                                            onchange={change_event_color(event_type)}
+                                        />
+                                        {event_type}
+                                        if props.preferences.symbology.color(event_type) != Symbology::default_color(event_type) {
+                                            <span class="event-color-custom">{"(custom)"}</span>
+                                        }
+                                    </label>
+                                })}
+                            </div>
+                            <button
+                                class="button"
+                                onclick={reset_event_colors}
+                                disabled={props.preferences.symbology.colors.is_empty()}
+                            >
+                                {"Reset Colors"}
+                            </button>
+                        </div>
+
                         <div class="preferences-section">
                             <h4>{"User Information"}</h4>
                             <div class="user-info-item">
-                                <strong>{"Email: "}</strong>
-                                <span>{&props.user_data.email}</span>
+                                <strong>{"Profile: "}</strong>
+                                <span>{&props.profile.name}</span>
                             </div>
                             <div class="user-info-item">
-                                <strong>{"API Key: "}</strong>
-                                <span>{"••••••••••••••••"}</span>
+                                <strong>{"Account: "}</strong>
+                                <span>{user_data.account()}</span>
                             </div>
+                            <div class="user-info-item">
+                                <strong>{"Authentication: "}</strong>
+                                <span>{user_data.method_label()}</span>
+                            </div>
+                            {match user_data {
+                                UserData::ApiKey { .. } => html! {
+                                    <div class="user-info-item">
+                                        <strong>{"API Key: "}</strong>
+                                        <span>{"••••••••••••••••"}</span>
+                                    </div>
+                                },
+                                UserData::OAuth { tokens, .. } => html! {
+                                    <div class="user-info-item">
+                                        <strong>{"Token Expires: "}</strong>
+                                        <span>
+                                            {String::from(
+                                                js_sys::Date::new(&tokens.expires_at.into())
+                                                    .to_locale_string("default", &JsValue::UNDEFINED),
+                                            )}
+                                        </span>
+                                    </div>
+                                },
+                            }}
                             <div class="user-info-item">
                                 <strong>{"Status: "}</strong>
                                 <span class="status-active">{"Active"}</span>
                             </div>
                         </div>
+
+                        <div class="preferences-section">
+                            <h4>{"Profiles"}</h4>
+                            if let Some(error) = (*profile_error).as_ref() {
+                                <div class="error-message">{error}</div>
+                            }
+                            <ul class="profile-list">
+                                {for props.profiles.profiles.iter().map(|profile| {
+                                    let is_active = profile.name == props.profile.name;
+                                    let is_default = props.profiles.is_default(&profile.name);
+                                    html! {
+                                        <li class={classes!("profile-row", is_active.then_some("active"))}>
+                                            <div class="profile-summary">
+                                                <strong>{&profile.name}</strong>
+                                                if is_default {
+                                                    <span class="profile-badge">{"default"}</span>
+                                                }
+                                                <span class="profile-account">
+                                                    {format!("{} · {}", profile.user_data.account(), profile.user_data.method_label())}
+                                                </span>
+                                            </div>
+                                            <div class="profile-actions">
+                                                <button
+                                                    class="button"
+                                                    onclick={switch_profile(profile)}
+                                                    disabled={is_active}
+                                                >
+                                                    {"Switch"}
+                                                </button>
+                                                <button
+                                                    class="button"
+                                                    onclick={set_default(&profile.name)}
+                                                    disabled={is_default}
+                                                >
+                                                    {"Set Default"}
+                                                </button>
+                                                <button class="button" onclick={remove_profile(&profile.name)}>
+                                                    {"Remove"}
+                                                </button>
+                                            </div>
+                                        </li>
+                                    }
+                                })}
+                            </ul>
+                            <button class="button" onclick={add_profile}>
+                                {"Add Profile"}
+                            </button>
+                        </div>
                     </div>
                 </div>
             </div>
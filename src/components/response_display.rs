@@ -1,29 +1,97 @@
-use crate::components::{AcledEvent, EventsList, ResponseInfo};
+use crate::components::{AcledEvent, EventsList, ExportMenu, ResponseInfo};
+use crate::types::AcledParams;
+use crate::utils::text::search_tokens;
+use std::rc::Rc;
+use web_sys::{HtmlInputElement, MouseEvent};
 use yew::prelude::*;
 
 #[derive(Properties, PartialEq)]
 pub struct ResponseDisplayProps {
+    /// Loaded events matching `search` and `selection`.
     pub events: Option<Vec<AcledEvent>>,
+    /// Number of loaded events before the search and selection were applied.
+    #[prop_or_default]
+    pub total_events: Option<usize>,
+    #[prop_or_default]
+    pub params: Option<AcledParams>,
     #[prop_or(true)]
     pub show_response_info: bool,
+    #[prop_or_default]
+    pub search: String,
+    #[prop_or_default]
+    pub on_search_change: Callback<String>,
+    /// Description of the area drawn on the map, if events are limited to one.
+    #[prop_or_default]
+    pub selection: Option<String>,
+    #[prop_or_default]
+    pub on_clear_selection: Callback<()>,
 }
 
 #[function_component(ResponseDisplay)]
 pub fn response_display(props: &ResponseDisplayProps) -> Html {
+    let on_search_input = {
+        let on_search_change = props.on_search_change.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            on_search_change.emit(input.value());
+        })
+    };
+
+    let clear_selection = {
+        let on_clear_selection = props.on_clear_selection.clone();
+        Callback::from(move |_: MouseEvent| on_clear_selection.emit(()))
+    };
+
+    let tokens = search_tokens(&props.search);
+    let filtered = !tokens.is_empty() || props.selection.is_some();
+    let total = props
+        .events
+        .as_ref()
+        .map(|events| props.total_events.unwrap_or(events.len()))
+        .unwrap_or_default();
+
     html! {
         <div class="response-content">
             if props.show_response_info {
                 if let Some(events) = &props.events {
                     <ResponseInfo
-                        total_records={events.len() as u32}
-                        returned_records={events.len() as u32}
+                        total_records={total as u32}
+                        returned_records={total as u32}
                         success={true}
                     />
+                    <ExportMenu events={Rc::new(events.clone())} params={props.params.clone()} />
                 }
             }
 
             if let Some(events) = &props.events {
-                <EventsList events={events.clone()} title="Events" />
+                <div class="search-bar">
+                    <input
+                        type="search"
+                        class="search-input"
+                        placeholder="Search notes, actors and locations..."
+                        value={props.search.clone()}
+                        oninput={on_search_input}
+                    />
+                    if let Some(selection) = &props.selection {
+                        <span class="chip selection-chip">
+                            {format!("Within {}", selection)}
+                            <button class="chip-remove" onclick={clear_selection} title="Clear selection">
+                                {"×"}
+                            </button>
+                        </span>
+                    }
+                    if filtered {
+                        <span class="search-count">
+                            {format!("{} of {} events match", events.len(), total)}
+                        </span>
+                    }
+                </div>
+
+                if events.is_empty() && filtered {
+                    <div class="no-data">{"No events match the search or map selection."}</div>
+                } else {
+                    <EventsList events={events.clone()} title="Events" highlight={tokens} />
+                }
             } else {
                 <div class="no-data">
                     {"No data loaded. Use the parameters on the left to fetch data."}
@@ -0,0 +1,282 @@
+use crate::errors::{AppError, AppResult};
+use crate::logging::console;
+use crate::services::{AcledService, ExportService, StorageService};
+use crate::types::{AcledParams, SavedQueries};
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{HtmlInputElement, HtmlSelectElement, MouseEvent};
+use yew::prelude::*;
+
+const EXPORT_FILE_NAME: &str = "arson_saved_queries.json";
+
+#[derive(Properties, PartialEq)]
+pub struct SavedQueryPickerProps {
+    /// The parameters currently in the form; what "Save" stores.
+    pub current: AcledParams,
+    pub on_load: Callback<AcledParams>,
+}
+
+// Imported presets come from other people's machines, so check them before
+// they can end up in a request
+fn parse_import(json: &str) -> AppResult<SavedQueries> {
+    let imported = SavedQueries::from_json(json)?;
+    for query in &imported.queries {
+        AcledService::validate_params(&query.params).map_err(|e| {
+            AppError::validation(
+                "import",
+                format!("Query \"{}\" is invalid: {}", query.name, e),
+            )
+        })?;
+    }
+    Ok(imported)
+}
+
+#[function_component(SavedQueryPicker)]
+pub fn saved_query_picker(props: &SavedQueryPickerProps) -> Html {
+    let store = use_state(|| {
+        StorageService::load_saved_queries().unwrap_or_else(|e| {
+            console::error_with_context!("SAVED_QUERIES", "Failed to load saved queries: {}", e);
+            SavedQueries::default()
+        })
+    });
+    let selected = use_state(String::new);
+    let name_input = use_state(String::new);
+    let status = use_state(|| Option::<Result<String, String>>::None);
+
+    // Persists a changed collection; the UI only moves on once storage succeeded
+    let commit = {
+        let store = store.clone();
+        let status = status.clone();
+        Callback::from(move |result: AppResult<(SavedQueries, String)>| {
+            let result = result.and_then(|(next, message)| {
+                StorageService::save_saved_queries(&next)?;
+                Ok((next, message))
+            });
+            match result {
+                Ok((next, message)) => {
+                    store.set(next);
+                    status.set(Some(Ok(message)));
+                }
+                Err(e) => {
+                    console::error_with_context!("SAVED_QUERIES", "{}", e);
+                    status.set(Some(Err(e.to_string())));
+                }
+            }
+        })
+    };
+
+    let on_select = {
+        let selected = selected.clone();
+        let name_input = name_input.clone();
+        let status = status.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            name_input.set(select.value());
+            selected.set(select.value());
+            status.set(None);
+        })
+    };
+
+    let on_name_input = {
+        let name_input = name_input.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            name_input.set(input.value());
+        })
+    };
+
+    let on_load = {
+        let store = store.clone();
+        let selected = selected.clone();
+        let on_load = props.on_load.clone();
+        Callback::from(move |_: MouseEvent| {
+            if let Some(query) = store.get(&selected) {
+                console::log_user_action!("Load saved query \"{}\"", query.name);
+                on_load.emit(query.params.clone());
+            }
+        })
+    };
+
+    let on_save = {
+        let store = store.clone();
+        let selected = selected.clone();
+        let name_input = name_input.clone();
+        let commit = commit.clone();
+        let current = props.current.clone();
+        Callback::from(move |_: MouseEvent| {
+            let name = name_input.trim().to_string();
+            let mut next = (*store).clone();
+            let result = next
+                .save(&name, current.clone())
+                .map(|_| (next, format!("Saved \"{}\"", name)));
+            if result.is_ok() {
+                selected.set(name);
+            }
+            commit.emit(result);
+        })
+    };
+
+    let on_rename = {
+        let store = store.clone();
+        let selected = selected.clone();
+        let name_input = name_input.clone();
+        let commit = commit.clone();
+        Callback::from(move |_: MouseEvent| {
+            let name = name_input.trim().to_string();
+            let mut next = (*store).clone();
+            let result = next
+                .rename(&selected, &name)
+                .map(|_| (next, format!("Renamed \"{}\" to \"{}\"", *selected, name)));
+            if result.is_ok() {
+                selected.set(name);
+            }
+            commit.emit(result);
+        })
+    };
+
+    let on_delete = {
+        let store = store.clone();
+        let selected = selected.clone();
+        let name_input = name_input.clone();
+        let commit = commit.clone();
+        Callback::from(move |_: MouseEvent| {
+            let confirmed = web_sys::window()
+                .and_then(|window| {
+                    window
+                        .confirm_with_message(&format!("Delete saved query \"{}\"?", *selected))
+                        .ok()
+                })
+                .unwrap_or(false);
+            if !confirmed {
+                return;
+            }
+
+            let mut next = (*store).clone();
+            next.remove(&selected);
+            commit.emit(Ok((next, format!("Deleted \"{}\"", *selected))));
+            selected.set(String::new());
+            name_input.set(String::new());
+        })
+    };
+
+    let on_export = {
+        let store = store.clone();
+        let status = status.clone();
+        Callback::from(move |_: MouseEvent| {
+            let store = (*store).clone();
+            let status = status.clone();
+            spawn_local(async move {
+                let result = match store.to_json() {
+                    Ok(json) => {
+                        ExportService::save_file(
+                            EXPORT_FILE_NAME,
+                            "JSON",
+                            "json",
+                            "application/json",
+                            &json,
+                        )
+                        .await
+                    }
+                    Err(e) => Err(e),
+                };
+                match result {
+                    Ok(true) => status.set(Some(Ok(format!(
+                        "Exported {} saved queries",
+                        store.queries.len()
+                    )))),
+                    Ok(false) => {}
+                    Err(e) => {
+                        console::error_with_context!("SAVED_QUERIES", "Export failed: {}", e);
+                        status.set(Some(Err(e.to_string())));
+                    }
+                }
+            });
+        })
+    };
+
+    let on_import = {
+        let store = store.clone();
+        let commit = commit.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            let Some(file) = input.files().and_then(|files| files.get(0)) else {
+                return;
+            };
+            // Allow importing the same file again later
+            input.set_value("");
+
+            let store = (*store).clone();
+            let commit = commit.clone();
+            spawn_local(async move {
+                let result = ExportService::read_file(file)
+                    .await
+                    .and_then(|json| parse_import(&json))
+                    .map(|imported| {
+                        let mut next = store;
+                        let count = next.merge(imported);
+                        (next, format!("Imported {} saved queries", count))
+                    });
+                commit.emit(result);
+            });
+        })
+    };
+
+    let has_selection = store.get(&selected).is_some();
+    let has_name = !name_input.trim().is_empty();
+
+    html! {
+        <div class="param-group saved-queries">
+            <label for="saved-query">{"Saved Queries:"}</label>
+            <select id="saved-query" onchange={on_select}>
+                <option value="" selected={!has_selection}>
+                    {if store.is_empty() { "No saved queries" } else { "Select a query..." }}
+                </option>
+                {for store.queries.iter().map(|query| html! {
+                    <option value={query.name.clone()} selected={*selected == query.name}>
+                        {&query.name}
+                    </option>
+                })}
+            </select>
+            <input
+                type="text"
+                class="saved-query-name"
+                placeholder="Query name"
+                value={(*name_input).clone()}
+                oninput={on_name_input}
+            />
+            <div class="saved-query-actions">
+                <button class="button" onclick={on_load} disabled={!has_selection}>
+                    {"Load"}
+                </button>
+                <button class="button" onclick={on_save} disabled={!has_name}>
+                    {"Save"}
+                </button>
+                <button
+                    class="button"
+                    onclick={on_rename}
+                    disabled={!has_selection || !has_name}
+                >
+                    {"Rename"}
+                </button>
+                <button class="button" onclick={on_delete} disabled={!has_selection}>
+                    {"Delete"}
+                </button>
+            </div>
+            <div class="saved-query-actions">
+                <button class="button" onclick={on_export} disabled={store.is_empty()}>
+                    {"Export"}
+                </button>
+                <label class="button saved-query-import">
+                    {"Import"}
+                    <input type="file" accept=".json,application/json" onchange={on_import} />
+                </label>
+            </div>
+            {match (*status).as_ref() {
+                Some(Ok(message)) => html! { <span class="export-status">{message}</span> },
+                Some(Err(error)) => html! {
+                    <span class="export-status export-error">{error}</span>
+                },
+                None => html! {},
+            }}
+        </div>
+    }
+}
@@ -0,0 +1,313 @@
+use crate::config::TIMELINE_PLAYBACK_INTERVAL_MS;
+use crate::utils::date::format_day;
+use gloo_timers::callback::Interval;
+use std::rc::Rc;
+use web_sys::{HtmlInputElement, HtmlSelectElement, MouseEvent};
+use yew::prelude::*;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TimeWindow {
+    Day,
+    Week,
+    Month,
+}
+
+impl TimeWindow {
+    fn days(&self) -> i64 {
+        match self {
+            TimeWindow::Day => 1,
+            TimeWindow::Week => 7,
+            TimeWindow::Month => 30,
+        }
+    }
+
+    // How far one playback tick moves the window
+    fn step_days(&self) -> i64 {
+        match self {
+            TimeWindow::Day | TimeWindow::Week => 1,
+            TimeWindow::Month => 7,
+        }
+    }
+}
+
+/// Inclusive range of day numbers (see [`crate::utils::date`]).
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct DateWindow {
+    pub start_day: i64,
+    pub end_day: i64,
+}
+
+impl DateWindow {
+    pub fn contains(&self, day: i64) -> bool {
+        day >= self.start_day && day <= self.end_day
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+struct TimelineState {
+    enabled: bool,
+    playing: bool,
+    window: TimeWindow,
+    range_start: i64,
+    range_end: i64,
+    /// Last day of the visible window.
+    cursor: i64,
+}
+
+impl TimelineState {
+    fn first_cursor(&self) -> i64 {
+        (self.range_start + self.window.days() - 1).min(self.range_end)
+    }
+
+    fn visible_window(&self) -> DateWindow {
+        DateWindow {
+            start_day: self.cursor - self.window.days() + 1,
+            end_day: self.cursor,
+        }
+    }
+}
+
+enum TimelineAction {
+    SetEnabled(bool),
+    TogglePlay,
+    Advance,
+    Seek(i64),
+    SetWindow(TimeWindow),
+    SetRange(i64, i64),
+}
+
+impl Reducible for TimelineState {
+    type Action = TimelineAction;
+
+    fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
+        let mut next = (*self).clone();
+
+        match action {
+            TimelineAction::SetEnabled(enabled) => {
+                next.enabled = enabled;
+                next.playing = next.playing && enabled;
+            }
+            TimelineAction::TogglePlay => {
+                next.playing = !next.playing;
+                if next.playing {
+                    next.enabled = true;
+                    if next.cursor >= next.range_end {
+                        next.cursor = next.first_cursor();
+                    }
+                }
+            }
+            TimelineAction::Advance => {
+                next.cursor = (next.cursor + next.window.step_days()).min(next.range_end);
+                if next.cursor >= next.range_end {
+                    next.playing = false;
+                }
+            }
+            TimelineAction::Seek(day) => {
+                next.cursor = day.clamp(next.range_start, next.range_end);
+            }
+            TimelineAction::SetWindow(window) => {
+                next.window = window;
+            }
+            TimelineAction::SetRange(start, end) => {
+                next.range_start = start;
+                next.range_end = end.max(start);
+                next.playing = false;
+                next.cursor = next.first_cursor();
+            }
+        }
+
+        Rc::new(next)
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct TimelineProps {
+    pub start_day: i64,
+    pub end_day: i64,
+    /// `None` while the timeline is switched off and every event should show.
+    pub on_change: Callback<Option<DateWindow>>,
+}
+
+#[function_component(Timeline)]
+pub fn timeline(props: &TimelineProps) -> Html {
+    let state = use_reducer(|| {
+        let initial = TimelineState {
+            enabled: false,
+            playing: false,
+            window: TimeWindow::Week,
+            range_start: props.start_day,
+            range_end: props.end_day.max(props.start_day),
+            cursor: props.start_day,
+        };
+        TimelineState {
+            cursor: initial.first_cursor(),
+            ..initial
+        }
+    });
+
+    // Follow the requested date range when a new fetch changes it
+    {
+        let dispatcher = state.dispatcher();
+        use_effect_with((props.start_day, props.end_day), move |(start, end)| {
+            dispatcher.dispatch(TimelineAction::SetRange(*start, *end));
+            || ()
+        });
+    }
+
+    // Playback ticks for as long as `playing` stays set
+    {
+        let dispatcher = state.dispatcher();
+        use_effect_with(state.playing, move |playing| {
+            let interval = playing.then(|| {
+                Interval::new(TIMELINE_PLAYBACK_INTERVAL_MS, move || {
+                    dispatcher.dispatch(TimelineAction::Advance)
+                })
+            });
+
+            move || drop(interval)
+        });
+    }
+
+    {
+        let on_change = props.on_change.clone();
+        let window = state.enabled.then(|| state.visible_window());
+        use_effect_with(window, move |window| {
+            on_change.emit(*window);
+            || ()
+        });
+    }
+
+    let on_enabled_toggle = {
+        let dispatcher = state.dispatcher();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            dispatcher.dispatch(TimelineAction::SetEnabled(input.checked()));
+        })
+    };
+
+    let on_play_toggle = {
+        let dispatcher = state.dispatcher();
+        Callback::from(move |_: MouseEvent| dispatcher.dispatch(TimelineAction::TogglePlay))
+    };
+
+    let on_seek = {
+        let dispatcher = state.dispatcher();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            if let Ok(day) = input.value().parse::<i64>() {
+                dispatcher.dispatch(TimelineAction::Seek(day));
+            }
+        })
+    };
+
+    let on_window_change = {
+        let dispatcher = state.dispatcher();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            let window = match select.value().as_str() {
+                "day" => TimeWindow::Day,
+                "month" => TimeWindow::Month,
+                _ => TimeWindow::Week,
+            };
+            dispatcher.dispatch(TimelineAction::SetWindow(window));
+        })
+    };
+
+    let visible = state.visible_window();
+
+    html! {
+        <div class={classes!("timeline", (!state.enabled).then_some("timeline-disabled"))}>
+            <label class="map-toggle">
+                <input type="checkbox" checked={state.enabled} onchange={on_enabled_toggle} />
+                {"Timeline"}
+            </label>
+            <button class="button timeline-play" onclick={on_play_toggle}>
+                {if state.playing { "Pause" } else { "Play" }}
+            </button>
+            <input
+                type="range"
+                class="timeline-slider"
+                min={state.range_start.to_string()}
+                max={state.range_end.to_string()}
+                value={state.cursor.to_string()}
+                disabled={!state.enabled}
+                oninput={on_seek}
+            />
+            <select class="map-select timeline-window" onchange={on_window_change}>
+                <option value="day" selected={state.window == TimeWindow::Day}>{"Day"}</option>
+                <option value="week" selected={state.window == TimeWindow::Week}>{"Week"}</option>
+                <option value="month" selected={state.window == TimeWindow::Month}>{"Month"}</option>
+            </select>
+            <span class="timeline-label">
+                {if state.enabled {
+                    format!("{} → {}", format_day(visible.start_day), format_day(visible.end_day))
+                } else {
+                    format!("{} → {}", format_day(state.range_start), format_day(state.range_end))
+                }}
+            </span>
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::date::parse_day;
+
+    fn day(text: &str) -> i64 {
+        parse_day(text).unwrap()
+    }
+
+    fn state(window: TimeWindow, start: &str, end: &str) -> Rc<TimelineState> {
+        Rc::new(TimelineState {
+            enabled: true,
+            playing: false,
+            window,
+            range_start: day(start),
+            range_end: day(end),
+            cursor: day(start),
+        })
+        .reduce(TimelineAction::SetRange(day(start), day(end)))
+    }
+
+    #[test]
+    fn date_window_includes_both_ends() {
+        let window = DateWindow {
+            start_day: day("2024-02-28"),
+            end_day: day("2024-03-01"),
+        };
+
+        assert!(!window.contains(day("2024-02-27")));
+        assert!(window.contains(day("2024-02-28")));
+        assert!(window.contains(day("2024-02-29")));
+        assert!(window.contains(day("2024-03-01")));
+        assert!(!window.contains(day("2024-03-02")));
+    }
+
+    #[test]
+    fn visible_window_spans_exactly_the_window_length() {
+        for (window, last) in [
+            (TimeWindow::Day, "2024-12-30"),
+            (TimeWindow::Week, "2025-01-05"),
+            (TimeWindow::Month, "2025-01-28"),
+        ] {
+            let visible = state(window, "2024-12-30", "2025-03-31").visible_window();
+            assert_eq!(visible.start_day, day("2024-12-30"));
+            assert_eq!(visible.end_day, day(last));
+            assert_eq!(visible.end_day - visible.start_day + 1, window.days());
+        }
+    }
+
+    #[test]
+    fn playback_stops_on_the_last_day_of_the_range() {
+        let mut state = state(TimeWindow::Month, "2024-02-01", "2024-03-10");
+        assert_eq!(state.cursor, day("2024-03-01"));
+
+        state = state.reduce(TimelineAction::Advance);
+        assert_eq!(state.cursor, day("2024-03-08"));
+        state = state.reduce(TimelineAction::Advance);
+        assert_eq!(state.cursor, day("2024-03-10"));
+        assert!(!state.playing);
+        assert!(state.visible_window().contains(day("2024-03-10")));
+    }
+}
@@ -1,9 +1,10 @@
-use crate::components::Preferences;
-use crate::services::StorageService;
-use crate::types::{AppState, DashboardView, Theme};
+use crate::components::{report_error, ErrorHistory, LogViewer, NotificationContext, Preferences};
+use crate::logging::console;
+use crate::services::{AuthService, StorageService};
+use crate::types::{AppState, DashboardView, PreferencesAction, ProfileStore, UserPreferences};
 use wasm_bindgen::prelude::*;
 use wasm_bindgen_futures::spawn_local;
-use web_sys::MouseEvent;
+use web_sys::{HtmlSelectElement, MouseEvent};
 use yew::prelude::*;
 
 #[wasm_bindgen]
@@ -16,15 +17,24 @@ extern "C" {
 pub struct TitlebarProps {
     pub app_state: AppState,
     pub dashboard_view: DashboardView,
-    pub current_theme: Theme,
+    pub preferences: UserPreferences,
+    pub profiles: ProfileStore,
     pub on_state_change: Callback<AppState>,
+    pub on_profiles_change: Callback<ProfileStore>,
     pub on_view_change: Callback<DashboardView>,
-    pub on_theme_change: Callback<Theme>,
+    pub on_preferences_change: Callback<PreferencesAction>,
 }
 
 #[function_component(Titlebar)]
 pub fn titlebar(props: &TitlebarProps) -> Html {
     let show_preferences = use_state(|| false);
+    let show_errors = use_state(|| false);
+    let show_logs = use_state(|| false);
+    let notifications = use_context::<NotificationContext>();
+    let error_count = notifications
+        .as_ref()
+        .map(|notifications| notifications.history.len())
+        .unwrap_or_default();
 
     let minimize_onclick = Callback::from(|_| {
         spawn_local(async move {
@@ -44,14 +54,55 @@ pub fn titlebar(props: &TitlebarProps) -> Html {
         });
     });
 
+    // Logging out removes the active profile's saved credentials; other
+    // profiles stay available on the login screen
     let on_logout = {
+        let app_state = props.app_state.clone();
         let on_state_change = props.on_state_change.clone();
+        let on_profiles_change = props.on_profiles_change.clone();
+        let notifications = notifications.clone();
         Callback::from(move |_: MouseEvent| {
-            // Use the service for storage management
-            if let Err(e) = StorageService::clear_user_data() {
-                console_error!("Failed to clear user data: {}", e);
-            }
+            AuthService::forget();
             on_state_change.emit(AppState::Login);
+
+            let AppState::Dashboard(active) = &app_state else {
+                return;
+            };
+            let name = active.name.clone();
+            let on_profiles_change = on_profiles_change.clone();
+            let notifications = notifications.clone();
+            spawn_local(async move {
+                let removed = StorageService::update_profiles(|store| {
+                    store.remove(&name);
+                    Ok(())
+                })
+                .await;
+
+                match removed {
+                    Ok(store) => on_profiles_change.emit(store),
+                    Err(e) => {
+                        console::error_with_context!(
+                            "PROFILES",
+                            "Failed to remove saved credentials on logout: {}",
+                            e
+                        );
+                        if let Some(notifications) = notifications.as_ref() {
+                            report_error(notifications, "Logging out", e);
+                        }
+                    }
+                }
+            });
+        })
+    };
+
+    let on_profile_switch = {
+        let on_state_change = props.on_state_change.clone();
+        let profiles = props.profiles.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            if let Some(profile) = profiles.get(&select.value()) {
+                on_state_change.emit(AppState::Dashboard(profile.clone()));
+            }
         })
     };
 
@@ -62,6 +113,34 @@ pub fn titlebar(props: &TitlebarProps) -> Html {
         })
     };
 
+    let toggle_errors = {
+        let show_errors = show_errors.clone();
+        Callback::from(move |_: MouseEvent| {
+            show_errors.set(!*show_errors);
+        })
+    };
+
+    let toggle_logs = {
+        let show_logs = show_logs.clone();
+        Callback::from(move |_: MouseEvent| {
+            show_logs.set(!*show_logs);
+        })
+    };
+
+    let close_logs = {
+        let show_logs = show_logs.clone();
+        Callback::from(move |_| {
+            show_logs.set(false);
+        })
+    };
+
+    let close_errors = {
+        let show_errors = show_errors.clone();
+        Callback::from(move |_| {
+            show_errors.set(false);
+        })
+    };
+
     let close_preferences = {
         let show_preferences = show_preferences.clone();
         Callback::from(move |_| {
@@ -83,6 +162,13 @@ pub fn titlebar(props: &TitlebarProps) -> Html {
         })
     };
 
+    let switch_to_table = {
+        let on_view_change = props.on_view_change.clone();
+        Callback::from(move |_: MouseEvent| {
+            on_view_change.emit(DashboardView::Table);
+        })
+    };
+
     html! {
         <>
             <div class="titlebar">
@@ -96,6 +182,12 @@ pub fn titlebar(props: &TitlebarProps) -> Html {
                                 >
                                     {"Data View"}
                                 </button>
+                                <button
+                                    class={if props.dashboard_view == DashboardView::Table { "tab-button active" } else { "tab-button" }}
+                                    onclick={switch_to_table}
+                                >
+                                    {"Table View"}
+                                </button>
                                 <button
                                     class={if props.dashboard_view == DashboardView::Map { "tab-button active" } else { "tab-button" }}
                                     onclick={switch_to_map}
@@ -118,6 +210,7 @@ pub fn titlebar(props: &TitlebarProps) -> Html {
                                 {match props.dashboard_view {
                                     DashboardView::Map => "Interactive Map",
                                     DashboardView::DataList => "Dashboard",
+                                    DashboardView::Table => "Event Table",
                                 }}
                             </span>
                         },
@@ -125,9 +218,37 @@ pub fn titlebar(props: &TitlebarProps) -> Html {
                 </div>
 
                 <div class="titlebar-right">
+                    <button class="titlebar-nav-button logs-button" onclick={toggle_logs}>
+                        {"Logs"}
+                    </button>
+                    if error_count > 0 || matches!(props.app_state, AppState::Dashboard(_)) {
+                        <button
+                            class={classes!("titlebar-nav-button", "errors-button", (error_count > 0).then_some("has-errors"))}
+                            onclick={toggle_errors}
+                            title="Errors this session"
+                        >
+                            {format!("Errors ({})", error_count)}
+                        </button>
+                    }
                     {match &props.app_state {
-                        AppState::Dashboard(_) => html! {
+                        AppState::Dashboard(active) => html! {
                             <>
+                                if props.profiles.profiles.len() > 1 {
+                                    <select
+                                        class="titlebar-profile-select"
+                                        title="Switch profile"
+                                        onchange={on_profile_switch}
+                                    >
+                                        {for props.profiles.profiles.iter().map(|profile| html! {
+                                            <option
+                                                value={profile.name.clone()}
+                                                selected={profile.name == active.name}
+                                            >
+                                                {&profile.name}
+                                            </option>
+                                        })}
+                                    </select>
+                                }
                                 <button class="titlebar-nav-button preferences-button" onclick={toggle_preferences}>
                                     {"Preferences"}
                                 </button>
@@ -198,14 +319,20 @@ pub fn titlebar(props: &TitlebarProps) -> Html {
                 </div>
             </div>
 
+            <ErrorHistory show={*show_errors} on_close={close_errors} />
+            <LogViewer show={*show_logs} on_close={close_logs} />
+
             {match &props.app_state {
-                AppState::Dashboard(user_data) => html! {
+                AppState::Dashboard(profile) => html! {
                     <Preferences
-                        user_data={user_data.clone()}
+                        profile={profile.clone()}
+                        profiles={props.profiles.clone()}
                         show={*show_preferences}
-                        current_theme={props.current_theme.clone()}
+                        preferences={props.preferences.clone()}
                         on_close={close_preferences}
-                        on_theme_change={props.on_theme_change.clone()}
+                        on_preferences_change={props.on_preferences_change.clone()}
+                        on_state_change={props.on_state_change.clone()}
+                        on_profiles_change={props.on_profiles_change.clone()}
                     />
                 },
                 AppState::Login => html! {},
@@ -1,28 +1,63 @@
 // API Configuration
 pub const ACLED_BASE_URL: &str = "https://api.acleddata.com/acled/read";
-#[allow(dead_code)]
+// OAuth-authenticated API; both URLs can be pointed at a local mock at build time
+pub const ACLED_OAUTH_API_URL: &str = match option_env!("ARSON_ACLED_API_URL") {
+    Some(url) => url,
+    None => "https://acleddata.com/api/acled/read",
+};
+pub const ACLED_TOKEN_URL: &str = match option_env!("ARSON_ACLED_TOKEN_URL") {
+    Some(url) => url,
+    None => "https://acleddata.com/oauth/token",
+};
+pub const ACLED_OAUTH_CLIENT_ID: &str = "acled";
+pub const TOKEN_REFRESH_MARGIN_MS: f64 = 60_000.0; // refresh this long before expiry
+pub const ACLED_ATTRIBUTION: &str =
+    "Armed Conflict Location & Event Data Project (ACLED); www.acleddata.com";
 pub const DEFAULT_API_TIMEOUT: u64 = 30; // seconds
 
+// Retry Configuration
+pub const MAX_FETCH_ATTEMPTS: u32 = 5; // including the first request
+pub const RETRY_BASE_DELAY_MS: u32 = 1_000;
+pub const RETRY_MAX_DELAY_MS: u32 = 30_000;
+pub const RETRY_AFTER_LIMIT_MS: u32 = 120_000; // longer Retry-After waits give up instead
+
+// Pagination Configuration
+pub const MAX_FETCH_PAGES: u32 = 200; // safety stop for paginated fetches
+
 // UI Configuration
-pub const MAX_EVENTS_LIMIT: u32 = 5000;
+pub const MAX_EVENTS_LIMIT: u32 = 5000; // per request / per page
 #[allow(dead_code)]
 pub const MIN_EVENTS_LIMIT: u32 = 1;
 #[allow(dead_code)]
 pub const DEFAULT_EVENTS_LIMIT: u32 = 50;
 
 // Map Configuration
-#[allow(dead_code)]
 pub const DEFAULT_MAP_CENTER: (f64, f64) = (33.8547, 35.8623); // Lebanon
-#[allow(dead_code)]
 pub const DEFAULT_MAP_ZOOM: f64 = 8.0;
-#[allow(dead_code)]
+pub const FIT_BOUNDS_MAX_ZOOM: f64 = 12.0; // keeps a single event in context
+pub const FIT_BOUNDS_PADDING_PX: u32 = 40;
 pub const MAP_TILE_URL: &str = "https://{s}.tile.openstreetmap.org/{z}/{x}/{y}.png";
-#[allow(dead_code)]
 pub const MAP_ATTRIBUTION: &str = "&copy; OpenStreetMap contributors";
+pub const SATELLITE_TILE_URL: &str =
+    "https://server.arcgisonline.com/ArcGIS/rest/services/World_Imagery/MapServer/tile/{z}/{y}/{x}";
+pub const SATELLITE_ATTRIBUTION: &str = "Tiles &copy; Esri &mdash; Source: Esri, i-cubed, USDA, USGS, AEX, GeoEye, Getmapping, Aerogrid, IGN, IGP, UPR-EGP, and the GIS User Community";
+pub const TERRAIN_TILE_URL: &str = "https://{s}.tile.opentopomap.org/{z}/{x}/{y}.png";
+pub const TERRAIN_ATTRIBUTION: &str =
+    "Map data: &copy; OpenStreetMap contributors, SRTM | Map style: &copy; OpenTopoMap (CC-BY-SA)";
+pub const DARK_TILE_URL: &str = "https://{s}.basemaps.cartocdn.com/dark_all/{z}/{x}/{y}{r}.png";
+pub const DARK_ATTRIBUTION: &str = "&copy; OpenStreetMap contributors &copy; CARTO";
+pub const OFFLINE_TILE_PROTOCOL: &str = "tiles"; // served by the desktop backend
+pub const CLUSTER_CELL_SIZE_PX: f64 = 60.0;
+pub const CLUSTER_MAX_ZOOM: f64 = 15.0; // individual markers from this zoom on
+pub const DENSITY_HEX_RADIUS_PX: f64 = 24.0;
+pub const CHOROPLETH_CLASSES: usize = 5;
 
 // Storage Keys
 pub const STORAGE_USER_EMAIL: &str = "user_email";
 pub const STORAGE_USER_API_KEY: &str = "user_api_key";
+pub const STORAGE_USER_CREDENTIALS: &str = "user_credentials";
+pub const STORAGE_SAVED_QUERIES: &str = "saved_queries";
+pub const STORAGE_PREFERENCES: &str = "preferences";
 
 // Default Values
 #[allow(dead_code)]
@@ -280,6 +315,270 @@ pub const AVAILABLE_COUNTRIES: &[&str] = &[
     "Zimbabwe",
 ];
 
+/// (south, west, north, east) in degrees.
+pub type Extent = (f64, f64, f64, f64);
+
+// Approximate extent of each of AVAILABLE_COUNTRIES, for framing the map before
+// any events are loaded. Territories straddling the antimeridian are clipped to
+// one side.
+pub const COUNTRY_BOUNDS: &[(&str, Extent)] = &[
+    ("Afghanistan", (29.4, 60.5, 38.5, 74.9)),
+    ("Albania", (39.6, 19.3, 42.7, 21.1)),
+    ("Algeria", (19.0, -8.7, 37.1, 12.0)),
+    ("American Samoa", (-14.6, -171.1, -14.2, -169.4)),
+    ("Andorra", (42.4, 1.4, 42.7, 1.8)),
+    ("Angola", (-18.0, 11.7, -4.4, 24.1)),
+    ("Anguilla", (18.1, -63.2, 18.3, -62.9)),
+    ("Antarctica", (-85.0, -180.0, -60.0, 180.0)),
+    ("Antigua and Barbuda", (16.9, -62.0, 17.8, -61.6)),
+    ("Argentina", (-55.1, -73.6, -21.8, -53.6)),
+    ("Armenia", (38.8, 43.4, 41.3, 46.6)),
+    ("Aruba", (12.4, -70.1, 12.6, -69.8)),
+    ("Australia", (-43.7, 113.2, -10.6, 153.6)),
+    ("Austria", (46.4, 9.5, 49.0, 17.2)),
+    ("Azerbaijan", (38.4, 44.8, 41.9, 50.4)),
+    ("Bahamas", (20.9, -79.0, 27.3, -72.7)),
+    ("Bahrain", (25.8, 50.4, 26.3, 50.7)),
+    ("Bangladesh", (20.7, 88.0, 26.6, 92.7)),
+    ("Barbados", (13.0, -59.7, 13.4, -59.4)),
+    ("Belarus", (51.3, 23.2, 56.2, 32.8)),
+    ("Belgium", (49.5, 2.5, 51.5, 6.4)),
+    ("Belize", (15.9, -89.2, 18.5, -87.5)),
+    ("Benin", (6.2, 0.8, 12.4, 3.8)),
+    ("Bermuda", (32.2, -64.9, 32.4, -64.6)),
+    ("Bhutan", (26.7, 88.7, 28.3, 92.1)),
+    ("Bolivia", (-22.9, -69.6, -9.7, -57.5)),
+    ("Bosnia and Herzegovina", (42.6, 15.7, 45.3, 19.6)),
+    ("Botswana", (-26.9, 20.0, -17.8, 29.4)),
+    ("Brazil", (-33.8, -74.0, 5.3, -34.8)),
+    ("British Indian Ocean Territory", (-7.5, 71.2, -5.2, 72.5)),
+    ("British Virgin Islands", (18.3, -64.8, 18.8, -64.3)),
+    ("Brunei", (4.0, 114.1, 5.1, 115.4)),
+    ("Bulgaria", (41.2, 22.4, 44.2, 28.6)),
+    ("Burkina Faso", (9.4, -5.5, 15.1, 2.4)),
+    ("Burundi", (-4.5, 29.0, -2.3, 30.9)),
+    ("Cambodia", (10.4, 102.3, 14.7, 107.6)),
+    ("Cameroon", (1.7, 8.5, 13.1, 16.2)),
+    ("Canada", (41.7, -141.0, 83.1, -52.6)),
+    ("Cape Verde", (14.8, -25.4, 17.2, -22.7)),
+    ("Caribbean Netherlands", (12.0, -68.4, 17.7, -62.9)),
+    ("Cayman Islands", (19.2, -81.4, 19.8, -79.7)),
+    ("Central African Republic", (2.2, 14.4, 11.0, 27.5)),
+    ("Chad", (7.4, 13.5, 23.5, 24.0)),
+    ("Chile", (-56.0, -75.7, -17.5, -66.4)),
+    ("China", (18.2, 73.5, 53.6, 134.8)),
+    ("Christmas Island", (-10.6, 105.5, -10.4, 105.7)),
+    ("Cocos (Keeling) Islands", (-12.2, 96.8, -11.8, 97.0)),
+    ("Colombia", (-4.2, -79.0, 12.5, -66.9)),
+    ("Comoros", (-12.4, 43.2, -11.4, 44.5)),
+    ("Cook Islands", (-21.9, -165.9, -8.9, -157.3)),
+    ("Costa Rica", (8.0, -85.9, 11.2, -82.6)),
+    ("Croatia", (42.4, 13.5, 46.6, 19.4)),
+    ("Cuba", (19.8, -84.9, 23.3, -74.1)),
+    ("Curacao", (12.0, -69.2, 12.4, -68.7)),
+    ("Cyprus", (34.6, 32.3, 35.7, 34.6)),
+    ("Czech Republic", (48.6, 12.1, 51.1, 18.9)),
+    ("Democratic Republic of Congo", (-13.5, 12.2, 5.4, 31.3)),
+    ("Denmark", (54.6, 8.1, 57.8, 15.2)),
+    ("Djibouti", (10.9, 41.8, 12.7, 43.4)),
+    ("Dominica", (15.2, -61.5, 15.6, -61.2)),
+    ("Dominican Republic", (17.5, -72.0, 19.9, -68.3)),
+    ("East Timor", (-9.5, 124.0, -8.1, 127.3)),
+    ("Ecuador", (-5.0, -81.1, 1.5, -75.2)),
+    ("Egypt", (22.0, 24.7, 31.7, 36.9)),
+    ("El Salvador", (13.1, -90.1, 14.5, -87.7)),
+    ("Equatorial Guinea", (-1.5, 5.6, 3.8, 11.3)),
+    ("Eritrea", (12.4, 36.4, 18.0, 43.1)),
+    ("Estonia", (57.5, 21.8, 59.7, 28.2)),
+    ("Eswatini", (-27.3, 30.8, -25.7, 32.1)),
+    ("Ethiopia", (3.4, 33.0, 14.9, 48.0)),
+    ("Faroe Islands", (61.4, -7.7, 62.4, -6.3)),
+    ("Fiji", (-19.2, 176.8, -16.0, 180.0)),
+    ("Finland", (59.8, 20.5, 70.1, 31.6)),
+    ("France", (41.3, -5.2, 51.1, 9.6)),
+    ("French Guiana", (2.1, -54.6, 5.8, -51.6)),
+    ("French Polynesia", (-27.7, -154.7, -7.9, -134.9)),
+    ("Gabon", (-3.98, 8.7, 2.3, 14.5)),
+    ("Gambia", (13.1, -16.8, 13.8, -13.8)),
+    ("Georgia", (41.1, 40.0, 43.6, 46.7)),
+    ("Germany", (47.3, 5.9, 55.1, 15.0)),
+    ("Ghana", (4.7, -3.3, 11.2, 1.2)),
+    ("Gibraltar", (36.1, -5.4, 36.2, -5.3)),
+    ("Greece", (34.8, 19.4, 41.8, 28.3)),
+    ("Greenland", (59.8, -73.3, 83.7, -11.3)),
+    ("Grenada", (11.98, -61.8, 12.5, -61.4)),
+    ("Guadeloupe", (15.8, -61.8, 16.5, -61.0)),
+    ("Guam", (13.2, 144.6, 13.7, 145.0)),
+    ("Guatemala", (13.7, -92.3, 17.8, -88.2)),
+    ("Guernsey", (49.4, -2.7, 49.7, -2.2)),
+    ("Guinea", (7.2, -15.1, 12.7, -7.6)),
+    ("Guinea-Bissau", (10.9, -16.7, 12.7, -13.6)),
+    ("Guyana", (1.2, -61.4, 8.6, -56.5)),
+    ("Haiti", (18.0, -74.5, 20.1, -71.6)),
+    (
+        "Heard Island and McDonald Islands",
+        (-53.2, 72.5, -52.9, 73.9),
+    ),
+    ("Honduras", (13.0, -89.4, 16.5, -83.1)),
+    ("Hungary", (45.7, 16.1, 48.6, 22.9)),
+    ("Iceland", (63.3, -24.5, 66.6, -13.5)),
+    ("India", (6.7, 68.1, 35.7, 97.4)),
+    ("Indonesia", (-11.0, 95.0, 6.1, 141.0)),
+    ("Iran", (25.1, 44.0, 39.8, 63.3)),
+    ("Iraq", (29.1, 38.8, 37.4, 48.6)),
+    ("Ireland", (51.4, -10.5, 55.4, -6.0)),
+    ("Isle of Man", (54.0, -4.8, 54.4, -4.3)),
+    ("Israel", (29.5, 34.3, 33.3, 35.9)),
+    ("Italy", (36.6, 6.6, 47.1, 18.5)),
+    ("Ivory Coast", (4.3, -8.6, 10.7, -2.5)),
+    ("Jamaica", (17.7, -78.4, 18.5, -76.2)),
+    ("Japan", (24.2, 122.9, 45.5, 145.8)),
+    ("Jersey", (49.2, -2.3, 49.3, -2.0)),
+    ("Jordan", (29.2, 34.9, 33.4, 39.3)),
+    ("Kazakhstan", (40.6, 46.5, 55.4, 87.3)),
+    ("Kenya", (-4.7, 33.9, 5.0, 41.9)),
+    ("Kiribati", (-2.7, 172.6, 3.4, 176.9)),
+    ("Kosovo", (41.9, 20.0, 43.3, 21.8)),
+    ("Kuwait", (28.5, 46.6, 30.1, 48.4)),
+    ("Kyrgyzstan", (39.2, 69.3, 43.3, 80.3)),
+    ("Laos", (13.9, 100.1, 22.5, 107.7)),
+    ("Latvia", (55.7, 21.0, 58.1, 28.2)),
+    ("Lebanon", (33.05, 35.1, 34.7, 36.6)),
+    ("Lesotho", (-30.7, 27.0, -28.6, 29.5)),
+    ("Liberia", (4.4, -11.5, 8.6, -7.4)),
+    ("Libya", (19.5, 9.3, 33.2, 25.2)),
+    ("Liechtenstein", (47.0, 9.5, 47.3, 9.6)),
+    ("Lithuania", (53.9, 21.0, 56.5, 26.8)),
+    ("Luxembourg", (49.4, 5.7, 50.2, 6.5)),
+    ("Madagascar", (-25.6, 43.2, -12.0, 50.5)),
+    ("Malawi", (-17.1, 32.7, -9.4, 35.9)),
+    ("Malaysia", (0.9, 99.6, 7.4, 119.3)),
+    ("Maldives", (-0.7, 72.6, 7.1, 73.8)),
+    ("Mali", (10.2, -12.2, 25.0, 4.3)),
+    ("Malta", (35.8, 14.2, 36.1, 14.6)),
+    ("Marshall Islands", (4.6, 160.8, 14.7, 172.2)),
+    ("Martinique", (14.4, -61.2, 14.9, -60.8)),
+    ("Mauritania", (14.7, -17.1, 27.3, -4.8)),
+    ("Mauritius", (-20.5, 57.3, -19.9, 57.8)),
+    ("Mayotte", (-13.0, 45.0, -12.6, 45.3)),
+    ("Mexico", (14.5, -118.4, 32.7, -86.7)),
+    ("Micronesia", (1.0, 138.0, 10.1, 163.1)),
+    ("Moldova", (45.5, 26.6, 48.5, 30.1)),
+    ("Monaco", (43.72, 7.4, 43.75, 7.44)),
+    ("Mongolia", (41.6, 87.7, 52.2, 119.9)),
+    ("Montenegro", (41.9, 18.4, 43.6, 20.4)),
+    ("Montserrat", (16.7, -62.3, 16.8, -62.1)),
+    ("Morocco", (27.7, -13.2, 35.9, -1.0)),
+    ("Mozambique", (-26.9, 30.2, -10.5, 40.8)),
+    ("Myanmar", (9.8, 92.2, 28.5, 101.2)),
+    ("Namibia", (-28.97, 11.7, -16.96, 25.3)),
+    ("Nauru", (-0.56, 166.9, -0.5, 166.96)),
+    ("Nepal", (26.3, 80.1, 30.4, 88.2)),
+    ("Netherlands", (50.8, 3.4, 53.5, 7.2)),
+    ("New Caledonia", (-22.7, 163.6, -19.5, 168.1)),
+    ("New Zealand", (-47.3, 166.4, -34.4, 178.6)),
+    ("Nicaragua", (10.7, -87.7, 15.0, -82.6)),
+    ("Niger", (11.7, 0.2, 23.5, 16.0)),
+    ("Nigeria", (4.3, 2.7, 13.9, 14.7)),
+    ("Niue", (-19.2, -170.0, -18.9, -169.8)),
+    ("North Korea", (37.7, 124.2, 43.0, 130.7)),
+    ("North Macedonia", (40.9, 20.5, 42.4, 23.0)),
+    ("Northern Mariana Islands", (14.1, 145.1, 20.6, 146.1)),
+    ("Norfolk Island", (-29.1, 167.9, -29.0, 168.0)),
+    ("Norway", (57.9, 4.6, 71.2, 31.1)),
+    ("Oman", (16.6, 52.0, 26.4, 59.8)),
+    ("Pakistan", (23.7, 60.9, 37.1, 77.8)),
+    ("Palau", (2.9, 131.1, 8.1, 134.7)),
+    ("Palestine", (31.2, 34.2, 32.6, 35.6)),
+    ("Panama", (7.2, -83.1, 9.7, -77.2)),
+    ("Papua New Guinea", (-11.7, 140.8, -1.3, 156.0)),
+    ("Paraguay", (-27.6, -62.6, -19.3, -54.3)),
+    ("Peru", (-18.4, -81.4, -0.04, -68.7)),
+    ("Philippines", (4.6, 116.9, 21.1, 126.6)),
+    ("Pitcairn", (-25.1, -130.8, -24.3, -124.8)),
+    ("Poland", (49.0, 14.1, 54.8, 24.2)),
+    ("Portugal", (36.9, -9.5, 42.2, -6.2)),
+    ("Puerto Rico", (17.9, -67.3, 18.5, -65.6)),
+    ("Qatar", (24.5, 50.7, 26.2, 51.6)),
+    ("Republic of Congo", (-5.0, 11.1, 3.7, 18.6)),
+    ("Reunion", (-21.4, 55.2, -20.9, 55.8)),
+    ("Romania", (43.6, 20.3, 48.3, 29.7)),
+    ("Russia", (41.2, 19.6, 81.9, 180.0)),
+    ("Rwanda", (-2.8, 28.9, -1.1, 30.9)),
+    ("Saint Kitts and Nevis", (17.1, -62.9, 17.4, -62.5)),
+    ("Saint Lucia", (13.7, -61.1, 14.1, -60.9)),
+    ("Saint Vincent and Grenadines", (12.6, -61.5, 13.4, -61.1)),
+    ("Saint-Barthélemy", (17.87, -62.95, 17.97, -62.78)),
+    (
+        "Saint Helena, Ascension and Tristan da Cunha",
+        (-40.4, -14.5, -7.9, -5.6),
+    ),
+    ("Saint Martin", (18.05, -63.15, 18.13, -63.0)),
+    ("Saint Pierre and Miquelon", (46.7, -56.5, 47.2, -56.1)),
+    ("Samoa", (-14.1, -172.8, -13.4, -171.4)),
+    ("San Marino", (43.9, 12.4, 44.0, 12.5)),
+    ("Sao Tome and Principe", (0.0, 6.4, 1.7, 7.5)),
+    ("Saudi Arabia", (16.3, 34.5, 32.2, 55.7)),
+    ("Senegal", (12.3, -17.6, 16.7, -11.3)),
+    ("Serbia", (42.2, 18.8, 46.2, 23.0)),
+    ("Seychelles", (-9.8, 46.2, -4.2, 56.3)),
+    ("Sierra Leone", (6.9, -13.3, 10.0, -10.3)),
+    ("Singapore", (1.2, 103.6, 1.5, 104.1)),
+    ("Sint Maarten", (18.0, -63.15, 18.07, -63.0)),
+    ("Slovakia", (47.7, 16.8, 49.6, 22.6)),
+    ("Slovenia", (45.4, 13.4, 46.9, 16.6)),
+    ("Solomon Islands", (-11.9, 155.4, -6.5, 167.3)),
+    ("Somalia", (-1.7, 40.9, 12.0, 51.4)),
+    ("South Africa", (-34.9, 16.4, -22.1, 32.9)),
+    (
+        "South Georgia and the South Sandwich Islands",
+        (-59.5, -38.1, -53.9, -26.2),
+    ),
+    ("South Korea", (33.1, 124.6, 38.6, 131.9)),
+    ("South Sudan", (3.5, 23.4, 12.2, 35.9)),
+    ("Spain", (36.0, -9.4, 43.8, 3.3)),
+    ("Sri Lanka", (5.9, 79.6, 9.9, 81.9)),
+    ("Sudan", (8.7, 21.8, 22.2, 38.6)),
+    ("Suriname", (1.8, -58.1, 6.0, -53.9)),
+    ("Sweden", (55.3, 11.1, 69.1, 24.2)),
+    ("Switzerland", (45.8, 5.9, 47.8, 10.5)),
+    ("Syria", (32.3, 35.7, 37.3, 42.4)),
+    ("Taiwan", (21.9, 120.0, 25.3, 122.0)),
+    ("Tajikistan", (36.7, 67.3, 41.0, 75.2)),
+    ("Tanzania", (-11.8, 29.3, -1.0, 40.4)),
+    ("Thailand", (5.6, 97.3, 20.5, 105.6)),
+    ("Togo", (6.1, -0.15, 11.1, 1.8)),
+    ("Tokelau", (-9.4, -172.5, -8.5, -171.2)),
+    ("Tonga", (-22.4, -176.2, -15.6, -173.7)),
+    ("Trinidad and Tobago", (10.0, -61.9, 11.4, -60.5)),
+    ("Tunisia", (30.2, 7.5, 37.6, 11.6)),
+    ("Turkey", (35.8, 25.7, 42.1, 44.8)),
+    ("Turkmenistan", (35.1, 52.4, 42.8, 66.7)),
+    ("Turks and Caicos Islands", (21.2, -72.5, 21.96, -71.1)),
+    ("Tuvalu", (-10.8, 176.1, -5.6, 179.9)),
+    ("Uganda", (-1.5, 29.6, 4.2, 35.0)),
+    ("Ukraine", (44.4, 22.1, 52.4, 40.2)),
+    ("United Arab Emirates", (22.6, 51.6, 26.1, 56.4)),
+    ("United Kingdom", (49.9, -8.6, 60.9, 1.8)),
+    ("United States", (24.4, -125.0, 49.4, -66.9)),
+    // The Pacific islands east of the antimeridian; Wake and Navassa are left out
+    (
+        "United States Minor Outlying Islands",
+        (-0.4, -177.4, 28.2, -160.0),
+    ),
+    ("Uruguay", (-35.0, -58.4, -30.1, -53.1)),
+    ("Uzbekistan", (37.2, 56.0, 45.6, 73.1)),
+    ("Vanuatu", (-20.3, 166.5, -13.1, 170.2)),
+    ("Vatican City", (41.9, 12.445, 41.907, 12.458)),
+    ("Venezuela", (0.6, -73.4, 12.2, -59.8)),
+    ("Vietnam", (8.4, 102.1, 23.4, 109.5)),
+    ("Virgin Islands, U.S.", (17.7, -65.1, 18.4, -64.6)),
+    ("Wallis and Futuna", (-14.4, -178.2, -13.2, -176.1)),
+    ("Yemen", (12.1, 42.5, 19.0, 54.5)),
+    ("Zambia", (-18.1, 22.0, -8.2, 33.7)),
+    ("Zimbabwe", (-22.4, 25.2, -15.6, 33.1)),
+];
+
 pub const AVAILABLE_EVENT_TYPES: &[&str] = &[
     "Battles",
     "Explosions/Remote violence",
@@ -289,7 +588,31 @@ pub const AVAILABLE_EVENT_TYPES: &[&str] = &[
     "Strategic developments",
 ];
 
+// Marker colors per event type (ColorBrewer qualitative), overridable in the
+// preferences; other types use the fallback
+pub const EVENT_TYPE_COLORS: &[(&str, &str)] = &[
+    ("Battles", "#e41a1c"),
+    ("Explosions/Remote violence", "#ff7f00"),
+    ("Violence against civilians", "#984ea3"),
+    ("Riots", "#a65628"),
+    ("Protests", "#377eb8"),
+    ("Strategic developments", "#4daf4a"),
+];
+pub const FALLBACK_EVENT_COLOR: &str = "#7f7f7f";
+pub const MARKER_MIN_RADIUS_PX: f64 = 5.0;
+pub const MARKER_MAX_RADIUS_PX: f64 = 20.0;
+
+// Timeline Configuration
+pub const TIMELINE_PLAYBACK_INTERVAL_MS: u32 = 400;
+
+// Logging Configuration
+pub const LOG_BUFFER_SIZE: usize = 2_000; // entries kept for the log viewer
+pub const LOG_FLUSH_INTERVAL_MS: u32 = 1_000; // batching of desktop log file writes
+
 // UI Constants
+pub const TOAST_DURATION_MS: u32 = 6_000; // info and warning toasts; errors stay until dismissed
+pub const MAX_ERROR_HISTORY: usize = 100;
+pub const DOWNLOAD_REVOKE_DELAY_MS: u32 = 10_000; // object URLs outlive the download start
 #[allow(dead_code)]
 pub const DEBOUNCE_DELAY_MS: u32 = 500;
 #[allow(dead_code)]
@@ -82,19 +82,22 @@ impl AppError {
         }
     }
 
-    #[allow(dead_code)]
     pub fn unknown(message: impl Into<String>) -> Self {
         Self::Unknown {
             message: message.into(),
         }
     }
 
-    #[allow(dead_code)]
+    /// Whether the same request may succeed if retried: network failures and
+    /// timeouts, rate limiting and server errors.
     pub fn is_recoverable(&self) -> bool {
-        matches!(self, AppError::Network { .. } | AppError::Api { .. })
+        match self {
+            AppError::Network { .. } => true,
+            AppError::Api { status, .. } => *status == 429 || *status >= 500,
+            _ => false,
+        }
     }
 
-    #[allow(dead_code)]
     pub fn severity(&self) -> ErrorSeverity {
         match self {
             AppError::Api { status, .. } if *status >= 500 => ErrorSeverity::Critical,
@@ -106,10 +109,42 @@ impl AppError {
             AppError::Unknown { .. } => ErrorSeverity::Critical,
         }
     }
+
+    /// Something the user can do about the error, where there is one.
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            AppError::Api { status: 401 | 403, .. } => {
+                Some("Check your API key and email, or log in again to renew the session.")
+            }
+            AppError::Api { status: 429, .. } => {
+                Some("ACLED is rate limiting requests; wait a minute before fetching again.")
+            }
+            AppError::Api {
+                status: 413 | 414, ..
+            } => Some("The request is too large; reduce the limit, date range or countries."),
+            AppError::Api { status, .. } if *status >= 500 => {
+                Some("ACLED's servers are having trouble; try again later or reduce the limit.")
+            }
+            AppError::Api { message, .. } if message.to_lowercase().contains("limit") => {
+                Some("Reduce the limit, or enable \"Fetch all pages\" to page through results.")
+            }
+            AppError::Network { message } if message.contains("timed out") => {
+                Some("The request took too long; reduce the limit or the date range.")
+            }
+            AppError::Network { .. } => Some("Check your internet connection."),
+            AppError::Validation { .. } => Some("Correct the parameters and try again."),
+            AppError::Storage { .. } => {
+                Some("Saved data could not be accessed; check storage permissions or the system keyring.")
+            }
+            AppError::Serialization { .. } => {
+                Some("The response could not be read; reducing the limit may help.")
+            }
+            _ => None,
+        }
+    }
 }
 
-#[allow(dead_code)]
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ErrorSeverity {
     Info,
     Warning,
@@ -117,6 +152,17 @@ pub enum ErrorSeverity {
     Critical,
 }
 
+impl ErrorSeverity {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ErrorSeverity::Info => "info",
+            ErrorSeverity::Warning => "warning",
+            ErrorSeverity::Error => "error",
+            ErrorSeverity::Critical => "critical",
+        }
+    }
+}
+
 pub type AppResult<T> = Result<T, AppError>;
 
 // Helper trait for converting common errors
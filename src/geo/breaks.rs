@@ -0,0 +1,221 @@
+//! Class breaks for choropleth maps.
+
+/// How region values are divided into color classes.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum ClassBreaks {
+    /// The same number of regions in each class.
+    #[default]
+    Quantile,
+    /// Classes of equal width between the minimum and maximum.
+    EqualInterval,
+    /// Jenks natural breaks: classes that minimize the variance within each.
+    Jenks,
+}
+
+impl ClassBreaks {
+    pub const ALL: [ClassBreaks; 3] = [
+        ClassBreaks::Quantile,
+        ClassBreaks::EqualInterval,
+        ClassBreaks::Jenks,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ClassBreaks::Quantile => "Quantile",
+            ClassBreaks::EqualInterval => "Equal interval",
+            ClassBreaks::Jenks => "Natural breaks (Jenks)",
+        }
+    }
+}
+
+/// Upper bound of each class, ascending, the last being the largest value.
+/// Classes that would repeat a bound are merged, so there can be fewer than
+/// `classes`.
+pub fn class_breaks(values: &[f64], method: ClassBreaks, classes: usize) -> Vec<f64> {
+    let mut sorted: Vec<f64> = values.iter().copied().filter(|v| v.is_finite()).collect();
+    sorted.sort_by(f64::total_cmp);
+    let (Some(&min), Some(&max)) = (sorted.first(), sorted.last()) else {
+        return Vec::new();
+    };
+    let classes = classes.max(1);
+
+    let mut breaks: Vec<f64> = match method {
+        ClassBreaks::Quantile => (1..=classes)
+            .map(|class| sorted[(class * sorted.len()).div_ceil(classes) - 1])
+            .collect(),
+        ClassBreaks::EqualInterval => (1..=classes)
+            .map(|class| min + (max - min) * class as f64 / classes as f64)
+            .collect(),
+        ClassBreaks::Jenks => jenks(&sorted, classes),
+    };
+
+    breaks.dedup();
+    // Guard against rounding leaving the maximum out of the last class
+    if let Some(last) = breaks.last_mut() {
+        *last = max;
+    }
+    breaks
+}
+
+/// Index of the class `value` falls into.
+pub fn class_index(value: f64, breaks: &[f64]) -> usize {
+    breaks
+        .iter()
+        .position(|&upper| value <= upper)
+        .unwrap_or(breaks.len().saturating_sub(1))
+}
+
+// Fisher-Jenks dynamic programming over sorted values. Indices into `sorted`
+// are 1-based to follow the usual statement of the algorithm;
+// `lower[i][j]` is where class `j` starts in the best split of the first `i`
+// values into `j` classes.
+fn jenks(sorted: &[f64], classes: usize) -> Vec<f64> {
+    let n = sorted.len();
+    let k = classes.min(n);
+
+    let mut lower = vec![vec![0usize; k + 1]; n + 1];
+    let mut cost = vec![vec![f64::INFINITY; k + 1]; n + 1];
+    for j in 1..=k {
+        lower[1][j] = 1;
+        cost[1][j] = 0.0;
+    }
+
+    for end in 2..=n {
+        let (mut sum, mut sum_squares, mut variance) = (0.0, 0.0, 0.0);
+
+        for size in 1..=end {
+            let start = end - size + 1;
+            let value = sorted[start - 1];
+            sum += value;
+            sum_squares += value * value;
+            variance = sum_squares - sum * sum / size as f64;
+
+            if start > 1 {
+                for j in 2..=k {
+                    let candidate = variance + cost[start - 1][j - 1];
+                    if cost[end][j] >= candidate {
+                        lower[end][j] = start;
+                        cost[end][j] = candidate;
+                    }
+                }
+            }
+        }
+
+        lower[end][1] = 1;
+        cost[end][1] = variance;
+    }
+
+    let mut breaks = vec![sorted[n - 1]; k];
+    let mut end = n;
+    for j in (2..=k).rev() {
+        let start = lower[end][j];
+        breaks[j - 2] = sorted[start.saturating_sub(2)];
+        end = start.saturating_sub(1).max(1);
+    }
+    breaks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(to: u32) -> Vec<f64> {
+        (1..=to).map(f64::from).collect()
+    }
+
+    #[test]
+    fn quantile_breaks_split_counts_evenly() {
+        assert_eq!(
+            class_breaks(&range(10), ClassBreaks::Quantile, 5),
+            vec![2.0, 4.0, 6.0, 8.0, 10.0]
+        );
+        assert_eq!(
+            class_breaks(&range(7), ClassBreaks::Quantile, 3),
+            vec![3.0, 5.0, 7.0]
+        );
+        // Input order doesn't matter
+        assert_eq!(
+            class_breaks(&[9.0, 1.0, 5.0, 3.0], ClassBreaks::Quantile, 2),
+            vec![3.0, 9.0]
+        );
+    }
+
+    #[test]
+    fn equal_interval_breaks_split_the_range() {
+        assert_eq!(
+            class_breaks(&[0.0, 3.0, 10.0], ClassBreaks::EqualInterval, 5),
+            vec![2.0, 4.0, 6.0, 8.0, 10.0]
+        );
+        assert_eq!(
+            class_breaks(&[1.0, 4.0], ClassBreaks::EqualInterval, 4),
+            vec![1.75, 2.5, 3.25, 4.0]
+        );
+    }
+
+    #[test]
+    fn jenks_breaks_find_natural_groups() {
+        let values = [20.0, 1.0, 11.0, 2.0, 22.0, 3.0, 10.0, 21.0, 12.0];
+        assert_eq!(
+            class_breaks(&values, ClassBreaks::Jenks, 3),
+            vec![3.0, 12.0, 22.0]
+        );
+        assert_eq!(
+            class_breaks(&[1.0, 1.0, 2.0, 2.0, 9.0, 10.0], ClassBreaks::Jenks, 2),
+            vec![2.0, 10.0]
+        );
+        assert_eq!(
+            class_breaks(&[0.0, 0.0, 0.0, 1.0, 50.0], ClassBreaks::Jenks, 2),
+            vec![1.0, 50.0]
+        );
+    }
+
+    #[test]
+    fn jenks_handles_fewer_values_than_classes() {
+        assert_eq!(
+            class_breaks(&[1.0, 5.0], ClassBreaks::Jenks, 5),
+            vec![1.0, 5.0]
+        );
+        assert_eq!(class_breaks(&[4.0], ClassBreaks::Jenks, 5), vec![4.0]);
+    }
+
+    #[test]
+    fn repeated_bounds_are_merged() {
+        for method in ClassBreaks::ALL {
+            assert_eq!(
+                class_breaks(&[5.0; 6], method, 5),
+                vec![5.0],
+                "{:?}",
+                method
+            );
+        }
+        assert_eq!(
+            class_breaks(&[0.0, 0.0, 0.0, 0.0, 8.0], ClassBreaks::Quantile, 4),
+            vec![0.0, 8.0]
+        );
+    }
+
+    #[test]
+    fn degenerate_inputs() {
+        for method in ClassBreaks::ALL {
+            assert!(class_breaks(&[], method, 5).is_empty());
+            assert!(class_breaks(&[f64::NAN], method, 5).is_empty());
+            assert_eq!(class_breaks(&[1.0, 7.0], method, 0), vec![7.0]);
+            assert_eq!(
+                class_breaks(&[2.0, f64::NAN, 6.0, f64::INFINITY], method, 1),
+                vec![6.0]
+            );
+        }
+    }
+
+    #[test]
+    fn class_index_uses_inclusive_upper_bounds() {
+        let breaks = [2.0, 4.0, 6.0];
+        assert_eq!(class_index(0.0, &breaks), 0);
+        assert_eq!(class_index(2.0, &breaks), 0);
+        assert_eq!(class_index(2.5, &breaks), 1);
+        assert_eq!(class_index(6.0, &breaks), 2);
+        // Values past the last break land in the top class
+        assert_eq!(class_index(60.0, &breaks), 2);
+        assert_eq!(class_index(3.0, &[]), 0);
+    }
+}
@@ -0,0 +1,634 @@
+//! Totals per region for choropleth maps, grouping events by ACLED's admin
+//! names or by the polygons of a boundary GeoJSON.
+
+use super::projection::Bounds;
+use super::selection::polygon_contains;
+use super::DensityWeight;
+use crate::errors::{AppError, AppResult, IntoAppError};
+use crate::types::AcledEvent;
+use crate::utils::text::fold;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+// Properties naming a feature, by boundary level, covering geoBoundaries
+// (`shapeName`), GADM (`NAME_1`) and OCHA COD (`ADM1_EN`) files
+const ADMIN1_NAME_KEYS: &[&str] = &[
+    "ADM1_EN",
+    "NAME_1",
+    "admin1Name",
+    "shapeName",
+    "name",
+    "NAME",
+];
+const ADMIN2_NAME_KEYS: &[&str] = &[
+    "ADM2_EN",
+    "NAME_2",
+    "admin2Name",
+    "shapeName",
+    "name",
+    "NAME",
+];
+const POLYGON_NAME_KEYS: &[&str] = &[
+    "shapeName",
+    "name",
+    "NAME",
+    "ADM2_EN",
+    "NAME_2",
+    "ADM1_EN",
+    "NAME_1",
+];
+// Admin 1 names carried by admin 2 features, to tell same-named districts apart
+const PARENT_NAME_KEYS: &[&str] = &["ADM1_EN", "NAME_1", "admin1Name"];
+
+/// What groups events into regions.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum RegionLevel {
+    /// ACLED's `admin1` name, matched to boundary features by name.
+    #[default]
+    Admin1,
+    /// ACLED's `admin2` name, matched to boundary features by name.
+    Admin2,
+    /// The boundary polygon containing each event.
+    Polygons,
+}
+
+impl RegionLevel {
+    pub const ALL: [RegionLevel; 3] = [
+        RegionLevel::Admin1,
+        RegionLevel::Admin2,
+        RegionLevel::Polygons,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            RegionLevel::Admin1 => "Admin 1",
+            RegionLevel::Admin2 => "Admin 2",
+            RegionLevel::Polygons => "Boundary polygons",
+        }
+    }
+
+    fn name_keys(self) -> &'static [&'static str] {
+        match self {
+            RegionLevel::Admin1 => ADMIN1_NAME_KEYS,
+            RegionLevel::Admin2 => ADMIN2_NAME_KEYS,
+            RegionLevel::Polygons => POLYGON_NAME_KEYS,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct BoundaryFeature {
+    /// String and numeric properties, as text.
+    pub properties: BTreeMap<String, String>,
+    /// `(lat, lng)` rings of every polygon, outer rings and holes alike.
+    pub rings: Vec<Vec<(f64, f64)>>,
+    pub bounds: Bounds,
+}
+
+impl BoundaryFeature {
+    // Holes lie inside their outer ring and polygons don't overlap, so a
+    // point is inside when an odd number of rings contain it
+    fn contains(&self, lat: f64, lng: f64) -> bool {
+        let bounds = &self.bounds;
+        (bounds.south..=bounds.north).contains(&lat)
+            && (bounds.west..=bounds.east).contains(&lng)
+            && self
+                .rings
+                .iter()
+                .filter(|ring| polygon_contains(ring, lat, lng))
+                .count()
+                % 2
+                == 1
+    }
+
+    pub fn name(&self, level: RegionLevel) -> Option<&str> {
+        level
+            .name_keys()
+            .iter()
+            .find_map(|key| self.properties.get(*key))
+            .map(String::as_str)
+            .filter(|name| !name.trim().is_empty())
+    }
+}
+
+/// Polygons loaded from a GeoJSON file; other geometries are skipped.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Boundaries {
+    pub name: String,
+    pub features: Vec<BoundaryFeature>,
+}
+
+impl Boundaries {
+    /// Reads a FeatureCollection or a single Feature with `[lng, lat]`
+    /// (WGS 84) coordinates.
+    pub fn from_geojson(name: &str, json: &str) -> AppResult<Self> {
+        let root: Value = serde_json::from_str(json).into_app_error()?;
+        let features = match root["type"].as_str() {
+            Some("FeatureCollection") => root["features"].as_array().cloned().unwrap_or_default(),
+            Some("Feature") => vec![root],
+            _ => {
+                return Err(AppError::validation(
+                    "boundaries",
+                    "Expected a GeoJSON FeatureCollection or Feature",
+                ))
+            }
+        };
+
+        let features: Vec<BoundaryFeature> = features.iter().filter_map(parse_feature).collect();
+        if features.is_empty() {
+            return Err(AppError::validation(
+                "boundaries",
+                format!("{} has no Polygon or MultiPolygon features", name),
+            ));
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            features,
+        })
+    }
+}
+
+fn parse_feature(feature: &Value) -> Option<BoundaryFeature> {
+    let geometry = &feature["geometry"];
+    let polygons = match geometry["type"].as_str()? {
+        "Polygon" => vec![&geometry["coordinates"]],
+        "MultiPolygon" => geometry["coordinates"].as_array()?.iter().collect(),
+        _ => return None,
+    };
+
+    let rings: Vec<Vec<(f64, f64)>> = polygons
+        .into_iter()
+        .filter_map(Value::as_array)
+        .flatten()
+        .filter_map(parse_ring)
+        .collect();
+
+    let mut points = rings.iter().flatten();
+    let &(lat, lng) = points.next()?;
+    let bounds = points.fold(Bounds::from_point(lat, lng), |mut bounds, &(lat, lng)| {
+        bounds.extend(lat, lng);
+        bounds
+    });
+
+    let properties = feature["properties"]
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter_map(|(key, value)| {
+            let text = match value {
+                Value::String(text) => text.clone(),
+                Value::Number(number) => number.to_string(),
+                _ => return None,
+            };
+            Some((key.clone(), text))
+        })
+        .collect();
+
+    Some(BoundaryFeature {
+        properties,
+        rings,
+        bounds,
+    })
+}
+
+fn parse_ring(ring: &Value) -> Option<Vec<(f64, f64)>> {
+    let points: Vec<(f64, f64)> = ring
+        .as_array()?
+        .iter()
+        .filter_map(|position| Some((position[1].as_f64()?, position[0].as_f64()?)))
+        .collect();
+    (points.len() >= 3).then_some(points)
+}
+
+/// Events grouped into one region.
+#[derive(Clone, PartialEq, Debug)]
+pub struct RegionTotal {
+    pub name: String,
+    pub count: usize,
+    pub fatalities: u32,
+    /// Index of the boundary feature drawn for the region, if one matched.
+    pub feature: Option<usize>,
+}
+
+impl RegionTotal {
+    pub fn value(&self, weight: DensityWeight) -> f64 {
+        match weight {
+            DensityWeight::Count => self.count as f64,
+            DensityWeight::Fatalities => f64::from(self.fatalities),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct RegionSummary {
+    /// Most events first. With boundaries loaded, features without events are
+    /// included with zero totals.
+    pub totals: Vec<RegionTotal>,
+    /// Events without a region: no admin name, or outside every polygon.
+    pub unassigned: usize,
+}
+
+impl RegionSummary {
+    /// Regions with events but no boundary feature to draw.
+    pub fn unmatched(&self) -> usize {
+        self.totals
+            .iter()
+            .filter(|total| total.feature.is_none())
+            .count()
+    }
+}
+
+/// Totals `events` by region. Grouping by polygons needs `boundaries`; the
+/// admin levels work without them but then have nothing to draw.
+pub fn region_totals(
+    events: &[AcledEvent],
+    level: RegionLevel,
+    boundaries: Option<&Boundaries>,
+) -> RegionSummary {
+    let mut summary = match level {
+        RegionLevel::Admin1 | RegionLevel::Admin2 => admin_totals(events, level, boundaries),
+        RegionLevel::Polygons => polygon_totals(events, boundaries),
+    };
+    summary.totals.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then(b.fatalities.cmp(&a.fatalities))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    summary
+}
+
+fn admin_totals(
+    events: &[AcledEvent],
+    level: RegionLevel,
+    boundaries: Option<&Boundaries>,
+) -> RegionSummary {
+    // Admin 2 names repeat across provinces, so prefer features that also
+    // carry a matching admin 1 name, and fall back to the bare name only when
+    // no other feature shares it
+    let mut lookup = HashMap::<String, usize>::new();
+    let mut names = HashMap::<String, (usize, usize)>::new();
+    for (index, feature) in boundaries
+        .iter()
+        .flat_map(|boundaries| &boundaries.features)
+        .enumerate()
+    {
+        let Some(name) = feature.name(level) else {
+            continue;
+        };
+        let name = fold(name.trim());
+        if level == RegionLevel::Admin2 {
+            if let Some(parent) = PARENT_NAME_KEYS
+                .iter()
+                .find_map(|key| feature.properties.get(*key))
+            {
+                lookup
+                    .entry(format!("{}/{}", fold(parent.trim()), name))
+                    .or_insert(index);
+            }
+        }
+        names.entry(name).or_insert((index, 0)).1 += 1;
+    }
+
+    let mut regions = BTreeMap::<(String, String), RegionTotal>::new();
+    let mut unassigned = 0;
+    for event in events {
+        let (parent, name) = match level {
+            RegionLevel::Admin2 => (event.admin1.trim(), event.admin2.trim()),
+            _ => ("", event.admin1.trim()),
+        };
+        if name.is_empty() {
+            unassigned += 1;
+            continue;
+        }
+
+        // Spellings differing only in case or accents are one region, named
+        // as first seen
+        let (parent_key, key) = (fold(parent), fold(name));
+        let total = regions
+            .entry((parent_key.clone(), key.clone()))
+            .or_insert_with(|| RegionTotal {
+                name: match parent {
+                    "" => name.to_string(),
+                    parent => format!("{}, {}", name, parent),
+                },
+                count: 0,
+                fatalities: 0,
+                feature: lookup
+                    .get(&format!("{}/{}", parent_key, key))
+                    .copied()
+                    .or_else(|| {
+                        names
+                            .get(&key)
+                            .filter(|(_, features)| level != RegionLevel::Admin2 || *features == 1)
+                            .map(|(index, _)| *index)
+                    }),
+            });
+        total.count += 1;
+        total.fatalities += event.fatality_count();
+    }
+
+    let mut totals: Vec<RegionTotal> = regions.into_values().collect();
+    if let Some(boundaries) = boundaries {
+        let mut drawn = vec![false; boundaries.features.len()];
+        for index in totals.iter().filter_map(|total| total.feature) {
+            drawn[index] = true;
+        }
+        totals.extend(empty_regions(boundaries, level, &drawn));
+    }
+
+    RegionSummary { totals, unassigned }
+}
+
+fn polygon_totals(events: &[AcledEvent], boundaries: Option<&Boundaries>) -> RegionSummary {
+    let Some(boundaries) = boundaries else {
+        return RegionSummary {
+            totals: Vec::new(),
+            unassigned: events.len(),
+        };
+    };
+
+    let mut counts = vec![(0usize, 0u32); boundaries.features.len()];
+    let mut unassigned = 0;
+    for event in events {
+        let feature = event.coordinates().and_then(|(lat, lng)| {
+            boundaries
+                .features
+                .iter()
+                .position(|feature| feature.contains(lat, lng))
+        });
+        match feature {
+            Some(index) => {
+                counts[index].0 += 1;
+                counts[index].1 += event.fatality_count();
+            }
+            None => unassigned += 1,
+        }
+    }
+
+    let totals = counts
+        .into_iter()
+        .enumerate()
+        .map(|(index, (count, fatalities))| RegionTotal {
+            name: feature_label(boundaries, index, RegionLevel::Polygons),
+            count,
+            fatalities,
+            feature: Some(index),
+        })
+        .collect();
+
+    RegionSummary { totals, unassigned }
+}
+
+fn empty_regions<'a>(
+    boundaries: &'a Boundaries,
+    level: RegionLevel,
+    drawn: &'a [bool],
+) -> impl Iterator<Item = RegionTotal> + 'a {
+    drawn
+        .iter()
+        .enumerate()
+        .filter(|(_, drawn)| !**drawn)
+        .map(move |(index, _)| RegionTotal {
+            name: feature_label(boundaries, index, level),
+            count: 0,
+            fatalities: 0,
+            feature: Some(index),
+        })
+}
+
+fn feature_label(boundaries: &Boundaries, index: usize, level: RegionLevel) -> String {
+    boundaries.features[index]
+        .name(level)
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("Feature {}", index + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn event(admin1: &str, admin2: &str, fatalities: Option<u32>) -> AcledEvent {
+        AcledEvent {
+            admin1: admin1.to_string(),
+            admin2: admin2.to_string(),
+            fatalities,
+            ..Default::default()
+        }
+    }
+
+    fn located(lat: f64, lng: f64, fatalities: u32) -> AcledEvent {
+        AcledEvent {
+            latitude: Some(lat),
+            longitude: Some(lng),
+            fatalities: Some(fatalities),
+            ..Default::default()
+        }
+    }
+
+    // An axis-aligned square ring in GeoJSON's `[lng, lat]` order
+    fn square(south: f64, west: f64, size: f64) -> Value {
+        let (north, east) = (south + size, west + size);
+        json!([
+            [west, south],
+            [east, south],
+            [east, north],
+            [west, north],
+            [west, south]
+        ])
+    }
+
+    fn feature(properties: Value, geometry: Value) -> Value {
+        json!({ "type": "Feature", "properties": properties, "geometry": geometry })
+    }
+
+    fn boundaries(features: Vec<Value>) -> Boundaries {
+        let collection = json!({ "type": "FeatureCollection", "features": features });
+        Boundaries::from_geojson("test.geojson", &collection.to_string()).unwrap()
+    }
+
+    fn polygon(properties: Value, south: f64, west: f64) -> Value {
+        feature(
+            properties,
+            json!({ "type": "Polygon", "coordinates": [square(south, west, 1.0)] }),
+        )
+    }
+
+    fn total<'a>(summary: &'a RegionSummary, name: &str) -> &'a RegionTotal {
+        summary
+            .totals
+            .iter()
+            .find(|total| total.name == name)
+            .unwrap_or_else(|| panic!("no region {}", name))
+    }
+
+    #[test]
+    fn admin1_totals_without_boundaries() {
+        let events = [
+            event("Borno", "", Some(3)),
+            event(" Borno ", "", None),
+            event("Lagos", "", Some(10)),
+            event("", "", Some(7)),
+        ];
+        let summary = region_totals(&events, RegionLevel::Admin1, None);
+
+        let totals: Vec<(&str, usize, u32)> = summary
+            .totals
+            .iter()
+            .map(|total| (total.name.as_str(), total.count, total.fatalities))
+            .collect();
+        assert_eq!(totals, vec![("Borno", 2, 3), ("Lagos", 1, 10)]);
+        assert_eq!(summary.unassigned, 1);
+        assert_eq!(summary.unmatched(), 2);
+    }
+
+    #[test]
+    fn admin1_names_match_features_loosely() {
+        let regions = boundaries(vec![
+            polygon(json!({ "shapeName": "Ségou" }), 13.0, -6.0),
+            polygon(json!({ "shapeName": "Mopti" }), 14.0, -4.0),
+            polygon(json!({ "shapeName": "Kidal" }), 18.0, 1.0),
+        ]);
+        let events = [
+            event("Segou", "", Some(1)),
+            event("MOPTI", "", Some(2)),
+            event("Mopti", "", None),
+            event("Tombouctou", "", None),
+        ];
+        let summary = region_totals(&events, RegionLevel::Admin1, Some(&regions));
+
+        assert_eq!(total(&summary, "Segou").feature, Some(0));
+        // The first spelling seen names the region; both share one total
+        let mopti = total(&summary, "MOPTI");
+        assert_eq!(
+            (mopti.count, mopti.fatalities, mopti.feature),
+            (2, 2, Some(1))
+        );
+        assert!(!summary.totals.iter().any(|total| total.name == "Mopti"));
+        assert_eq!(total(&summary, "Tombouctou").feature, None);
+
+        // Features without events are listed with zero totals
+        let kidal = total(&summary, "Kidal");
+        assert_eq!((kidal.count, kidal.feature), (0, Some(2)));
+        assert_eq!(summary.totals.last().unwrap().name, "Kidal");
+    }
+
+    #[test]
+    fn admin2_names_prefer_the_matching_parent() {
+        let regions = boundaries(vec![
+            polygon(
+                json!({ "ADM2_EN": "Central", "ADM1_EN": "North" }),
+                10.0,
+                0.0,
+            ),
+            polygon(
+                json!({ "ADM2_EN": "Central", "ADM1_EN": "South" }),
+                0.0,
+                0.0,
+            ),
+        ]);
+        let events = [
+            event("South", "Central", Some(4)),
+            event("North", "Central", Some(1)),
+            event("East", "Central", Some(2)),
+        ];
+        let summary = region_totals(&events, RegionLevel::Admin2, Some(&regions));
+
+        assert_eq!(total(&summary, "Central, North").feature, Some(0));
+        assert_eq!(total(&summary, "Central, South").feature, Some(1));
+        // Two features share the bare name, so an unknown parent matches neither
+        assert_eq!(total(&summary, "Central, East").feature, None);
+        assert_eq!(summary.unmatched(), 1);
+    }
+
+    #[test]
+    fn admin2_bare_names_match_only_when_unique() {
+        let regions = boundaries(vec![
+            polygon(json!({ "NAME_2": "Lakeside" }), 0.0, 0.0),
+            polygon(json!({ "NAME_2": "Hills" }), 1.0, 0.0),
+            polygon(json!({ "NAME_2": "Hills" }), 2.0, 0.0),
+        ]);
+        let events = [
+            event("West", "Lakeside", None),
+            event("West", "Hills", None),
+            event("West", "", Some(5)),
+        ];
+        let summary = region_totals(&events, RegionLevel::Admin2, Some(&regions));
+
+        assert_eq!(total(&summary, "Lakeside, West").feature, Some(0));
+        assert_eq!(total(&summary, "Hills, West").feature, None);
+        assert_eq!(summary.unassigned, 1);
+        // Both Hills features stay on the map, empty
+        assert_eq!(
+            summary
+                .totals
+                .iter()
+                .filter(|total| total.name == "Hills")
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn polygon_totals_respect_holes_and_multipolygons() {
+        let ring_with_hole = json!({
+            "type": "Polygon",
+            "coordinates": [square(0.0, 0.0, 10.0), square(4.0, 4.0, 2.0)]
+        });
+        let islands = json!({
+            "type": "MultiPolygon",
+            "coordinates": [[square(20.0, 0.0, 1.0)], [square(20.0, 5.0, 1.0)]]
+        });
+        let regions = boundaries(vec![
+            feature(json!({ "name": "Mainland" }), ring_with_hole),
+            feature(json!({ "name": "Islands" }), islands),
+            feature(
+                json!({ "name": "Line" }),
+                json!({ "type": "LineString", "coordinates": [] }),
+            ),
+        ]);
+        assert_eq!(regions.features.len(), 2);
+
+        let events = [
+            located(1.0, 1.0, 2),
+            located(5.0, 5.0, 100),
+            located(20.5, 0.5, 1),
+            located(20.5, 5.5, 1),
+            located(20.5, 3.0, 1),
+            AcledEvent::default(),
+        ];
+        let summary = region_totals(&events, RegionLevel::Polygons, Some(&regions));
+
+        let islands = total(&summary, "Islands");
+        assert_eq!((islands.count, islands.fatalities), (2, 2));
+        let mainland = total(&summary, "Mainland");
+        assert_eq!((mainland.count, mainland.fatalities), (1, 2));
+        // Inside the hole, between the islands, and without coordinates
+        assert_eq!(summary.unassigned, 3);
+    }
+
+    #[test]
+    fn polygon_totals_need_boundaries() {
+        let summary = region_totals(&[located(1.0, 1.0, 0)], RegionLevel::Polygons, None);
+        assert!(summary.totals.is_empty());
+        assert_eq!(summary.unassigned, 1);
+    }
+
+    #[test]
+    fn from_geojson_rejects_files_without_polygons() {
+        assert!(Boundaries::from_geojson("a", "[]").is_err());
+        assert!(Boundaries::from_geojson("a", "not json").is_err());
+        let points = json!({
+            "type": "FeatureCollection",
+            "features": [feature(json!({}), json!({ "type": "Point", "coordinates": [1, 2] }))]
+        });
+        assert!(Boundaries::from_geojson("a", &points.to_string()).is_err());
+
+        let single = polygon(json!({ "name": "Only", "code": 7 }), 0.0, 0.0);
+        let parsed = Boundaries::from_geojson("a", &single.to_string()).unwrap();
+        assert_eq!(parsed.features[0].properties.get("code").unwrap(), "7");
+        assert_eq!(parsed.features[0].bounds.north, 1.0);
+    }
+}
@@ -0,0 +1,201 @@
+use super::projection::{project, Bounds};
+use crate::config::{CLUSTER_CELL_SIZE_PX, CLUSTER_MAX_ZOOM};
+use crate::types::AcledEvent;
+use std::collections::BTreeMap;
+
+/// A group of events sharing one grid cell at the current zoom level.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Cluster {
+    pub lat: f64,
+    pub lng: f64,
+    pub fatalities: u32,
+    pub bounds: Bounds,
+    /// Indices into the event slice passed to [`cluster_events`].
+    pub members: Vec<usize>,
+}
+
+impl Cluster {
+    pub fn count(&self) -> usize {
+        self.members.len()
+    }
+
+    /// CSS class colouring the cluster icon by its summed fatalities.
+    pub fn fatality_class(&self) -> &'static str {
+        match self.fatalities {
+            0 => "cluster-fatalities-none",
+            1..=9 => "cluster-fatalities-low",
+            10..=99 => "cluster-fatalities-medium",
+            _ => "cluster-fatalities-high",
+        }
+    }
+}
+
+/// Clusters events for display at `zoom`. From [`CLUSTER_MAX_ZOOM`] on every
+/// located event gets its own single-member cluster.
+pub fn clusters_at_zoom(events: &[AcledEvent], zoom: f64) -> Vec<Cluster> {
+    if zoom < CLUSTER_MAX_ZOOM {
+        return cluster_events(events, zoom, CLUSTER_CELL_SIZE_PX);
+    }
+
+    events
+        .iter()
+        .enumerate()
+        .filter_map(|(index, event)| {
+            let (lat, lng) = event.coordinates()?;
+            Some(Cluster {
+                lat,
+                lng,
+                fatalities: event.fatality_count(),
+                bounds: Bounds::from_point(lat, lng),
+                members: vec![index],
+            })
+        })
+        .collect()
+}
+
+/// Buckets events with coordinates into square grid cells of `cell_size` screen
+/// pixels at `zoom`. Each cluster is positioned at the mean of its members.
+pub fn cluster_events(events: &[AcledEvent], zoom: f64, cell_size: f64) -> Vec<Cluster> {
+    let zoom = zoom.floor();
+    let mut cells: BTreeMap<(i64, i64), (f64, f64, Cluster)> = BTreeMap::new();
+
+    for (index, event) in events.iter().enumerate() {
+        let Some((lat, lng)) = event.coordinates() else {
+            continue;
+        };
+
+        let (x, y) = project(lat, lng, zoom);
+        let cell = (
+            (x / cell_size).floor() as i64,
+            (y / cell_size).floor() as i64,
+        );
+
+        let (lat_sum, lng_sum, cluster) = cells.entry(cell).or_insert_with(|| {
+            (
+                0.0,
+                0.0,
+                Cluster {
+                    lat,
+                    lng,
+                    fatalities: 0,
+                    bounds: Bounds::from_point(lat, lng),
+                    members: Vec::new(),
+                },
+            )
+        });
+
+        *lat_sum += lat;
+        *lng_sum += lng;
+        cluster.fatalities += event.fatality_count();
+        cluster.bounds.extend(lat, lng);
+        cluster.members.push(index);
+    }
+
+    cells
+        .into_values()
+        .map(|(lat_sum, lng_sum, mut cluster)| {
+            let count = cluster.members.len() as f64;
+            cluster.lat = lat_sum / count;
+            cluster.lng = lng_sum / count;
+            cluster
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event_at(lat: f64, lng: f64, fatalities: u32) -> AcledEvent {
+        AcledEvent {
+            latitude: Some(lat),
+            longitude: Some(lng),
+            fatalities: Some(fatalities),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn cells_split_at_their_edges() {
+        // At zoom 0 the world is 256px wide, so a 64px cell spans 90 degrees
+        // of longitude starting at -180.
+        let events = [
+            event_at(0.0, -90.000_001, 0),
+            event_at(0.0, -90.0, 0),
+            event_at(0.0, -0.000_001, 0),
+        ];
+        let clusters = cluster_events(&events, 0.0, 64.0);
+
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[0].members, vec![0]);
+        assert_eq!(clusters[1].members, vec![1, 2]);
+    }
+
+    #[test]
+    fn clusters_sum_fatalities_for_their_colour() {
+        let events = [
+            event_at(10.0, 10.0, 4),
+            event_at(10.01, 10.01, 5),
+            event_at(10.02, 10.02, 1),
+            AcledEvent {
+                fatalities: Some(50),
+                ..Default::default()
+            },
+        ];
+        let clusters = cluster_events(&events, 5.0, CLUSTER_CELL_SIZE_PX);
+
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].count(), 3);
+        assert_eq!(clusters[0].fatalities, 10);
+        assert_eq!(clusters[0].fatality_class(), "cluster-fatalities-medium");
+        assert!((clusters[0].lat - 10.01).abs() < 1e-9);
+        assert!(!clusters[0].bounds.is_point());
+    }
+
+    #[test]
+    fn fatality_class_thresholds() {
+        let class = |fatalities| {
+            Cluster {
+                lat: 0.0,
+                lng: 0.0,
+                fatalities,
+                bounds: Bounds::from_point(0.0, 0.0),
+                members: Vec::new(),
+            }
+            .fatality_class()
+        };
+
+        assert_eq!(class(0), "cluster-fatalities-none");
+        assert_eq!(class(9), "cluster-fatalities-low");
+        assert_eq!(class(10), "cluster-fatalities-medium");
+        assert_eq!(class(100), "cluster-fatalities-high");
+    }
+
+    #[test]
+    fn events_split_into_singles_from_max_zoom() {
+        let events = [event_at(10.0, 10.0, 1), event_at(10.000_01, 10.000_01, 2)];
+
+        let below = clusters_at_zoom(&events, CLUSTER_MAX_ZOOM - 0.5);
+        assert_eq!(below.len(), 1);
+        assert_eq!(below[0].count(), 2);
+
+        for zoom in [CLUSTER_MAX_ZOOM, CLUSTER_MAX_ZOOM + 3.0] {
+            let singles = clusters_at_zoom(&events, zoom);
+            assert_eq!(singles.len(), 2);
+            assert!(singles.iter().all(|cluster| cluster.count() == 1));
+            assert_eq!(singles[1].fatalities, 2);
+        }
+    }
+
+    #[test]
+    fn co_located_events_form_a_point_cluster() {
+        let events = [event_at(5.5, -3.25, 2), event_at(5.5, -3.25, 0)];
+
+        for zoom in [0.0, CLUSTER_MAX_ZOOM - 1.0] {
+            let clusters = cluster_events(&events, zoom, CLUSTER_CELL_SIZE_PX);
+            assert_eq!(clusters.len(), 1);
+            assert!(clusters[0].bounds.is_point());
+            assert_eq!((clusters[0].lat, clusters[0].lng), (5.5, -3.25));
+        }
+    }
+}
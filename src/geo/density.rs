@@ -0,0 +1,218 @@
+use super::projection::{project, unproject};
+use crate::types::AcledEvent;
+use std::collections::BTreeMap;
+
+const SQRT_3: f64 = 1.732_050_807_568_877_2;
+
+/// What each event contributes to its hexagon.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DensityWeight {
+    Count,
+    Fatalities,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct HexBin {
+    /// Six `(lat, lng)` corners, clockwise from the upper right.
+    pub vertices: Vec<(f64, f64)>,
+    pub count: usize,
+    pub value: f64,
+    /// `value` relative to the densest bin, in `0.0..=1.0`.
+    pub intensity: f64,
+}
+
+// Rounds fractional axial hex coordinates to the containing hexagon
+fn round_axial(q: f64, r: f64) -> (i64, i64) {
+    let s = -q - r;
+    let (mut rq, mut rr, rs) = (q.round(), r.round(), s.round());
+    let (dq, dr, ds) = ((rq - q).abs(), (rr - r).abs(), (rs - s).abs());
+
+    if dq > dr && dq > ds {
+        rq = -rr - rs;
+    } else if dr > ds {
+        rr = -rq - rs;
+    }
+
+    (rq as i64, rr as i64)
+}
+
+/// Bins events into pointy-top hexagons of `radius` screen pixels at `zoom`,
+/// computed in Web Mercator pixel space so bins look regular on the map.
+pub fn hex_bin(
+    events: &[AcledEvent],
+    zoom: f64,
+    radius: f64,
+    weight: DensityWeight,
+) -> Vec<HexBin> {
+    let zoom = zoom.floor();
+    let mut cells: BTreeMap<(i64, i64), (usize, f64)> = BTreeMap::new();
+
+    for event in events {
+        let Some((lat, lng)) = event.coordinates() else {
+            continue;
+        };
+
+        let (x, y) = project(lat, lng, zoom);
+        let q = (SQRT_3 / 3.0 * x - y / 3.0) / radius;
+        let r = (2.0 / 3.0 * y) / radius;
+
+        let contribution = match weight {
+            DensityWeight::Count => 1.0,
+            DensityWeight::Fatalities => event.fatality_count() as f64,
+        };
+
+        let cell = cells.entry(round_axial(q, r)).or_insert((0, 0.0));
+        cell.0 += 1;
+        cell.1 += contribution;
+    }
+
+    let max_value = cells.values().map(|(_, value)| *value).fold(0.0, f64::max);
+    if max_value <= 0.0 {
+        return Vec::new();
+    }
+
+    cells
+        .into_iter()
+        .filter(|(_, (_, value))| *value > 0.0)
+        .map(|((q, r), (count, value))| {
+            let (q, r) = (q as f64, r as f64);
+            let center_x = radius * (SQRT_3 * q + SQRT_3 / 2.0 * r);
+            let center_y = radius * (1.5 * r);
+
+            let vertices = (0..6)
+                .map(|corner| {
+                    let angle = (60.0 * corner as f64 - 30.0).to_radians();
+                    unproject(
+                        center_x + radius * angle.cos(),
+                        center_y + radius * angle.sin(),
+                        zoom,
+                    )
+                })
+                .collect();
+
+            HexBin {
+                vertices,
+                count,
+                value,
+                intensity: value / max_value,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Pixel-space centre of an axial hex of unit radius
+    fn center(q: f64, r: f64) -> (f64, f64) {
+        (SQRT_3 * q + SQRT_3 / 2.0 * r, 1.5 * r)
+    }
+
+    fn event_at(lat: f64, lng: f64, fatalities: u32) -> AcledEvent {
+        AcledEvent {
+            latitude: Some(lat),
+            longitude: Some(lng),
+            fatalities: Some(fatalities),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn round_axial_picks_the_nearest_hex() {
+        const NEIGHBOURS: [(i64, i64); 6] = [(1, 0), (1, -1), (0, -1), (-1, 0), (-1, 1), (0, 1)];
+        let points = [
+            (0.0, 0.0),
+            (0.5, 0.0),
+            (0.0, 0.5),
+            (0.5, -0.5),
+            (1.0 / 3.0, 1.0 / 3.0),
+            (0.49, 0.49),
+            (-0.5, 0.999),
+            (2.7, -1.2),
+            (-3.34, 1.67),
+        ];
+
+        for (q, r) in points {
+            let (rq, rr) = round_axial(q, r);
+            // The hex stays on the q + r + s = 0 plane, within one step of
+            // the point on every cube axis
+            let rs = -rq - rr;
+            for (rounded, exact) in [(rq, q), (rr, r), (rs, -q - r)] {
+                assert!((rounded as f64 - exact).abs() < 1.0);
+            }
+
+            let (x, y) = center(q, r);
+            let distance = |(hq, hr): (i64, i64)| {
+                let (cx, cy) = center(hq as f64, hr as f64);
+                (cx - x).hypot(cy - y)
+            };
+            let chosen = distance((rq, rr));
+            for (dq, dr) in NEIGHBOURS {
+                assert!(
+                    chosen <= distance((rq + dq, rr + dr)) + 1e-9,
+                    "({}, {}) rounded to ({}, {})",
+                    q,
+                    r,
+                    rq,
+                    rr
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn nearby_events_share_a_hex() {
+        let events = [
+            event_at(12.0, 8.0, 3),
+            event_at(12.001, 8.001, 0),
+            event_at(12.0, 8.002, 7),
+            event_at(-30.0, 25.0, 1),
+            AcledEvent::default(),
+        ];
+
+        let bins = hex_bin(&events, 6.0, 24.0, DensityWeight::Count);
+        assert_eq!(bins.len(), 2);
+        let dense = bins.iter().find(|bin| bin.count == 3).unwrap();
+        assert_eq!(dense.value, 3.0);
+        assert_eq!(dense.intensity, 1.0);
+        assert_eq!(dense.vertices.len(), 6);
+        let sparse = bins.iter().find(|bin| bin.count == 1).unwrap();
+        assert!((sparse.intensity - 1.0 / 3.0).abs() < 1e-12);
+
+        let by_fatalities = hex_bin(&events, 6.0, 24.0, DensityWeight::Fatalities);
+        let dense = by_fatalities.iter().find(|bin| bin.count == 3).unwrap();
+        assert_eq!(dense.value, 10.0);
+        assert_eq!(dense.intensity, 1.0);
+    }
+
+    #[test]
+    fn bins_without_weight_are_dropped() {
+        let events = [event_at(12.0, 8.0, 0), event_at(-30.0, 25.0, 4)];
+
+        let bins = hex_bin(&events, 6.0, 24.0, DensityWeight::Fatalities);
+        assert_eq!(bins.len(), 1);
+        assert_eq!(bins[0].value, 4.0);
+
+        assert!(hex_bin(&events[..1], 6.0, 24.0, DensityWeight::Fatalities).is_empty());
+    }
+
+    #[test]
+    fn hex_vertices_surround_their_events() {
+        let bins = hex_bin(&[event_at(12.0, 8.0, 1)], 6.0, 24.0, DensityWeight::Count);
+        let vertices = &bins[0].vertices;
+
+        let (south, north) = vertices
+            .iter()
+            .fold((f64::MAX, f64::MIN), |(s, n), (lat, _)| {
+                (s.min(*lat), n.max(*lat))
+            });
+        let (west, east) = vertices
+            .iter()
+            .fold((f64::MAX, f64::MIN), |(w, e), (_, lng)| {
+                (w.min(*lng), e.max(*lng))
+            });
+        assert!(south < 12.0 && 12.0 < north);
+        assert!(west < 8.0 && 8.0 < east);
+    }
+}
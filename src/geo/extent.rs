@@ -0,0 +1,110 @@
+//! Extents for framing the map: of the loaded events, or of the countries a
+//! query covers.
+
+use super::projection::Bounds;
+use crate::config::COUNTRY_BOUNDS;
+use crate::types::AcledEvent;
+
+/// Smallest bounds containing every located event.
+pub fn events_bounds(events: &[AcledEvent]) -> Option<Bounds> {
+    events.iter().filter_map(AcledEvent::coordinates).fold(
+        None,
+        |bounds, (lat, lng)| match bounds {
+            None => Some(Bounds::from_point(lat, lng)),
+            Some(mut bounds) => {
+                bounds.extend(lat, lng);
+                Some(bounds)
+            }
+        },
+    )
+}
+
+/// Combined extent of `countries`, skipping names missing from [`COUNTRY_BOUNDS`].
+pub fn countries_bounds(countries: &[String]) -> Option<Bounds> {
+    countries
+        .iter()
+        .filter_map(|country| {
+            COUNTRY_BOUNDS
+                .iter()
+                .find(|(name, _)| name == country)
+                .map(|(_, extent)| *extent)
+        })
+        .fold(None, |bounds, (south, west, north, east)| match bounds {
+            None => Some(Bounds {
+                south,
+                west,
+                north,
+                east,
+            }),
+            Some(mut bounds) => {
+                bounds.extend(south, west);
+                bounds.extend(north, east);
+                Some(bounds)
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AVAILABLE_COUNTRIES;
+
+    #[test]
+    fn every_available_country_has_bounds() {
+        let missing: Vec<&str> = AVAILABLE_COUNTRIES
+            .iter()
+            .copied()
+            .filter(|country| !COUNTRY_BOUNDS.iter().any(|(name, _)| name == country))
+            .collect();
+        assert!(missing.is_empty(), "no bounds for {:?}", missing);
+
+        for (name, _) in COUNTRY_BOUNDS {
+            assert!(
+                AVAILABLE_COUNTRIES.contains(name),
+                "unknown country {}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn country_bounds_are_well_formed() {
+        for (name, (south, west, north, east)) in COUNTRY_BOUNDS {
+            assert!(
+                (-90.0..=90.0).contains(south) && (-90.0..=90.0).contains(north),
+                "{} latitude out of range",
+                name
+            );
+            assert!(
+                (-180.0..=180.0).contains(west) && (-180.0..=180.0).contains(east),
+                "{} longitude out of range",
+                name
+            );
+            assert!(south < north && west < east, "{} is inverted", name);
+        }
+    }
+
+    #[test]
+    fn countries_bounds_combines_extents() {
+        let countries = [
+            "Mali".to_string(),
+            "Niger".to_string(),
+            "Atlantis".to_string(),
+        ];
+        let bounds = countries_bounds(&countries).unwrap();
+        let extent = |country: &str| {
+            COUNTRY_BOUNDS
+                .iter()
+                .find(|(name, _)| *name == country)
+                .unwrap()
+                .1
+        };
+        let (mali, niger) = (extent("Mali"), extent("Niger"));
+
+        assert_eq!(bounds.south, mali.0.min(niger.0));
+        assert_eq!(bounds.west, mali.1.min(niger.1));
+        assert_eq!(bounds.north, mali.2.max(niger.2));
+        assert_eq!(bounds.east, mali.3.max(niger.3));
+        assert_eq!(countries_bounds(&["Atlantis".to_string()]), None);
+    }
+}
@@ -0,0 +1,17 @@
+//! Client-side spatial computations over loaded events.
+
+pub mod breaks;
+pub mod choropleth;
+pub mod cluster;
+pub mod density;
+pub mod extent;
+pub mod projection;
+pub mod selection;
+
+pub use breaks::{class_breaks, class_index, ClassBreaks};
+pub use choropleth::{region_totals, Boundaries, RegionLevel, RegionSummary, RegionTotal};
+pub use cluster::{clusters_at_zoom, Cluster};
+pub use density::{hex_bin, DensityWeight, HexBin};
+pub use extent::{countries_bounds, events_bounds};
+pub use projection::Bounds;
+pub use selection::{distance_m, select_events, Selection};
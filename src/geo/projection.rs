@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+// Leaflet's default CRS (EPSG:3857) uses 256px tiles
+const TILE_SIZE: f64 = 256.0;
+const MAX_LATITUDE: f64 = 85.051_128_779_806_59;
+
+/// Projects a coordinate to Web Mercator world pixels at `zoom`.
+pub fn project(lat: f64, lng: f64, zoom: f64) -> (f64, f64) {
+    let scale = TILE_SIZE * 2f64.powf(zoom);
+    let lat = lat.clamp(-MAX_LATITUDE, MAX_LATITUDE).to_radians();
+
+    let x = (lng + 180.0) / 360.0 * scale;
+    let y = (1.0 - (lat.tan() + 1.0 / lat.cos()).ln() / PI) / 2.0 * scale;
+    (x, y)
+}
+
+/// Inverse of [`project`].
+pub fn unproject(x: f64, y: f64, zoom: f64) -> (f64, f64) {
+    let scale = TILE_SIZE * 2f64.powf(zoom);
+
+    let lng = x / scale * 360.0 - 180.0;
+    let n = PI - 2.0 * PI * y / scale;
+    let lat = n.sinh().atan().to_degrees();
+    (lat, lng)
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct Bounds {
+    pub south: f64,
+    pub west: f64,
+    pub north: f64,
+    pub east: f64,
+}
+
+impl Bounds {
+    pub fn from_point(lat: f64, lng: f64) -> Self {
+        Self {
+            south: lat,
+            west: lng,
+            north: lat,
+            east: lng,
+        }
+    }
+
+    pub fn extend(&mut self, lat: f64, lng: f64) {
+        self.south = self.south.min(lat);
+        self.west = self.west.min(lng);
+        self.north = self.north.max(lat);
+        self.east = self.east.max(lng);
+    }
+
+    pub fn is_point(&self) -> bool {
+        self.south == self.north && self.west == self.east
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn project_maps_the_world_onto_the_tile_grid() {
+        assert_eq!(project(0.0, 0.0, 0.0), (128.0, 128.0));
+        assert_eq!(project(0.0, -180.0, 1.0).0, 0.0);
+        assert_eq!(project(0.0, 180.0, 1.0).0, 512.0);
+
+        let (_, top) = project(MAX_LATITUDE, 0.0, 2.0);
+        let (_, bottom) = project(-MAX_LATITUDE, 0.0, 2.0);
+        assert!(top.abs() < 1e-6);
+        assert!((bottom - 1024.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn unproject_inverts_project() {
+        for zoom in [0.0, 5.0, 18.0] {
+            for lat in [-85.0, -84.99, -45.5, 0.0, 12.345_678, 60.0, 84.99, 85.0] {
+                for lng in [-180.0, -77.03, 0.0, 100.5, 179.999] {
+                    let (x, y) = project(lat, lng, zoom);
+                    let (back_lat, back_lng) = unproject(x, y, zoom);
+                    assert!(
+                        (back_lat - lat).abs() < 1e-9 && (back_lng - lng).abs() < 1e-9,
+                        "({}, {}) at zoom {} came back as ({}, {})",
+                        lat,
+                        lng,
+                        zoom,
+                        back_lat,
+                        back_lng
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn latitudes_beyond_the_mercator_limit_are_clamped() {
+        for (lat, limit) in [(89.9, MAX_LATITUDE), (-90.0, -MAX_LATITUDE)] {
+            let (x, y) = project(lat, 10.0, 3.0);
+            let (back_lat, _) = unproject(x, y, 3.0);
+            assert!((back_lat - limit).abs() < 1e-9);
+            assert!(y.is_finite());
+        }
+    }
+}
@@ -0,0 +1,247 @@
+use super::projection::Bounds;
+use crate::types::AcledEvent;
+
+// Mean Earth radius, as used by Leaflet's `L.CRS.Earth`
+const EARTH_RADIUS_M: f64 = 6_371_000.0;
+
+/// An area drawn on the map to narrow the loaded events.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Selection {
+    Rectangle(Bounds),
+    /// `(lat, lng)` vertices; the ring closes implicitly.
+    Polygon(Vec<(f64, f64)>),
+    Circle {
+        lat: f64,
+        lng: f64,
+        radius_m: f64,
+    },
+}
+
+impl Selection {
+    pub fn contains(&self, lat: f64, lng: f64) -> bool {
+        match self {
+            Selection::Rectangle(bounds) => {
+                (bounds.south..=bounds.north).contains(&lat)
+                    && (bounds.west..=bounds.east).contains(&lng)
+            }
+            Selection::Polygon(vertices) => polygon_contains(vertices, lat, lng),
+            Selection::Circle {
+                lat: center_lat,
+                lng: center_lng,
+                radius_m,
+            } => distance_m(*center_lat, *center_lng, lat, lng) <= *radius_m,
+        }
+    }
+
+    /// Smallest latitude/longitude box around the shape.
+    pub fn bounds(&self) -> Bounds {
+        match self {
+            Selection::Rectangle(bounds) => *bounds,
+            Selection::Polygon(vertices) => {
+                let (lat, lng) = vertices.first().copied().unwrap_or_default();
+                vertices
+                    .iter()
+                    .fold(Bounds::from_point(lat, lng), |mut bounds, &(lat, lng)| {
+                        bounds.extend(lat, lng);
+                        bounds
+                    })
+            }
+            Selection::Circle { lat, lng, radius_m } => {
+                let angle = radius_m / EARTH_RADIUS_M;
+                let lat_delta = angle.to_degrees();
+                // A circle reaching a pole spans every longitude; otherwise its
+                // widest point is where a meridian touches it
+                let lng_delta = if lat.abs() + lat_delta >= 90.0 {
+                    180.0
+                } else {
+                    (angle.sin() / lat.to_radians().cos()).asin().to_degrees()
+                };
+                Bounds {
+                    south: (lat - lat_delta).max(-90.0),
+                    west: (lng - lng_delta).max(-180.0),
+                    north: (lat + lat_delta).min(90.0),
+                    east: (lng + lng_delta).min(180.0),
+                }
+            }
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Selection::Rectangle(_) => "drawn rectangle".to_string(),
+            Selection::Polygon(vertices) => format!("drawn polygon ({} points)", vertices.len()),
+            Selection::Circle { radius_m, .. } => {
+                format!("{:.1} km of the drawn point", radius_m / 1000.0)
+            }
+        }
+    }
+}
+
+/// Events inside `selection`; events without coordinates never match.
+pub fn select_events(events: &[AcledEvent], selection: &Selection) -> Vec<AcledEvent> {
+    events
+        .iter()
+        .filter(|event| {
+            event
+                .coordinates()
+                .is_some_and(|(lat, lng)| selection.contains(lat, lng))
+        })
+        .cloned()
+        .collect()
+}
+
+// Even-odd ray casting in the latitude/longitude plane, which matches the
+// straight edges Leaflet draws for areas of a few hundred kilometres
+pub(super) fn polygon_contains(vertices: &[(f64, f64)], lat: f64, lng: f64) -> bool {
+    let Some(&last) = vertices.last() else {
+        return false;
+    };
+
+    let mut inside = false;
+    let mut previous = last;
+    for &current in vertices {
+        let ((lat_a, lng_a), (lat_b, lng_b)) = (current, previous);
+        if (lat_a > lat) != (lat_b > lat) {
+            let crossing = lng_a + (lat - lat_a) / (lat_b - lat_a) * (lng_b - lng_a);
+            if lng < crossing {
+                inside = !inside;
+            }
+        }
+        previous = current;
+    }
+    inside
+}
+
+/// Great-circle (haversine) distance in metres.
+pub fn distance_m(lat_a: f64, lng_a: f64, lat_b: f64, lng_b: f64) -> f64 {
+    let (phi_a, phi_b) = (lat_a.to_radians(), lat_b.to_radians());
+    let d_phi = phi_b - phi_a;
+    let d_lambda = (lng_b - lng_a).to_radians();
+
+    let h =
+        (d_phi / 2.0).sin().powi(2) + phi_a.cos() * phi_b.cos() * (d_lambda / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_M * h.sqrt().min(1.0).asin()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // One degree along a great circle
+    const DEGREE_M: f64 = EARTH_RADIUS_M * std::f64::consts::PI / 180.0;
+
+    fn square() -> Vec<(f64, f64)> {
+        vec![(0.0, 0.0), (0.0, 10.0), (10.0, 10.0), (10.0, 0.0)]
+    }
+
+    #[test]
+    fn polygon_contains_simple_shapes() {
+        assert!(polygon_contains(&square(), 5.0, 5.0));
+        assert!(!polygon_contains(&square(), 5.0, 15.0));
+        assert!(!polygon_contains(&square(), -1.0, 5.0));
+
+        let triangle = [(0.0, 0.0), (10.0, 5.0), (0.0, 10.0)];
+        assert!(polygon_contains(&triangle, 2.0, 5.0));
+        assert!(!polygon_contains(&triangle, 8.0, 1.0));
+    }
+
+    #[test]
+    fn polygon_contains_concave_shapes() {
+        // An L: the lower-right quadrant of the square is cut away
+        let l_shape = [
+            (0.0, 0.0),
+            (10.0, 0.0),
+            (10.0, 10.0),
+            (5.0, 10.0),
+            (5.0, 5.0),
+            (0.0, 5.0),
+        ];
+        assert!(polygon_contains(&l_shape, 2.0, 2.0));
+        assert!(polygon_contains(&l_shape, 8.0, 8.0));
+        assert!(!polygon_contains(&l_shape, 2.0, 8.0));
+    }
+
+    #[test]
+    fn polygon_contains_ignores_vertex_order() {
+        let mut reversed = square();
+        reversed.reverse();
+        assert!(polygon_contains(&reversed, 5.0, 5.0));
+        assert!(!polygon_contains(&reversed, 11.0, 5.0));
+    }
+
+    #[test]
+    fn degenerate_polygons_contain_nothing() {
+        assert!(!polygon_contains(&[], 0.0, 0.0));
+        assert!(!polygon_contains(&[(1.0, 1.0)], 1.0, 1.0));
+        assert!(!polygon_contains(&[(0.0, 0.0), (10.0, 10.0)], 5.0, 5.0));
+    }
+
+    #[test]
+    fn distance_matches_known_values() {
+        assert!((distance_m(0.0, 0.0, 0.0, 1.0) - DEGREE_M).abs() < 1e-6);
+        assert!((distance_m(0.0, 0.0, 1.0, 0.0) - DEGREE_M).abs() < 1e-6);
+        assert_eq!(distance_m(12.6, -8.0, 12.6, -8.0), 0.0);
+        // Antipodes are half the circumference apart
+        assert!((distance_m(0.0, 0.0, 0.0, 180.0) - 180.0 * DEGREE_M).abs() < 1e-3);
+        // A degree of longitude shrinks with the cosine of the latitude
+        let at_60 = distance_m(60.0, 0.0, 60.0, 1.0);
+        assert!((at_60 - DEGREE_M / 2.0).abs() < 10.0);
+    }
+
+    #[test]
+    fn circle_contains_and_bounds() {
+        let circle = Selection::Circle {
+            lat: 0.0,
+            lng: 20.0,
+            radius_m: DEGREE_M,
+        };
+        assert!(circle.contains(0.0, 20.99));
+        assert!(!circle.contains(0.0, 21.01));
+        assert!(!circle.contains(0.8, 20.8));
+
+        let bounds = circle.bounds();
+        assert!((bounds.south + 1.0).abs() < 1e-9 && (bounds.north - 1.0).abs() < 1e-9);
+        assert!((bounds.west - 19.0).abs() < 1e-9 && (bounds.east - 21.0).abs() < 1e-9);
+
+        let polar = Selection::Circle {
+            lat: 89.5,
+            lng: 0.0,
+            radius_m: DEGREE_M,
+        };
+        let bounds = polar.bounds();
+        assert_eq!(
+            (bounds.west, bounds.north, bounds.east),
+            (-180.0, 90.0, 180.0)
+        );
+    }
+
+    #[test]
+    fn rectangle_includes_its_edges() {
+        let rectangle = Selection::Rectangle(Bounds {
+            south: 10.0,
+            west: -5.0,
+            north: 15.0,
+            east: 2.0,
+        });
+        assert!(rectangle.contains(10.0, -5.0));
+        assert!(rectangle.contains(15.0, 2.0));
+        assert!(!rectangle.contains(15.1, 0.0));
+    }
+
+    #[test]
+    fn select_events_skips_unlocated_events() {
+        let at = |latitude, longitude| AcledEvent {
+            latitude,
+            longitude,
+            ..Default::default()
+        };
+        let events = [
+            at(Some(5.0), Some(5.0)),
+            at(Some(50.0), Some(5.0)),
+            at(None, None),
+        ];
+
+        let selected = select_events(&events, &Selection::Polygon(square()));
+        assert_eq!(selected, vec![events[0].clone()]);
+    }
+}
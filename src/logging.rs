@@ -2,7 +2,7 @@
 #[macro_export]
 macro_rules! console_log {
     ($($arg:tt)*) => {
-        web_sys::console::log_1(&format!($($arg)*).into())
+        $crate::logging::record($crate::logging::LogLevel::Info, "", format!($($arg)*))
     };
 }
 
@@ -10,7 +10,7 @@ macro_rules! console_log {
 #[macro_export]
 macro_rules! console_warn {
     ($($arg:tt)*) => {
-        web_sys::console::warn_1(&format!($($arg)*).into());
+        $crate::logging::record($crate::logging::LogLevel::Warn, "", format!($($arg)*))
     };
 }
 
@@ -18,7 +18,7 @@ macro_rules! console_warn {
 #[macro_export]
 macro_rules! console_error {
     ($($arg:tt)*) => {
-        web_sys::console::error_1(&format!($($arg)*).into());
+        $crate::logging::record($crate::logging::LogLevel::Error, "", format!($($arg)*))
     };
 }
 
@@ -27,7 +27,7 @@ macro_rules! console_error {
 macro_rules! console_debug {
     ($($arg:tt)*) => {
         if cfg!(debug_assertions) {
-            web_sys::console::log_1(&format!("[DEBUG] {}", format!($($arg)*)).into());
+            $crate::logging::record($crate::logging::LogLevel::Debug, "", format!($($arg)*))
         }
     };
 }
@@ -36,7 +36,7 @@ macro_rules! console_debug {
 #[macro_export]
 macro_rules! console_log_with_context {
     ($context:expr, $($arg:tt)*) => {
-        web_sys::console::log_1(&format!("[{}] {}", $context, format!($($arg)*)).into());
+        $crate::logging::record($crate::logging::LogLevel::Info, $context, format!($($arg)*))
     };
 }
 
@@ -44,14 +44,28 @@ macro_rules! console_log_with_context {
 #[macro_export]
 macro_rules! console_warn_with_context {
     ($context:expr, $($arg:tt)*) => {
-        web_sys::console::warn_1(&format!("[{}] WARNING: {}", $context, format!($($arg)*)).into());
+        $crate::logging::record($crate::logging::LogLevel::Warn, $context, format!($($arg)*))
     };
 }
 
 #[macro_export]
 macro_rules! console_error_with_context {
     ($context:expr, $($arg:tt)*) => {
-        web_sys::console::error_1(&format!("[{}] ERROR: {}", $context, format!($($arg)*)).into())
+        $crate::logging::record($crate::logging::LogLevel::Error, $context, format!($($arg)*))
+    };
+}
+
+/// Logs `message` with `key = value` fields, e.g.
+/// `console::log_fields!("API", "Fetch complete", events = events.len())`.
+#[macro_export]
+macro_rules! console_log_fields {
+    ($context:expr, $message:expr $(, $key:ident = $value:expr)* $(,)?) => {
+        $crate::logging::record_fields(
+            $crate::logging::LogLevel::Info,
+            $context,
+            $message.to_string(),
+            vec![$((stringify!($key).to_string(), $value.to_string())),*],
+        )
     };
 }
 
@@ -60,7 +74,11 @@ macro_rules! console_error_with_context {
 macro_rules! console_log_function {
     ($function_name:expr) => {
         if cfg!(debug_assertions) {
-            web_sys::console::log_1(&format!("[FUNCTION] Entering: {}", $function_name).into());
+            $crate::logging::record(
+                $crate::logging::LogLevel::Debug,
+                "FUNCTION",
+                format!("Entering: {}", $function_name),
+            )
         }
     };
 }
@@ -70,7 +88,11 @@ macro_rules! console_log_function {
 macro_rules! console_log_function_exit {
     ($function_name:expr) => {
         if cfg!(debug_assertions) {
-            web_sys::console::log_1(&format!("[FUNCTION] Exiting: {}", $function_name).into());
+            $crate::logging::record(
+                $crate::logging::LogLevel::Debug,
+                "FUNCTION",
+                format!("Exiting: {}", $function_name),
+            )
         }
     };
 }
@@ -79,17 +101,21 @@ macro_rules! console_log_function_exit {
 #[macro_export]
 macro_rules! console_log_state_change {
     ($from:expr, $to:expr) => {
-        web_sys::console::log_1(&format!("[STATE] {} -> {}", $from, $to).into());
+        $crate::logging::record(
+            $crate::logging::LogLevel::Info,
+            "STATE",
+            format!("{} -> {}", $from, $to),
+        )
     };
 }
 
 #[macro_export]
 macro_rules! console_log_user_action {
     ($action:expr) => {
-        web_sys::console::log_1(&format!("[USER ACTION] {}", $action).into());
+        $crate::logging::record($crate::logging::LogLevel::Info, "USER ACTION", format!("{}", $action))
     };
     ($($arg:tt)*) => {
-        web_sys::console::log_1(&format!("[USER ACTION] {}", format!($($arg)*)).into());
+        $crate::logging::record($crate::logging::LogLevel::Info, "USER ACTION", format!($($arg)*))
     };
 }
 
@@ -98,9 +124,173 @@ pub mod console {
     pub use crate::{
         console_debug as debug, console_error as error,
         console_error_with_context as error_with_context, console_log as log,
-        console_log_function as log_function, console_log_function_exit as log_function_exit,
+        console_log_fields as log_fields, console_log_function as log_function,
+        console_log_function_exit as log_function_exit,
         console_log_state_change as log_state_change, console_log_user_action as log_user_action,
         console_log_with_context as log_with_context, console_warn as warn,
         console_warn_with_context as warn_with_context,
     };
 }
+
+use crate::config::{LOG_BUFFER_SIZE, LOG_FLUSH_INTERVAL_MS};
+use crate::services::TauriService;
+use crate::utils::redact::redact;
+use gloo_timers::callback::Timeout;
+use serde::Serialize;
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::fmt;
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    pub const ALL: [LogLevel; 4] = [
+        LogLevel::Debug,
+        LogLevel::Info,
+        LogLevel::Warn,
+        LogLevel::Error,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Warn => "warn",
+            LogLevel::Error => "error",
+        }
+    }
+}
+
+/// One log record. Secrets are redacted before an entry is created.
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct LogEntry {
+    /// ISO 8601, UTC.
+    pub timestamp: String,
+    pub level: LogLevel,
+    pub context: String,
+    pub message: String,
+    pub fields: Vec<(String, String)>,
+}
+
+impl fmt::Display for LogEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {:<5} ",
+            self.timestamp,
+            self.level.label().to_uppercase()
+        )?;
+        if !self.context.is_empty() {
+            write!(f, "[{}] ", self.context)?;
+        }
+        write!(f, "{}", self.message)?;
+        for (key, value) in &self.fields {
+            write!(f, " {}={}", key, value)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct WriteLogArgs<'a> {
+    entries: &'a [LogEntry],
+}
+
+thread_local! {
+    static BUFFER: RefCell<VecDeque<LogEntry>> = const { RefCell::new(VecDeque::new()) };
+    // Bumped on every change so viewers can tell when to re-read the buffer
+    static REVISION: Cell<u64> = const { Cell::new(0) };
+    // Entries waiting to be sent to the desktop log file
+    static PENDING: RefCell<Vec<LogEntry>> = const { RefCell::new(Vec::new()) };
+    static FLUSH_SCHEDULED: Cell<bool> = const { Cell::new(false) };
+}
+
+pub fn record(level: LogLevel, context: &str, message: String) {
+    record_fields(level, context, message, Vec::new());
+}
+
+/// Writes an entry to the devtools console, the in-memory ring buffer and,
+/// on desktop, the log file.
+pub fn record_fields(
+    level: LogLevel,
+    context: &str,
+    message: String,
+    fields: Vec<(String, String)>,
+) {
+    let entry = LogEntry {
+        timestamp: js_sys::Date::new_0().to_iso_string().into(),
+        level,
+        context: context.to_string(),
+        message: redact(&message),
+        fields: fields
+            .into_iter()
+            .map(|(key, value)| (key, redact(&value)))
+            .collect(),
+    };
+
+    let line = entry.to_string().into();
+    match level {
+        LogLevel::Debug | LogLevel::Info => web_sys::console::log_1(&line),
+        LogLevel::Warn => web_sys::console::warn_1(&line),
+        LogLevel::Error => web_sys::console::error_1(&line),
+    }
+
+    if TauriService::is_available() {
+        PENDING.with(|pending| pending.borrow_mut().push(entry.clone()));
+        schedule_flush();
+    }
+
+    BUFFER.with(|buffer| {
+        let mut buffer = buffer.borrow_mut();
+        if buffer.len() == LOG_BUFFER_SIZE {
+            buffer.pop_front();
+        }
+        buffer.push_back(entry);
+    });
+    REVISION.with(|revision| revision.set(revision.get() + 1));
+}
+
+/// The buffered entries, oldest first.
+pub fn entries() -> Vec<LogEntry> {
+    BUFFER.with(|buffer| buffer.borrow().iter().cloned().collect())
+}
+
+pub fn revision() -> u64 {
+    REVISION.with(Cell::get)
+}
+
+pub fn clear() {
+    BUFFER.with(|buffer| buffer.borrow_mut().clear());
+    REVISION.with(|revision| revision.set(revision.get() + 1));
+}
+
+// Batches entries into one command per interval rather than one per line
+fn schedule_flush() {
+    if FLUSH_SCHEDULED.with(|scheduled| scheduled.replace(true)) {
+        return;
+    }
+
+    Timeout::new(LOG_FLUSH_INTERVAL_MS, || {
+        FLUSH_SCHEDULED.with(|scheduled| scheduled.set(false));
+        let entries = PENDING.with(|pending| std::mem::take(&mut *pending.borrow_mut()));
+        if entries.is_empty() {
+            return;
+        }
+
+        wasm_bindgen_futures::spawn_local(async move {
+            let args = WriteLogArgs { entries: &entries };
+            // Reported straight to the console; logging it would queue another write
+            if let Err(e) = TauriService::invoke::<_, ()>("write_log", &args).await {
+                web_sys::console::warn_1(&format!("Failed to write log file: {}", e).into());
+            }
+        });
+    })
+    .forget();
+}
@@ -5,13 +5,15 @@ mod app;
 mod components;
 mod config;
 mod errors;
+mod geo;
 mod services;
 mod states;
 mod types;
+mod utils;
 
-use app::App;
+use app::Root;
 
 fn main() {
     console_error_panic_hook::set_once();
-    yew::Renderer::<App>::new().render();
+    yew::Renderer::<Root>::new().render();
 }
@@ -1,68 +1,440 @@
-use crate::config::ACLED_BASE_URL;
+use crate::config::{
+    ACLED_BASE_URL, ACLED_OAUTH_API_URL, DEFAULT_API_TIMEOUT, MAX_FETCH_ATTEMPTS, MAX_FETCH_PAGES,
+    RETRY_AFTER_LIMIT_MS, RETRY_BASE_DELAY_MS, RETRY_MAX_DELAY_MS,
+};
 use crate::errors::{AppError, AppResult, IntoAppError};
-use crate::types::{AcledEvent, AcledParams, AcledResponse, UserData};
+use crate::logging::console;
+use crate::services::{AuthService, RequestAuth, TauriService};
+use crate::types::{
+    AcledEvent, AcledParams, AcledResponse, FetchProgress, PagedEvents, RetryStatus, UserData,
+};
+use crate::utils::retry::{backoff_delay_ms, retry_after_ms};
 use gloo_net::http::Request;
+use gloo_timers::callback::Timeout;
+use gloo_timers::future::TimeoutFuture;
+use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU32, Ordering};
 use wasm_bindgen_futures::spawn_local;
+use web_sys::AbortController;
 use yew::Callback;
 
+/// Status and body of an ACLED request, whichever transport made it.
+#[derive(Deserialize)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub status_text: String,
+    pub body: String,
+    /// Raw `Retry-After` header, when the server sent one.
+    #[serde(default)]
+    pub retry_after: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AcledQueryArgs<'a> {
+    auth: &'a RequestAuth,
+    query: &'a [(String, String)],
+    request_id: u32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CancelQueryArgs {
+    request_id: u32,
+}
+
+// Identifies desktop queries so the backend can cancel them
+static NEXT_REQUEST_ID: AtomicU32 = AtomicU32::new(1);
+
+/// The request of a fetch that is currently on the wire.
+enum InFlight {
+    Http(AbortController),
+    Tauri(u32),
+}
+
+#[derive(Default)]
+struct FetchState {
+    cancelled: Cell<bool>,
+    in_flight: RefCell<Option<InFlight>>,
+}
+
+/// Handle to a running fetch. Cancelling aborts the request on the wire and
+/// guarantees that none of the fetch's callbacks run afterwards.
+#[derive(Clone, Default)]
+pub struct FetchHandle(Rc<FetchState>);
+
+impl FetchHandle {
+    pub fn cancel(&self) {
+        if self.0.cancelled.replace(true) {
+            return;
+        }
+
+        match self.0.in_flight.borrow_mut().take() {
+            Some(InFlight::Http(controller)) => controller.abort(),
+            Some(InFlight::Tauri(request_id)) => spawn_local(async move {
+                let args = CancelQueryArgs { request_id };
+                if let Err(e) = TauriService::invoke::<_, ()>("cancel_acled_query", &args).await {
+                    console::warn_with_context!("ACLED_SERVICE", "Failed to cancel query: {}", e);
+                }
+            }),
+            None => {}
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.get()
+    }
+
+    fn start(&self, request: InFlight) {
+        *self.0.in_flight.borrow_mut() = Some(request);
+    }
+
+    fn finish(&self) {
+        self.0.in_flight.borrow_mut().take();
+    }
+
+    fn cancelled_error() -> AppError {
+        AppError::unknown("Request cancelled")
+    }
+}
+
 pub struct AcledService;
 
 impl AcledService {
     pub fn fetch_events(
         user_data: &UserData,
         params: &AcledParams,
+        on_retry: Callback<RetryStatus>,
         on_success: Callback<Vec<AcledEvent>>,
         on_error: Callback<AppError>,
-    ) {
+    ) -> FetchHandle {
+        let handle = FetchHandle::default();
+        if let Err(error) = Self::check_request(user_data, params) {
+            on_error.emit(error);
+            return handle;
+        }
+
+        let user_data = user_data.clone();
+        let params = params.clone();
+        let fetch = handle.clone();
+
+        spawn_local(async move {
+            let result = Self::fetch_events_async(&user_data, &params, &on_retry, &fetch).await;
+            if fetch.is_cancelled() {
+                console::log_with_context!("ACLED_SERVICE", "Discarded cancelled fetch");
+                return;
+            }
+            match result {
+                Ok(events) => on_success.emit(events),
+                Err(error) => on_error.emit(error),
+            }
+        });
+
+        handle
+    }
+
+    /// Fetches every page for `params`, emitting each page as it arrives.
+    pub fn fetch_all_events(
+        user_data: &UserData,
+        params: &AcledParams,
+        on_progress: Callback<FetchProgress>,
+        on_retry: Callback<RetryStatus>,
+        on_success: Callback<PagedEvents>,
+        on_error: Callback<AppError>,
+    ) -> FetchHandle {
+        let handle = FetchHandle::default();
+        if let Err(error) = Self::check_request(user_data, params) {
+            on_error.emit(error);
+            return handle;
+        }
+
+        let user_data = user_data.clone();
+        let params = params.clone();
+        let fetch = handle.clone();
+
+        spawn_local(async move {
+            let result =
+                Self::fetch_all_events_async(&user_data, &params, &on_progress, &on_retry, &fetch)
+                    .await;
+            if fetch.is_cancelled() {
+                console::log_with_context!("ACLED_SERVICE", "Discarded cancelled fetch");
+                return;
+            }
+            match result {
+                Ok(events) => on_success.emit(events),
+                Err(error) => on_error.emit(error),
+            }
+        });
+
+        handle
+    }
+
+    /// Cheapest possible authenticated request (`limit=1`), used to check
+    /// credentials before entering the dashboard. It is not retried, so the
+    /// login screen can report rate limiting and network failures directly.
+    pub async fn verify_credentials(user_data: &UserData) -> AppResult<()> {
+        let query = [("limit".to_string(), "1".to_string())];
+        let response = Self::send_query_async(user_data, &query, &FetchHandle::default()).await?;
+        Self::parse_response(response).map(|_| ())
+    }
+
+    fn check_request(user_data: &UserData, params: &AcledParams) -> AppResult<()> {
         // Validate parameters before making request
         if !params.is_valid() {
-            on_error.emit(AppError::validation(
+            return Err(AppError::validation(
                 "parameters",
                 "Invalid API parameters provided",
             ));
-            return;
         }
 
         if !user_data.is_valid() {
-            on_error.emit(AppError::validation(
+            return Err(AppError::validation(
                 "credentials",
                 "Invalid user credentials provided",
             ));
-            return;
         }
 
-        let user_data = user_data.clone();
-        let params = params.clone();
+        Ok(())
+    }
 
-        spawn_local(async move {
-            match Self::fetch_events_async(&user_data, &params).await {
-                Ok(events) => on_success.emit(events),
-                Err(error) => on_error.emit(error),
+    async fn fetch_all_events_async(
+        user_data: &UserData,
+        params: &AcledParams,
+        on_progress: &Callback<FetchProgress>,
+        on_retry: &Callback<RetryStatus>,
+        fetch: &FetchHandle,
+    ) -> AppResult<PagedEvents> {
+        let mut events = Vec::new();
+
+        // ACLED pages are 1-based; a short page means there is nothing left to fetch
+        for page in 1..=MAX_FETCH_PAGES {
+            let page_events = Self::fetch_query_async(
+                user_data,
+                &params.to_page_query_params(page),
+                on_retry,
+                fetch,
+            )
+            .await?;
+            let page_len = page_events.len();
+
+            events.extend_from_slice(&page_events);
+            on_progress.emit(FetchProgress {
+                page,
+                page_events,
+                total_events: events.len(),
+            });
+
+            if page_len < params.limit as usize {
+                console::log_fields!(
+                    "ACLED_SERVICE",
+                    "Paginated fetch complete",
+                    pages = page,
+                    events = events.len()
+                );
+                return Ok(PagedEvents {
+                    events,
+                    truncated: false,
+                });
             }
-        });
+        }
+
+        console::warn_with_context!(
+            "ACLED_SERVICE",
+            "Stopped paginated fetch after {} pages ({} events)",
+            MAX_FETCH_PAGES,
+            events.len()
+        );
+
+        Ok(PagedEvents {
+            events,
+            truncated: true,
+        })
     }
 
     async fn fetch_events_async(
         user_data: &UserData,
         params: &AcledParams,
+        on_retry: &Callback<RetryStatus>,
+        fetch: &FetchHandle,
     ) -> AppResult<Vec<AcledEvent>> {
-        let url = Self::build_url(user_data, params);
+        Self::fetch_query_async(user_data, &params.to_query_params(), on_retry, fetch).await
+    }
 
-        let response = Request::get(&url).send().await.into_app_error()?;
+    /// Runs a query, retrying recoverable failures with jittered exponential
+    /// backoff (or the server's `Retry-After`) up to `MAX_FETCH_ATTEMPTS` times.
+    async fn fetch_query_async(
+        user_data: &UserData,
+        query: &[(String, String)],
+        on_retry: &Callback<RetryStatus>,
+        fetch: &FetchHandle,
+    ) -> AppResult<Vec<AcledEvent>> {
+        let mut attempt = 1;
+        loop {
+            let response = Self::send_query_async(user_data, query, fetch).await;
+            // Whatever came back belongs to a fetch nobody is waiting for
+            if fetch.is_cancelled() {
+                return Err(FetchHandle::cancelled_error());
+            }
+            let retry_after = response
+                .as_ref()
+                .ok()
+                .and_then(|response| response.retry_after.as_deref())
+                .and_then(|value| retry_after_ms(value, js_sys::Date::now(), js_sys::Date::parse));
 
-        if !response.ok() {
-            return Err(AppError::api(
-                response.status().into(),
-                format!("HTTP {}: {}", response.status(), response.status_text()),
-            ));
+            let error = match response.and_then(Self::parse_response) {
+                Ok(events) => return Ok(events),
+                Err(error) => error,
+            };
+            if !error.is_recoverable() || attempt >= MAX_FETCH_ATTEMPTS {
+                return Err(error);
+            }
+
+            let delay_ms = match retry_after {
+                Some(delay_ms) if delay_ms > RETRY_AFTER_LIMIT_MS => {
+                    console::warn_with_context!(
+                        "ACLED_SERVICE",
+                        "Not retrying; server asked to wait {}s",
+                        delay_ms / 1000
+                    );
+                    return Err(error);
+                }
+                Some(delay_ms) => delay_ms,
+                None => backoff_delay_ms(
+                    attempt,
+                    RETRY_BASE_DELAY_MS,
+                    RETRY_MAX_DELAY_MS,
+                    js_sys::Math::random(),
+                ),
+            };
+
+            attempt += 1;
+            console::warn_with_context!(
+                "ACLED_SERVICE",
+                "Attempt {}/{} in {}ms after: {}",
+                attempt,
+                MAX_FETCH_ATTEMPTS,
+                delay_ms,
+                error
+            );
+            on_retry.emit(RetryStatus {
+                attempt,
+                max_attempts: MAX_FETCH_ATTEMPTS,
+                delay_ms,
+                reason: error.to_string(),
+            });
+            TimeoutFuture::new(delay_ms).await;
+            if fetch.is_cancelled() {
+                return Err(FetchHandle::cancelled_error());
+            }
+        }
+    }
+
+    async fn send_query_async(
+        user_data: &UserData,
+        query: &[(String, String)],
+        fetch: &FetchHandle,
+    ) -> AppResult<HttpResponse> {
+        let auth = AuthService::request_auth(user_data).await?;
+        if fetch.is_cancelled() {
+            return Err(FetchHandle::cancelled_error());
         }
 
-        let text = response.text().await.into_app_error()?;
+        // The desktop build queries from the Rust side, keeping credentials out
+        // of webview-visible URLs and avoiding CORS
+        let response = if TauriService::is_available() {
+            Self::send_tauri_async(&auth, query, fetch).await
+        } else {
+            Self::send_http_async(&auth, query, fetch).await
+        };
+        fetch.finish();
 
-        // Try to parse as AcledResponse first
-        if let Ok(api_response) = serde_json::from_str::<AcledResponse>(&text) {
+        response
+    }
+
+    async fn send_tauri_async(
+        auth: &RequestAuth,
+        query: &[(String, String)],
+        fetch: &FetchHandle,
+    ) -> AppResult<HttpResponse> {
+        let request_id = NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed);
+        fetch.start(InFlight::Tauri(request_id));
+
+        TauriService::invoke(
+            "acled_query",
+            &AcledQueryArgs {
+                auth,
+                query,
+                request_id,
+            },
+        )
+        .await
+        .map_err(|e| match e {
+            // The command only fails when the request itself could not be made
+            AppError::Unknown { message } => AppError::network(message),
+            other => other,
+        })
+    }
+
+    async fn send_http_async(
+        auth: &RequestAuth,
+        query: &[(String, String)],
+        fetch: &FetchHandle,
+    ) -> AppResult<HttpResponse> {
+        // The desktop client has its own timeout; here the request is aborted.
+        // The same controller lets the fetch handle cancel it
+        let controller = AbortController::new()
+            .map_err(|e| AppError::unknown(format!("Failed to create AbortController: {:?}", e)))?;
+        let signal = controller.signal();
+        fetch.start(InFlight::Http(controller.clone()));
+        let _timeout = Timeout::new(DEFAULT_API_TIMEOUT as u32 * 1000, move || {
+            controller.abort()
+        });
+        let timed_out = |error: AppError| {
+            if fetch.is_cancelled() {
+                FetchHandle::cancelled_error()
+            } else if signal.aborted() {
+                AppError::network(format!("Request timed out after {}s", DEFAULT_API_TIMEOUT))
+            } else {
+                error
+            }
+        };
+
+        let mut request = Request::get(&Self::build_url(auth, query)).abort_signal(Some(&signal));
+        if let RequestAuth::Bearer { access_token } = auth {
+            request = request.header("Authorization", &format!("Bearer {}", access_token));
+        }
+        let response = request.send().await.into_app_error().map_err(timed_out)?;
+
+        Ok(HttpResponse {
+            status: response.status(),
+            status_text: response.status_text(),
+            // Only readable cross-origin if the API exposes it
+            retry_after: response.headers().get("Retry-After"),
+            body: response.text().await.into_app_error().map_err(timed_out)?,
+        })
+    }
+
+    fn parse_response(response: HttpResponse) -> AppResult<Vec<AcledEvent>> {
+        let text = response.body;
+        let api_response = serde_json::from_str::<AcledResponse>(&text).ok();
+
+        if !(200..300).contains(&response.status) {
+            // Prefer ACLED's own explanation over the bare HTTP status
+            let message = api_response
+                .as_ref()
+                .and_then(AcledResponse::get_error_message)
+                .unwrap_or_else(|| format!("HTTP {}: {}", response.status, response.status_text));
+            return Err(AppError::api(response.status.into(), message));
+        }
+
+        if let Some(api_response) = api_response {
             if let Some(error_msg) = api_response.get_error_message() {
-                return Err(AppError::api(api_response.status.unwrap_or(400), error_msg));
+                return Err(AppError::api(
+                    api_response.get_error_status().unwrap_or(400),
+                    error_msg,
+                ));
             }
 
             if api_response.is_success() {
@@ -76,20 +448,24 @@ impl AcledService {
         Ok(events)
     }
 
-    fn build_url(user_data: &UserData, params: &AcledParams) -> String {
-        let mut url = format!(
-            "{}?key={}&email={}",
-            ACLED_BASE_URL, user_data.api_key, user_data.email
-        );
+    fn build_url(auth: &RequestAuth, query: &[(String, String)]) -> String {
+        let mut url = match auth {
+            RequestAuth::ApiKey { email, api_key } => format!(
+                "{}?key={}&email={}",
+                ACLED_BASE_URL,
+                urlencoding::encode(api_key),
+                urlencoding::encode(email)
+            ),
+            RequestAuth::Bearer { .. } => format!("{}?_format=json", ACLED_OAUTH_API_URL),
+        };
 
-        for (key, value) in params.to_query_params() {
-            url.push_str(&format!("&{}={}", key, urlencoding::encode(&value)));
+        for (key, value) in query {
+            url.push_str(&format!("&{}={}", key, urlencoding::encode(value)));
         }
 
         url
     }
 
-    #[allow(dead_code)]
     pub fn validate_params(params: &AcledParams) -> AppResult<()> {
         if params.start_date.is_empty() {
             return Err(AppError::validation("start_date", "Start date is required"));
@@ -99,8 +475,11 @@ impl AcledService {
             return Err(AppError::validation("end_date", "End date is required"));
         }
 
-        if params.country.is_empty() {
-            return Err(AppError::validation("country", "Country is required"));
+        if params.countries.is_empty() {
+            return Err(AppError::validation(
+                "countries",
+                "At least one country is required",
+            ));
         }
 
         if params.limit == 0 {
@@ -117,6 +496,109 @@ impl AcledService {
             ));
         }
 
+        if let Some(area) = &params.area {
+            if area.south > area.north || area.west > area.east {
+                return Err(AppError::validation(
+                    "area",
+                    "Area must run south to north and west to east",
+                ));
+            }
+        }
+
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::VerificationFailure;
+
+    fn response(status: u16, status_text: &str, body: &str) -> HttpResponse {
+        HttpResponse {
+            status,
+            status_text: status_text.to_string(),
+            body: body.to_string(),
+            retry_after: None,
+        }
+    }
+
+    #[test]
+    fn verification_failures_are_classified() {
+        let cases = [
+            (
+                response(401, "Unauthorized", r#"{"message":"Unauthorized"}"#),
+                "Invalid credentials",
+            ),
+            (
+                response(403, "Forbidden", "<html>Forbidden</html>"),
+                "Invalid credentials",
+            ),
+            (
+                response(
+                    403,
+                    "Forbidden",
+                    r#"{"message":"Account pending approval"}"#,
+                ),
+                "Account not activated",
+            ),
+            (response(429, "Too Many Requests", ""), "Rate limited"),
+            (
+                response(500, "Internal Server Error", ""),
+                "Verification failed",
+            ),
+            (
+                response(503, "Service Unavailable", "<html>Down</html>"),
+                "Verification failed",
+            ),
+            (
+                response(
+                    200,
+                    "OK",
+                    r#"{"success":false,"error":{"status":403,"message":"Access key is not valid"}}"#,
+                ),
+                "Invalid credentials",
+            ),
+            (
+                response(
+                    200,
+                    "OK",
+                    r#"{"success":false,"status":429,"message":"Too many requests"}"#,
+                ),
+                "Rate limited",
+            ),
+        ];
+
+        for (response, title) in cases {
+            let status = response.status;
+            let error = AcledService::parse_response(response).unwrap_err();
+            assert_eq!(
+                VerificationFailure::from_error(error).title(),
+                title,
+                "HTTP {}",
+                status
+            );
+        }
+    }
+
+    #[test]
+    fn transport_failures_are_network_failures() {
+        for message in [
+            "Failed to fetch".to_string(),
+            format!("Request timed out after {}s", DEFAULT_API_TIMEOUT),
+        ] {
+            let failure = VerificationFailure::from_error(AppError::network(message.clone()));
+            assert_eq!(failure.title(), "Cannot reach ACLED");
+            assert_eq!(failure, VerificationFailure::Network(message));
+        }
+    }
+
+    #[test]
+    fn successful_probe_is_not_a_failure() {
+        let body = r#"{"success":true,"count":0,"data":[]}"#;
+        assert_eq!(
+            AcledService::parse_response(response(200, "OK", body)).unwrap(),
+            Vec::new()
+        );
+    }
+}
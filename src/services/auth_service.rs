@@ -0,0 +1,477 @@
+use crate::config::{ACLED_OAUTH_CLIENT_ID, ACLED_TOKEN_URL, TOKEN_REFRESH_MARGIN_MS};
+use crate::errors::{AppError, AppResult, IntoAppError};
+use crate::logging::console;
+use crate::services::acled_service::HttpResponse;
+use crate::services::{AcledService, StorageService, TauriService};
+use crate::types::{OAuthTokens, UserData};
+use gloo_net::http::Request;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll, Waker};
+
+/// Credentials for a single ACLED request.
+#[derive(Serialize, Clone, PartialEq, Debug)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum RequestAuth {
+    ApiKey { email: String, api_key: String },
+    Bearer { access_token: String },
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    // Some servers only rotate the refresh token occasionally
+    refresh_token: Option<String>,
+    expires_in: f64,
+}
+
+#[derive(Deserialize)]
+struct TokenErrorResponse {
+    error: Option<String>,
+    error_description: Option<String>,
+    message: Option<String>,
+}
+
+#[derive(Serialize)]
+struct TokenArgs<'a> {
+    form: &'a [(String, String)],
+}
+
+/// Why a login could not be verified, in the terms the login screen explains.
+#[derive(Clone, PartialEq, Debug)]
+pub enum VerificationFailure {
+    InvalidCredentials(String),
+    AccountNotActivated(String),
+    RateLimited(String),
+    Network(String),
+    Other(String),
+}
+
+impl VerificationFailure {
+    /// Classifies an error from the login probe or token exchange by status
+    /// and by the wording of ACLED's error payload.
+    pub fn from_error(error: AppError) -> Self {
+        match error {
+            AppError::Network { message } => Self::Network(message),
+            AppError::Api { status, message } => {
+                let lower = message.to_lowercase();
+                if status == 429 || lower.contains("rate limit") || lower.contains("too many") {
+                    Self::RateLimited(message)
+                } else if lower.contains("activat")
+                    || lower.contains("pending")
+                    || lower.contains("not approved")
+                {
+                    Self::AccountNotActivated(message)
+                } else if matches!(status, 400 | 401 | 403)
+                    || lower.contains("access key")
+                    || lower.contains("invalid")
+                    || lower.contains("denied")
+                {
+                    Self::InvalidCredentials(message)
+                } else {
+                    Self::Other(message)
+                }
+            }
+            other => Self::Other(other.to_string()),
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Self::InvalidCredentials(_) => "Invalid credentials",
+            Self::AccountNotActivated(_) => "Account not activated",
+            Self::RateLimited(_) => "Rate limited",
+            Self::Network(_) => "Cannot reach ACLED",
+            Self::Other(_) => "Verification failed",
+        }
+    }
+
+    pub fn hint(&self) -> &'static str {
+        match self {
+            Self::InvalidCredentials(_) => {
+                "Check the email and key (or username and password) registered with ACLED."
+            }
+            Self::AccountNotActivated(_) => {
+                "Your ACLED account or key has not been approved for API access yet."
+            }
+            Self::RateLimited(_) => "ACLED is limiting requests; wait a minute and try again.",
+            Self::Network(_) => "Check your internet connection and try again.",
+            Self::Other(_) => "ACLED returned an unexpected error.",
+        }
+    }
+
+    pub fn detail(&self) -> &str {
+        match self {
+            Self::InvalidCredentials(detail)
+            | Self::AccountNotActivated(detail)
+            | Self::RateLimited(detail)
+            | Self::Network(detail)
+            | Self::Other(detail) => detail,
+        }
+    }
+}
+
+thread_local! {
+    // Latest tokens per username. Refresh tokens may be single-use, so once
+    // refreshed, the tokens captured in component props are stale; keeping
+    // every account lets profiles be switched back and forth
+    static SESSION_TOKENS: RefCell<HashMap<String, OAuthTokens>> = RefCell::new(HashMap::new());
+
+    // Refreshes in flight per username, so that requests racing past the
+    // expiry margin share one refresh_token grant
+    static REFRESHING: RefCell<HashMap<String, Rc<RefCell<PendingRefresh>>>> =
+        RefCell::new(HashMap::new());
+}
+
+#[derive(Default)]
+struct PendingRefresh {
+    result: Option<AppResult<OAuthTokens>>,
+    waiters: Vec<Waker>,
+}
+
+/// Resolves once the shared refresh has finished, for every caller awaiting it.
+struct RefreshResult(Rc<RefCell<PendingRefresh>>);
+
+impl Future for RefreshResult {
+    type Output = AppResult<OAuthTokens>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut pending = self.0.borrow_mut();
+        match &pending.result {
+            Some(result) => Poll::Ready(result.clone()),
+            None => {
+                pending.waiters.push(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+pub struct AuthService;
+
+impl AuthService {
+    /// Exchanges a username and password for tokens (OAuth password grant).
+    pub async fn login(username: &str, password: &str) -> AppResult<UserData> {
+        if username.is_empty() || password.is_empty() {
+            return Err(AppError::validation(
+                "credentials",
+                "Username and password are required",
+            ));
+        }
+
+        let tokens = Self::request_tokens(
+            &[
+                ("username", username),
+                ("password", password),
+                ("grant_type", "password"),
+                ("client_id", ACLED_OAUTH_CLIENT_ID),
+                ("scope", "authenticated"),
+            ],
+            None,
+        )
+        .await?;
+        Self::remember(username, &tokens);
+
+        console::log_with_context!("AUTH", "Obtained OAuth tokens for {}", username);
+        Ok(UserData::OAuth {
+            username: username.to_string(),
+            tokens,
+        })
+    }
+
+    /// Probes the API with `user_data` before it is accepted at login.
+    pub async fn verify(user_data: &UserData) -> Result<(), VerificationFailure> {
+        console::log_with_context!("AUTH", "Verifying credentials for {}", user_data.account());
+        AcledService::verify_credentials(user_data)
+            .await
+            .map_err(VerificationFailure::from_error)
+    }
+
+    /// Credentials for the next request, refreshing OAuth tokens that are
+    /// about to expire.
+    pub async fn request_auth(user_data: &UserData) -> AppResult<RequestAuth> {
+        let (username, tokens) = match user_data {
+            UserData::ApiKey { email, api_key } => {
+                return Ok(RequestAuth::ApiKey {
+                    email: email.clone(),
+                    api_key: api_key.clone(),
+                })
+            }
+            UserData::OAuth { username, tokens } => (username, tokens),
+        };
+
+        let tokens = Self::remembered(username).unwrap_or_else(|| tokens.clone());
+        if !needs_refresh(&tokens, js_sys::Date::now()) {
+            return Ok(RequestAuth::Bearer {
+                access_token: tokens.access_token,
+            });
+        }
+
+        let refreshed = RefreshResult(Self::start_refresh(username, tokens)).await?;
+        Ok(RequestAuth::Bearer {
+            access_token: refreshed.access_token,
+        })
+    }
+
+    // Joins the refresh already running for `username`, or starts one. It runs
+    // detached so that a cancelled request can't strand the others waiting on it
+    fn start_refresh(username: &str, tokens: OAuthTokens) -> Rc<RefCell<PendingRefresh>> {
+        if let Some(pending) =
+            REFRESHING.with(|refreshing| refreshing.borrow().get(username).cloned())
+        {
+            return pending;
+        }
+
+        let pending = Rc::new(RefCell::new(PendingRefresh::default()));
+        REFRESHING.with(|refreshing| {
+            refreshing
+                .borrow_mut()
+                .insert(username.to_string(), pending.clone())
+        });
+
+        let username = username.to_string();
+        let shared = pending.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            let result = Self::refresh(&username, &tokens).await;
+            REFRESHING.with(|refreshing| refreshing.borrow_mut().remove(&username));
+
+            let waiters = {
+                let mut shared = shared.borrow_mut();
+                shared.result = Some(result);
+                std::mem::take(&mut shared.waiters)
+            };
+            waiters.into_iter().for_each(Waker::wake);
+        });
+
+        pending
+    }
+
+    async fn refresh(username: &str, tokens: &OAuthTokens) -> AppResult<OAuthTokens> {
+        console::log_with_context!("AUTH", "Refreshing access token for {}", username);
+        let refreshed = Self::request_tokens(
+            &[
+                ("grant_type", "refresh_token"),
+                ("refresh_token", &tokens.refresh_token),
+                ("client_id", ACLED_OAUTH_CLIENT_ID),
+            ],
+            Some(&tokens.refresh_token),
+        )
+        .await
+        .map_err(|e| match e {
+            AppError::Api { status, .. } if status == 400 || status == 401 => {
+                AppError::api(status, "Session expired; please log in again")
+            }
+            other => other,
+        })?;
+        Self::remember(username, &refreshed);
+
+        let updated = UserData::OAuth {
+            username: username.to_string(),
+            tokens: refreshed.clone(),
+        };
+        if let Err(e) = StorageService::update_user_data(&updated).await {
+            console::error_with_context!("AUTH", "Failed to save refreshed tokens: {}", e);
+        }
+
+        Ok(refreshed)
+    }
+
+    /// Forgets session tokens, e.g. on logout.
+    pub fn forget() {
+        SESSION_TOKENS.with(|session| session.borrow_mut().clear());
+    }
+
+    fn remember(username: &str, tokens: &OAuthTokens) {
+        SESSION_TOKENS.with(|session| {
+            session
+                .borrow_mut()
+                .insert(username.to_string(), tokens.clone());
+        });
+    }
+
+    fn remembered(username: &str) -> Option<OAuthTokens> {
+        SESSION_TOKENS.with(|session| session.borrow().get(username).cloned())
+    }
+
+    async fn request_tokens(
+        form: &[(&str, &str)],
+        previous_refresh_token: Option<&str>,
+    ) -> AppResult<OAuthTokens> {
+        let form: Vec<(String, String)> = form
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+
+        let response = if TauriService::is_available() {
+            TauriService::invoke("acled_token", &TokenArgs { form: &form })
+                .await
+                .map_err(|e| match e {
+                    AppError::Unknown { message } => AppError::network(message),
+                    other => other,
+                })?
+        } else {
+            Self::post_form_async(&form).await?
+        };
+
+        parse_token_response(response, previous_refresh_token, js_sys::Date::now())
+    }
+
+    async fn post_form_async(form: &[(String, String)]) -> AppResult<HttpResponse> {
+        let body = form
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, urlencoding::encode(value)))
+            .collect::<Vec<_>>()
+            .join("&");
+
+        let response = Request::post(ACLED_TOKEN_URL)
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .body(body)
+            .into_app_error()?
+            .send()
+            .await
+            .into_app_error()?;
+
+        Ok(HttpResponse {
+            status: response.status(),
+            status_text: response.status_text(),
+            retry_after: None,
+            body: response.text().await.into_app_error()?,
+        })
+    }
+}
+
+/// Whether `tokens` should be refreshed before a request made at `now_ms`.
+fn needs_refresh(tokens: &OAuthTokens, now_ms: f64) -> bool {
+    tokens.expires_within(now_ms, TOKEN_REFRESH_MARGIN_MS)
+}
+
+/// Turns a token endpoint response received at `now_ms` into tokens. A
+/// response without a refresh token keeps `previous_refresh_token`.
+fn parse_token_response(
+    response: HttpResponse,
+    previous_refresh_token: Option<&str>,
+    now_ms: f64,
+) -> AppResult<OAuthTokens> {
+    if !(200..300).contains(&response.status) {
+        let detail = serde_json::from_str::<TokenErrorResponse>(&response.body)
+            .ok()
+            .and_then(|error| error.error_description.or(error.message).or(error.error))
+            .unwrap_or(response.status_text);
+        return Err(AppError::api(
+            response.status.into(),
+            format!("Authentication failed: {}", detail),
+        ));
+    }
+
+    let token: TokenResponse = serde_json::from_str(&response.body).into_app_error()?;
+    let refresh_token = token
+        .refresh_token
+        .or_else(|| previous_refresh_token.map(str::to_string))
+        .ok_or_else(|| AppError::api(502, "Token response has no refresh token"))?;
+
+    Ok(OAuthTokens {
+        access_token: token.access_token,
+        refresh_token,
+        expires_at: now_ms + token.expires_in * 1000.0,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW_MS: f64 = 1_700_000_000_000.0;
+
+    fn response(status: u16, body: &str) -> HttpResponse {
+        HttpResponse {
+            status,
+            status_text: "Status Text".to_string(),
+            body: body.to_string(),
+            retry_after: None,
+        }
+    }
+
+    fn tokens(expires_at: f64) -> OAuthTokens {
+        OAuthTokens {
+            access_token: "access".to_string(),
+            refresh_token: "refresh".to_string(),
+            expires_at,
+        }
+    }
+
+    #[test]
+    fn token_response_sets_expiry_from_expires_in() {
+        let body =
+            r#"{"access_token":"a1","refresh_token":"r1","expires_in":3600,"token_type":"Bearer"}"#;
+        let parsed = parse_token_response(response(200, body), None, NOW_MS).unwrap();
+
+        assert_eq!(parsed.access_token, "a1");
+        assert_eq!(parsed.refresh_token, "r1");
+        assert_eq!(parsed.expires_at, NOW_MS + 3_600_000.0);
+    }
+
+    #[test]
+    fn token_response_keeps_the_previous_refresh_token() {
+        let body = r#"{"access_token":"a2","expires_in":60}"#;
+
+        let parsed = parse_token_response(response(200, body), Some("r1"), NOW_MS).unwrap();
+        assert_eq!(parsed.refresh_token, "r1");
+
+        assert!(matches!(
+            parse_token_response(response(200, body), None, NOW_MS),
+            Err(AppError::Api { status: 502, .. })
+        ));
+    }
+
+    #[test]
+    fn token_errors_carry_the_server_detail() {
+        let cases = [
+            (
+                r#"{"error":"invalid_grant","error_description":"Bad password"}"#,
+                "Authentication failed: Bad password",
+            ),
+            (
+                r#"{"message":"Account blocked"}"#,
+                "Authentication failed: Account blocked",
+            ),
+            (
+                r#"{"error":"invalid_client"}"#,
+                "Authentication failed: invalid_client",
+            ),
+            (
+                "<html>Bad Request</html>",
+                "Authentication failed: Status Text",
+            ),
+        ];
+
+        for (body, expected) in cases {
+            match parse_token_response(response(400, body), None, NOW_MS) {
+                Err(AppError::Api { status, message }) => {
+                    assert_eq!(status, 400);
+                    assert_eq!(message, expected);
+                }
+                other => panic!("expected an API error for {}, got {:?}", body, other),
+            }
+        }
+    }
+
+    #[test]
+    fn malformed_success_body_is_an_error() {
+        assert!(
+            parse_token_response(response(200, r#"{"access_token":"a"}"#), None, NOW_MS).is_err()
+        );
+    }
+
+    #[test]
+    fn refreshes_within_the_margin_of_expiry() {
+        let expires_at = NOW_MS + TOKEN_REFRESH_MARGIN_MS;
+
+        assert!(!needs_refresh(&tokens(expires_at + 1.0), NOW_MS));
+        assert!(needs_refresh(&tokens(expires_at), NOW_MS));
+        assert!(needs_refresh(&tokens(NOW_MS - 1.0), NOW_MS));
+    }
+}
@@ -0,0 +1,369 @@
+use crate::config::{ACLED_ATTRIBUTION, DOWNLOAD_REVOKE_DELAY_MS};
+use crate::errors::{AppError, AppResult, IntoAppError};
+use crate::services::TauriService;
+use crate::types::{AcledEvent, AcledParams, EventField};
+use gloo_timers::callback::Timeout;
+use serde::Serialize;
+use serde_json::{json, Value};
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ExportFormat {
+    Csv,
+    GeoJson,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::GeoJson => "geojson",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "CSV",
+            ExportFormat::GeoJson => "GeoJSON",
+        }
+    }
+
+    fn mime_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv;charset=utf-8",
+            ExportFormat::GeoJson => "application/geo+json",
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SaveExportArgs<'a> {
+    file_name: &'a str,
+    filter_name: &'a str,
+    extension: &'a str,
+    contents: &'a str,
+    note: Option<&'a str>,
+}
+
+pub struct ExportService;
+
+impl ExportService {
+    /// Serializes `events` and hands them to the platform's save mechanism.
+    /// Resolves to `false` if the user cancelled the desktop save dialog.
+    pub async fn export(
+        format: ExportFormat,
+        events: &[AcledEvent],
+        params: Option<&AcledParams>,
+    ) -> AppResult<bool> {
+        // GeoJSON carries the query note in its metadata; CSV gets a sidecar file
+        let (contents, note) = match format {
+            ExportFormat::Csv => (
+                Self::to_csv(events),
+                Some(Self::note_lines(params).join("\n") + "\n"),
+            ),
+            ExportFormat::GeoJson => (Self::to_geojson(events, params)?, None),
+        };
+        let file_name = Self::file_name(format, params);
+
+        Self::save_with_note(
+            &file_name,
+            format.label(),
+            format.extension(),
+            format.mime_type(),
+            &contents,
+            note.as_deref(),
+        )
+        .await
+    }
+
+    /// Saves `contents` through the desktop save dialog, or as a browser
+    /// download on the web. Resolves to `false` if the dialog was cancelled.
+    pub async fn save_file(
+        file_name: &str,
+        filter_name: &str,
+        extension: &str,
+        mime_type: &str,
+        contents: &str,
+    ) -> AppResult<bool> {
+        Self::save_with_note(file_name, filter_name, extension, mime_type, contents, None).await
+    }
+
+    // `note` is saved as a `.note.txt` file named after `file_name`
+    async fn save_with_note(
+        file_name: &str,
+        filter_name: &str,
+        extension: &str,
+        mime_type: &str,
+        contents: &str,
+        note: Option<&str>,
+    ) -> AppResult<bool> {
+        if TauriService::is_available() {
+            let saved_path: Option<String> = TauriService::invoke(
+                "save_export",
+                &SaveExportArgs {
+                    file_name,
+                    filter_name,
+                    extension,
+                    contents,
+                    note,
+                },
+            )
+            .await?;
+            Ok(saved_path.is_some())
+        } else {
+            Self::download(file_name, mime_type, contents)?;
+            if let Some(note) = note {
+                Self::download(&Self::note_file_name(file_name), "text/plain", note)?;
+            }
+            Ok(true)
+        }
+    }
+
+    /// Reads a file picked with an `<input type="file">` as text.
+    pub async fn read_file(file: web_sys::File) -> AppResult<String> {
+        let text = JsFuture::from(file.text())
+            .await
+            .map_err(|e| AppError::storage(format!("Failed to read file: {:?}", e)))?;
+        text.as_string()
+            .ok_or_else(|| AppError::storage("File is not valid text"))
+    }
+
+    /// A header row followed by one row per event, with a column for every
+    /// [`EventField`]. The query and attribution go in a sidecar note instead.
+    pub fn to_csv(events: &[AcledEvent]) -> String {
+        let mut csv = String::new();
+
+        let header: Vec<&str> = EventField::ALL.iter().map(|field| field.key()).collect();
+        csv.push_str(&header.join(","));
+        csv.push('\n');
+
+        for event in events {
+            let row: Vec<String> = EventField::ALL
+                .iter()
+                .map(|field| Self::csv_escape(&field.text(event)))
+                .collect();
+            csv.push_str(&row.join(","));
+            csv.push('\n');
+        }
+
+        csv
+    }
+
+    /// A FeatureCollection of Point features (null geometry for events without
+    /// coordinates), with the query note in a top-level `metadata` member.
+    pub fn to_geojson(events: &[AcledEvent], params: Option<&AcledParams>) -> AppResult<String> {
+        let features: Vec<Value> = events
+            .iter()
+            .map(|event| {
+                let geometry = match event.coordinates() {
+                    Some((lat, lng)) => json!({ "type": "Point", "coordinates": [lng, lat] }),
+                    None => Value::Null,
+                };
+                let properties = serde_json::to_value(event).into_app_error()?;
+
+                Ok(json!({
+                    "type": "Feature",
+                    "id": event.data_id,
+                    "geometry": geometry,
+                    "properties": properties,
+                }))
+            })
+            .collect::<AppResult<_>>()?;
+
+        let collection = json!({
+            "type": "FeatureCollection",
+            "metadata": {
+                "attribution": ACLED_ATTRIBUTION,
+                "exported_at": Self::timestamp(),
+                "event_count": events.len(),
+                "query": params,
+            },
+            "features": features,
+        });
+
+        serde_json::to_string_pretty(&collection).into_app_error()
+    }
+
+    fn note_lines(params: Option<&AcledParams>) -> Vec<String> {
+        let mut lines = vec![
+            format!("Source: {}", ACLED_ATTRIBUTION),
+            format!("Exported: {}", Self::timestamp()),
+        ];
+
+        if let Some(params) = params {
+            lines.push(format!(
+                "Query: {} to {}; countries: {}; event types: {}",
+                params.start_date,
+                params.end_date,
+                params.countries.join(" | "),
+                if params.event_types.is_empty() {
+                    "all".to_string()
+                } else {
+                    params.event_types.join(" | ")
+                }
+            ));
+            if let Some(area) = &params.area {
+                lines.push(format!(
+                    "Area: latitude {:.4} to {:.4}; longitude {:.4} to {:.4}",
+                    area.south, area.north, area.west, area.east
+                ));
+            }
+        }
+
+        lines
+    }
+
+    // Text that a spreadsheet would evaluate as a formula gets a leading `'`;
+    // numbers such as negative coordinates are left as they are
+    fn csv_escape(value: &str) -> String {
+        let value = if value.starts_with(['=', '+', '-', '@']) && value.parse::<f64>().is_err() {
+            format!("'{}", value)
+        } else {
+            value.to_string()
+        };
+
+        if value.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        } else {
+            value
+        }
+    }
+
+    fn note_file_name(file_name: &str) -> String {
+        let stem = file_name
+            .rsplit_once('.')
+            .map_or(file_name, |(stem, _)| stem);
+        format!("{}.note.txt", stem)
+    }
+
+    fn file_name(format: ExportFormat, params: Option<&AcledParams>) -> String {
+        let stem = match params {
+            Some(params) => format!(
+                "acled_{}_{}_{}",
+                params.countries.join("-"),
+                params.start_date,
+                params.end_date
+            ),
+            None => "acled_events".to_string(),
+        };
+
+        let stem: String = stem
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+
+        format!("{}.{}", stem, format.extension())
+    }
+
+    fn timestamp() -> String {
+        String::from(js_sys::Date::new_0().to_iso_string())
+    }
+
+    // Web build: hand the file to the browser through a temporary object URL
+    fn download(file_name: &str, mime_type: &str, contents: &str) -> AppResult<()> {
+        let storage_error = |e| AppError::storage(format!("Failed to download export: {:?}", e));
+
+        let parts = js_sys::Array::of1(&contents.into());
+        let options = web_sys::BlobPropertyBag::new();
+        options.set_type(mime_type);
+        let blob = web_sys::Blob::new_with_str_sequence_and_options(&parts, &options)
+            .map_err(storage_error)?;
+        let url = web_sys::Url::create_object_url_with_blob(&blob).map_err(storage_error)?;
+
+        let document = web_sys::window()
+            .and_then(|window| window.document())
+            .ok_or_else(|| AppError::storage("Document not available"))?;
+        let anchor = document
+            .create_element("a")
+            .map_err(storage_error)?
+            .unchecked_into::<web_sys::HtmlAnchorElement>();
+        anchor.set_href(&url);
+        anchor.set_download(file_name);
+        anchor.click();
+
+        // Revoking right away can cancel a download that hasn't started yet
+        Timeout::new(DOWNLOAD_REVOKE_DELAY_MS, move || {
+            let _ = web_sys::Url::revoke_object_url(&url);
+        })
+        .forget();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_escape_quotes_separators() {
+        assert_eq!(ExportService::csv_escape("Lagos"), "Lagos");
+        assert_eq!(
+            ExportService::csv_escape("Kano, Nigeria"),
+            "\"Kano, Nigeria\""
+        );
+        assert_eq!(
+            ExportService::csv_escape("said \"stop\""),
+            "\"said \"\"stop\"\"\""
+        );
+        assert_eq!(ExportService::csv_escape("line\nbreak"), "\"line\nbreak\"");
+        assert_eq!(ExportService::csv_escape(""), "");
+    }
+
+    #[test]
+    fn csv_escape_defuses_formulas() {
+        assert_eq!(
+            ExportService::csv_escape("=HYPERLINK(\"x\")"),
+            "\"'=HYPERLINK(\"\"x\"\")\""
+        );
+        assert_eq!(ExportService::csv_escape("+cmd"), "'+cmd");
+        assert_eq!(ExportService::csv_escape("-rebels"), "'-rebels");
+        assert_eq!(ExportService::csv_escape("@SUM(A1)"), "'@SUM(A1)");
+        assert_eq!(ExportService::csv_escape("=1+2, 3"), "\"'=1+2, 3\"");
+    }
+
+    #[test]
+    fn csv_escape_keeps_numbers() {
+        assert_eq!(ExportService::csv_escape("-4.25"), "-4.25");
+        assert_eq!(ExportService::csv_escape("+3"), "+3");
+        assert_eq!(ExportService::csv_escape("12"), "12");
+    }
+
+    #[test]
+    fn csv_starts_with_the_header() {
+        let event = AcledEvent {
+            actor1: "=cmd|' /C calc'!A0".to_string(),
+            latitude: Some(-1.5),
+            notes: "Two killed, one injured".to_string(),
+            ..Default::default()
+        };
+        let csv = ExportService::to_csv(&[event]);
+        let mut lines = csv.lines();
+
+        let header = lines.next().unwrap();
+        assert!(header.starts_with("event_id_cnty,event_date,"));
+        assert_eq!(header.split(',').count(), EventField::ALL.len());
+
+        let row = lines.next().unwrap();
+        assert!(row.contains(",'=cmd|' /C calc'!A0,"));
+        assert!(row.contains(",-1.5,"));
+        assert!(row.contains(",\"Two killed, one injured\","));
+        assert_eq!(lines.next(), None);
+    }
+
+    #[test]
+    fn note_file_name_replaces_the_extension() {
+        assert_eq!(
+            ExportService::note_file_name("acled_Mali_2024-01-01_2024-02-01.csv"),
+            "acled_Mali_2024-01-01_2024-02-01.note.txt"
+        );
+        assert_eq!(ExportService::note_file_name("events"), "events.note.txt");
+    }
+}
@@ -1,5 +1,13 @@
 pub mod acled_service;
+pub mod auth_service;
+pub mod export_service;
 pub mod storage_service;
+pub mod tauri_service;
+pub mod tile_service;
 
-pub use acled_service::AcledService;
-pub use storage_service::StorageService; 
\ No newline at end of file
+pub use acled_service::{AcledService, FetchHandle};
+pub use auth_service::{AuthService, RequestAuth, VerificationFailure};
+pub use export_service::{ExportFormat, ExportService};
+pub use storage_service::StorageService;
+pub use tauri_service::TauriService;
+pub use tile_service::{OfflineTileSet, TileService}; 
\ No newline at end of file
@@ -1,27 +1,125 @@
-use crate::config::{STORAGE_USER_API_KEY, STORAGE_USER_EMAIL};
+use crate::config::{
+    STORAGE_PREFERENCES, STORAGE_SAVED_QUERIES, STORAGE_USER_API_KEY, STORAGE_USER_CREDENTIALS,
+    STORAGE_USER_EMAIL,
+};
 use crate::errors::{AppError, AppResult};
-use crate::types::UserData;
+use crate::logging::console;
+use crate::services::TauriService;
+use crate::types::{ProfileStore, SavedQueries, UserData, UserPreferences};
+use serde::Serialize;
+use serde_json::json;
+
+#[derive(Serialize)]
+struct CredentialArgs<'a> {
+    /// Serialized [`ProfileStore`]; the backend stores it opaquely.
+    credentials: &'a str,
+}
 
 pub struct StorageService;
 
 impl StorageService {
-    pub fn save_user_data(user_data: &UserData) -> AppResult<()> {
-        let storage = Self::get_storage()?;
+    /// Desktop builds keep credential profiles in the OS secret store via the
+    /// Tauri backend; the web build can only use local storage.
+    pub async fn save_profiles(store: &ProfileStore) -> AppResult<()> {
+        if !TauriService::is_available() {
+            return Self::save_local_profiles(store);
+        }
 
-        storage
-            .set_item(STORAGE_USER_EMAIL, &user_data.email)
-            .map_err(|e| AppError::storage(format!("Failed to save email: {:?}", e)))?;
+        let credentials = store.to_json()?;
+        TauriService::invoke::<_, ()>(
+            "save_credentials",
+            &CredentialArgs {
+                credentials: &credentials,
+            },
+        )
+        .await
+        .map_err(Self::desktop_error)?;
+
+        // Don't leave a plaintext copy from an older version behind
+        Self::clear_local_profiles()
+    }
 
-        storage
-            .set_item(STORAGE_USER_API_KEY, &user_data.api_key)
-            .map_err(|e| AppError::storage(format!("Failed to save API key: {:?}", e)))?;
+    pub async fn load_profiles() -> AppResult<ProfileStore> {
+        if !TauriService::is_available() {
+            return Self::load_local_profiles();
+        }
+
+        let stored: Option<String> = TauriService::invoke("load_credentials", &json!({}))
+            .await
+            .map_err(Self::desktop_error)?;
+        if let Some(json) = stored {
+            return ProfileStore::from_json(&json);
+        }
+
+        // Move credentials saved in local storage by older versions
+        let store = Self::load_local_profiles()?;
+        if !store.is_empty() {
+            console::log_with_context!(
+                "STORAGE",
+                "Moving saved credentials to the system secret store"
+            );
+            Self::save_profiles(&store).await?;
+        }
+        Ok(store)
+    }
 
+    /// Loads the stored profiles, applies `change` and saves the result, so
+    /// concurrent writers such as token refreshes are not overwritten with a
+    /// stale copy. Returns the updated store.
+    pub async fn update_profiles(
+        change: impl FnOnce(&mut ProfileStore) -> AppResult<()>,
+    ) -> AppResult<ProfileStore> {
+        let mut store = Self::load_profiles().await?;
+        change(&mut store)?;
+        if store.is_empty() {
+            Self::clear_profiles().await?;
+        } else {
+            Self::save_profiles(&store).await?;
+        }
+        Ok(store)
+    }
+
+    /// Stores new credentials for the profiles that share an account with
+    /// `user_data`.
+    pub async fn update_user_data(user_data: &UserData) -> AppResult<()> {
+        let mut store = Self::load_profiles().await?;
+        if store.update_credentials(user_data) {
+            Self::save_profiles(&store).await?;
+        }
         Ok(())
     }
 
-    pub fn load_user_data() -> AppResult<Option<UserData>> {
+    pub async fn clear_profiles() -> AppResult<()> {
+        if TauriService::is_available() {
+            TauriService::invoke::<_, ()>("clear_credentials", &json!({}))
+                .await
+                .map_err(Self::desktop_error)?;
+        }
+
+        Self::clear_local_profiles()
+    }
+
+    fn save_local_profiles(store: &ProfileStore) -> AppResult<()> {
         let storage = Self::get_storage()?;
 
+        storage
+            .set_item(STORAGE_USER_CREDENTIALS, &store.to_json()?)
+            .map_err(|e| AppError::storage(format!("Failed to save credentials: {:?}", e)))?;
+
+        Self::clear_legacy_user_data(&storage)
+    }
+
+    fn load_local_profiles() -> AppResult<ProfileStore> {
+        let storage = Self::get_storage()?;
+
+        let json = storage
+            .get_item(STORAGE_USER_CREDENTIALS)
+            .map_err(|e| AppError::storage(format!("Failed to load credentials: {:?}", e)))?;
+        if let Some(json) = json {
+            return ProfileStore::from_json(&json);
+        }
+
+        // Older versions stored the email and API key under separate keys
         let email = storage
             .get_item(STORAGE_USER_EMAIL)
             .map_err(|e| AppError::storage(format!("Failed to load email: {:?}", e)))?;
@@ -32,15 +130,23 @@ impl StorageService {
 
         match (email, api_key) {
             (Some(email), Some(api_key)) if !email.is_empty() && !api_key.is_empty() => {
-                Ok(Some(UserData::new(email, api_key)))
+                Ok(ProfileStore::from_user_data(UserData::new(email, api_key)))
             }
-            _ => Ok(None),
+            _ => Ok(ProfileStore::default()),
         }
     }
 
-    pub fn clear_user_data() -> AppResult<()> {
+    fn clear_local_profiles() -> AppResult<()> {
         let storage = Self::get_storage()?;
 
+        storage
+            .remove_item(STORAGE_USER_CREDENTIALS)
+            .map_err(|e| AppError::storage(format!("Failed to remove credentials: {:?}", e)))?;
+
+        Self::clear_legacy_user_data(&storage)
+    }
+
+    fn clear_legacy_user_data(storage: &web_sys::Storage) -> AppResult<()> {
         storage
             .remove_item(STORAGE_USER_EMAIL)
             .map_err(|e| AppError::storage(format!("Failed to remove email: {:?}", e)))?;
@@ -52,6 +158,60 @@ impl StorageService {
         Ok(())
     }
 
+    fn desktop_error(error: AppError) -> AppError {
+        match error {
+            AppError::Unknown { message } => AppError::storage(message),
+            other => other,
+        }
+    }
+
+    pub fn save_saved_queries(saved: &SavedQueries) -> AppResult<()> {
+        let storage = Self::get_storage()?;
+
+        storage
+            .set_item(STORAGE_SAVED_QUERIES, &saved.to_json()?)
+            .map_err(|e| AppError::storage(format!("Failed to save queries: {:?}", e)))?;
+
+        Ok(())
+    }
+
+    pub fn load_saved_queries() -> AppResult<SavedQueries> {
+        let storage = Self::get_storage()?;
+
+        let json = storage
+            .get_item(STORAGE_SAVED_QUERIES)
+            .map_err(|e| AppError::storage(format!("Failed to load queries: {:?}", e)))?;
+
+        match json {
+            Some(json) => SavedQueries::from_json(&json),
+            None => Ok(SavedQueries::default()),
+        }
+    }
+
+    pub fn save_preferences(preferences: &UserPreferences) -> AppResult<()> {
+        let storage = Self::get_storage()?;
+
+        storage
+            .set_item(STORAGE_PREFERENCES, &preferences.to_json()?)
+            .map_err(|e| AppError::storage(format!("Failed to save preferences: {:?}", e)))?;
+
+        Ok(())
+    }
+
+    /// Preferences saved by any earlier version are migrated on load.
+    pub fn load_preferences() -> AppResult<UserPreferences> {
+        let storage = Self::get_storage()?;
+
+        let json = storage
+            .get_item(STORAGE_PREFERENCES)
+            .map_err(|e| AppError::storage(format!("Failed to load preferences: {:?}", e)))?;
+
+        match json {
+            Some(json) => UserPreferences::from_json(&json),
+            None => Ok(UserPreferences::default()),
+        }
+    }
+
     #[allow(dead_code)]
     pub fn clear_all() -> AppResult<()> {
         let storage = Self::get_storage()?;
@@ -0,0 +1,47 @@
+use crate::errors::{AppError, AppResult, IntoAppError};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"], js_name = invoke, catch)]
+    async fn tauri_invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+/// Bridge to the commands registered in `src-tauri`. Only usable in the desktop
+/// build; check [`TauriService::is_available`] before invoking.
+pub struct TauriService;
+
+impl TauriService {
+    pub fn is_available() -> bool {
+        web_sys::window()
+            .and_then(|window| js_sys::Reflect::get(&window, &"__TAURI__".into()).ok())
+            .is_some_and(|tauri| !tauri.is_undefined() && !tauri.is_null())
+    }
+
+    /// Invokes `cmd` with `args` serialized as the command's named arguments
+    /// (Tauri expects them camelCased).
+    pub async fn invoke<A, R>(cmd: &str, args: &A) -> AppResult<R>
+    where
+        A: Serialize,
+        R: DeserializeOwned,
+    {
+        let args = serde_json::to_string(args).into_app_error()?;
+        let args = js_sys::JSON::parse(&args)
+            .map_err(|e| AppError::serialization(format!("Invalid command arguments: {:?}", e)))?;
+
+        let result = tauri_invoke(cmd, args).await.map_err(|e| {
+            let message = e.as_string().unwrap_or_else(|| format!("{:?}", e));
+            AppError::unknown(format!("Desktop command '{}' failed: {}", cmd, message))
+        })?;
+
+        if result.is_undefined() || result.is_null() {
+            return serde_json::from_str("null").into_app_error();
+        }
+
+        let json = js_sys::JSON::stringify(&result)
+            .map_err(|e| AppError::serialization(format!("Invalid command result: {:?}", e)))?;
+        serde_json::from_str(&String::from(json)).into_app_error()
+    }
+}
@@ -0,0 +1,75 @@
+use crate::config::OFFLINE_TILE_PROTOCOL;
+use crate::errors::{AppError, AppResult};
+use crate::services::TauriService;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"], js_name = convertFileSrc)]
+    fn convert_file_src(path: &str, protocol: &str) -> String;
+}
+
+/// Summary of an offline tile source, as read by the backend.
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OfflineTileSet {
+    pub name: String,
+    pub format: Option<String>,
+    pub min_zoom: Option<u32>,
+    pub max_zoom: Option<u32>,
+}
+
+#[derive(Serialize)]
+struct ChooseArgs {
+    directory: bool,
+}
+
+#[derive(Serialize)]
+struct OpenArgs<'a> {
+    path: &'a str,
+}
+
+/// Offline basemap tiles, served by the desktop backend from a local MBTiles
+/// file or `{z}/{x}/{y}` directory. Not available in the web build.
+pub struct TileService;
+
+impl TileService {
+    /// Leaflet URL template for the offline tiles. The scheme differs by
+    /// platform (`tiles://localhost/` or `http://tiles.localhost/`).
+    pub fn url_template() -> String {
+        format!(
+            "{}{{z}}/{{x}}/{{y}}",
+            convert_file_src("", OFFLINE_TILE_PROTOCOL)
+        )
+    }
+
+    /// Prompts for an MBTiles file, or a tile directory when `directory` is
+    /// set. Resolves to `None` if the dialog was cancelled.
+    pub async fn choose(directory: bool) -> AppResult<Option<String>> {
+        TauriService::invoke("choose_offline_tiles", &ChooseArgs { directory })
+            .await
+            .map_err(Self::desktop_error)
+    }
+
+    /// Starts serving tiles from `path`.
+    pub async fn open(path: &str) -> AppResult<OfflineTileSet> {
+        TauriService::invoke("open_offline_tiles", &OpenArgs { path })
+            .await
+            .map_err(Self::desktop_error)
+    }
+
+    pub async fn close() -> AppResult<()> {
+        TauriService::invoke("close_offline_tiles", &json!({}))
+            .await
+            .map_err(Self::desktop_error)
+    }
+
+    fn desktop_error(error: AppError) -> AppError {
+        match error {
+            AppError::Unknown { message } => AppError::storage(message),
+            other => other,
+        }
+    }
+}
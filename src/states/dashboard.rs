@@ -1,17 +1,60 @@
-use crate::components::{AcledEvent, MapData, MapDisplay, ResponseDisplay};
-use crate::types::{AppState, DashboardView, UserData};
+use crate::components::{AcledEvent, EventsTable, MapData, MapDisplay, ResponseDisplay};
+use crate::geo::{select_events, Bounds, Selection};
+use crate::types::{
+    AcledParams, AppState, Basemap, DashboardView, MapView, PreferencesAction, Profile,
+    UserPreferences,
+};
+use crate::utils::text::search_tokens;
 use yew::prelude::*;
 
 #[derive(Properties, PartialEq)]
 pub struct DashboardProps {
-    pub user_data: UserData,
+    pub profile: Profile,
     pub current_view: DashboardView,
+    pub preferences: UserPreferences,
     pub on_state_change: Callback<AppState>,
+    pub on_preferences_change: Callback<PreferencesAction>,
 }
 
 #[function_component(Dashboard)]
 pub fn dashboard(props: &DashboardProps) -> Html {
     let events_data = use_state(|| Option::<Vec<AcledEvent>>::None);
+    let query_params = use_state(|| Option::<AcledParams>::None);
+    let search = use_state(String::new);
+    let selection = use_state(|| Option::<Selection>::None);
+    let query_area = use_state(|| {
+        props
+            .preferences
+            .last_params
+            .as_ref()
+            .and_then(|params| params.area)
+    });
+
+    // The search and the area drawn on the map narrow the list, the table and
+    // the map markers
+    let matching_events = use_memo(
+        (
+            (*events_data).clone(),
+            search_tokens(&search),
+            (*selection).clone(),
+        ),
+        |(events, tokens, selection)| {
+            events.as_ref().map(|events| {
+                let events = match selection {
+                    Some(selection) => select_events(events, selection),
+                    None => events.clone(),
+                };
+                if tokens.is_empty() {
+                    events
+                } else {
+                    events
+                        .into_iter()
+                        .filter(|event| event.matches_search(tokens))
+                        .collect::<Vec<_>>()
+                }
+            })
+        },
+    );
 
     let on_data_change = {
         let events_data = events_data.clone();
@@ -20,19 +63,103 @@ pub fn dashboard(props: &DashboardProps) -> Html {
         })
     };
 
+    // The last fetch's parameters are restored next session
+    let on_params_change = {
+        let query_params = query_params.clone();
+        let on_preferences_change = props.on_preferences_change.clone();
+        Callback::from(move |params: AcledParams| {
+            on_preferences_change.emit(PreferencesAction::SetLastParams(params.clone()));
+            query_params.set(Some(params));
+        })
+    };
+
+    let on_map_view_change = {
+        let on_preferences_change = props.on_preferences_change.clone();
+        Callback::from(move |view: MapView| {
+            on_preferences_change.emit(PreferencesAction::SetMapView(view));
+        })
+    };
+
+    let on_basemap_change = {
+        let on_preferences_change = props.on_preferences_change.clone();
+        Callback::from(move |basemap: Basemap| {
+            on_preferences_change.emit(PreferencesAction::SetBasemap(basemap));
+        })
+    };
+
+    let on_selection_change = {
+        let selection = selection.clone();
+        Callback::from(move |value: Option<Selection>| selection.set(value))
+    };
+
+    let on_query_area_change = {
+        let query_area = query_area.clone();
+        Callback::from(move |area: Option<Bounds>| query_area.set(area))
+    };
+
+    let on_search_change = {
+        let search = search.clone();
+        Callback::from(move |value: String| search.set(value))
+    };
+
     html! {
         <div class="dashboard">
             <div class="dashboard-layout">
-                <MapData user_data={props.user_data.clone()} on_data_change={on_data_change} />
+                <MapData
+                    user_data={props.profile.user_data.clone()}
+                    initial_params={props.preferences.last_params.clone()}
+                    on_data_change={on_data_change}
+                    on_params_change={on_params_change}
+                    area={*query_area}
+                    on_area_change={on_query_area_change.clone()}
+                />
 
                 <div class="panel response-panel">
                     <div class="view-content">
                         {match props.current_view {
                             DashboardView::Map => html! {
-                                <MapDisplay events={(*events_data).clone()} />
+                                <MapDisplay
+                                    events={(*matching_events).clone()}
+                                    params={(*query_params).clone()}
+                                    search={(*search).clone()}
+                                    basemap={props.preferences.basemap}
+                                    on_basemap_change={on_basemap_change}
+                                    initial_view={props.preferences.map_view}
+                                    on_view_change={on_map_view_change}
+                                    symbology={props.preferences.symbology.clone()}
+                                    selection={(*selection).clone()}
+                                    on_selection_change={on_selection_change.clone()}
+                                    on_query_area_change={on_query_area_change}
+                                />
                             },
                             DashboardView::DataList => html! {
-                                <ResponseDisplay events={(*events_data).clone()} />
+                                <ResponseDisplay
+                                    events={(*matching_events).clone()}
+                                    total_events={events_data.as_ref().map(Vec::len)}
+                                    params={(*query_params).clone()}
+                                    search={(*search).clone()}
+                                    on_search_change={on_search_change}
+                                    selection={selection.as_ref().map(Selection::describe)}
+                                    on_clear_selection={on_selection_change.reform(|_| None)}
+                                />
+                            },
+                            DashboardView::Table => match (*matching_events).clone() {
+                                Some(events) => html! {
+                                    <EventsTable
+                                        {events}
+                                        params={(*query_params).clone()}
+                                        total_events={events_data.as_ref().map(Vec::len)}
+                                        search={(*search).clone()}
+                                        on_clear_search={on_search_change.reform(|_| String::new())}
+                                        selection={selection.as_ref().map(Selection::describe)}
+                                        on_clear_selection={on_selection_change.reform(|_| None)}
+                                    />
+                                },
+                                None => html! {
+                                    <div class="no-data">
+                                        {"No data loaded. Use the parameters on the left to fetch data."}
+                                    </div>
+                                },
                             },
                         }}
                     </div>
@@ -1,97 +1,203 @@
 use crate::logging::console;
-use crate::services::StorageService;
-use crate::types::{AppState, UserData};
+use crate::services::{AuthService, StorageService, VerificationFailure};
+use crate::types::{AppState, Profile, ProfileStore, UserData};
 use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::spawn_local;
 use web_sys::{HtmlInputElement, MouseEvent};
 use yew::prelude::*;
 
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum AuthMethod {
+    ApiKey,
+    OAuth,
+}
+
 #[derive(Properties, PartialEq)]
 pub struct LoginProps {
+    pub profiles: ProfileStore,
     pub on_state_change: Callback<AppState>,
+    pub on_profiles_change: Callback<ProfileStore>,
+}
+
+// Input handler that stores the value and clears any stale error
+fn text_input(
+    state: &UseStateHandle<String>,
+    error_message: &UseStateHandle<Option<String>>,
+    verification: &UseStateHandle<Option<VerificationFailure>>,
+) -> Callback<InputEvent> {
+    let state = state.clone();
+    let error_message = error_message.clone();
+    let verification = verification.clone();
+    Callback::from(move |e: InputEvent| {
+        let target = e.target().unwrap();
+        let input = target.unchecked_into::<HtmlInputElement>();
+        state.set(input.value());
+        error_message.set(None); // Clear error when user types
+        verification.set(None);
+    })
+}
+
+fn verification_class(failure: &VerificationFailure) -> &'static str {
+    match failure {
+        VerificationFailure::InvalidCredentials(_) => "verify-invalid",
+        VerificationFailure::AccountNotActivated(_) => "verify-inactive",
+        VerificationFailure::RateLimited(_) => "verify-rate-limited",
+        VerificationFailure::Network(_) => "verify-network",
+        VerificationFailure::Other(_) => "verify-other",
+    }
 }
 
 #[function_component(Login)]
 pub fn login(props: &LoginProps) -> Html {
-    let email = use_state(|| String::new());
-    let api_key = use_state(|| String::new());
+    let method = use_state(|| AuthMethod::ApiKey);
+    let email = use_state(String::new);
+    let api_key = use_state(String::new);
+    let username = use_state(String::new);
+    let password = use_state(String::new);
+    let profile_name = use_state(String::new);
+    let submitting = use_state(|| false);
     let error_message = use_state(|| Option::<String>::None);
+    let verification = use_state(|| Option::<VerificationFailure>::None);
 
-    // Try to load saved credentials on component mount
-    {
-        let email = email.clone();
-        let api_key = api_key.clone();
-        use_effect_with((), move |_| {
-            if let Ok(Some(user_data)) = StorageService::load_user_data() {
-                email.set(user_data.email);
-                api_key.set(user_data.api_key);
-            }
-            || {}
-        });
-    }
-
-    let on_email_input = {
-        let email = email.clone();
-        let error_message = error_message.clone();
-        Callback::from(move |e: InputEvent| {
-            let target = e.target().unwrap();
-            let input = target.unchecked_into::<HtmlInputElement>();
-            email.set(input.value());
-            error_message.set(None); // Clear error when user types
+    let open_profile = |profile: &Profile| {
+        let on_state_change = props.on_state_change.clone();
+        let profile = profile.clone();
+        Callback::from(move |_: MouseEvent| {
+            console::log_user_action!("Opened profile {}", profile.name);
+            on_state_change.emit(AppState::Dashboard(profile.clone()));
         })
     };
 
-    let on_api_key_input = {
-        let api_key = api_key.clone();
+    let select_method = |new_method: AuthMethod| {
+        let method = method.clone();
         let error_message = error_message.clone();
-        Callback::from(move |e: InputEvent| {
-            let target = e.target().unwrap();
-            let input = target.unchecked_into::<HtmlInputElement>();
-            api_key.set(input.value());
-            error_message.set(None); // Clear error when user types
+        let verification = verification.clone();
+        Callback::from(move |_: MouseEvent| {
+            method.set(new_method);
+            error_message.set(None);
+            verification.set(None);
         })
     };
 
     let on_submit = {
         let on_state_change = props.on_state_change.clone();
+        let on_profiles_change = props.on_profiles_change.clone();
+        let method = method.clone();
         let email = email.clone();
         let api_key = api_key.clone();
+        let username = username.clone();
+        let password = password.clone();
+        let profile_name = profile_name.clone();
+        let submitting = submitting.clone();
         let error_message = error_message.clone();
+        let verification = verification.clone();
 
         Callback::from(move |_: MouseEvent| {
-            console::log_user_action!("Submit button clicked");
+            console::log_user_action!("Submit button clicked ({:?})", *method);
+
+            let method = *method;
             let email_value = (*email).clone();
             let api_key_value = (*api_key).clone();
+            let username_value = username.trim().to_string();
+            let password_value = (*password).clone();
+            let profile_name_value = profile_name.trim().to_string();
 
-            console::log!(
-                "Form submitted with email: {} and API key length: {}",
-                email_value,
-                api_key_value.len()
-            );
+            match method {
+                AuthMethod::ApiKey if email_value.is_empty() || api_key_value.is_empty() => {
+                    error_message.set(Some("Please provide both email and API key.".to_string()));
+                    console::warn!("Form validation failed - missing email or API key");
+                    return;
+                }
+                AuthMethod::OAuth if username_value.is_empty() || password_value.is_empty() => {
+                    error_message.set(Some(
+                        "Please provide both username and password.".to_string(),
+                    ));
+                    console::warn!("Form validation failed - missing username or password");
+                    return;
+                }
+                _ => {}
+            }
 
-            // Create and validate user data
-            let user_data = UserData::new(email_value, api_key_value);
+            submitting.set(true);
+            verification.set(None);
+            let on_state_change = on_state_change.clone();
+            let on_profiles_change = on_profiles_change.clone();
+            let password = password.clone();
+            let submitting = submitting.clone();
+            let verification = verification.clone();
+            spawn_local(async move {
+                let result = match method {
+                    AuthMethod::ApiKey => Ok(UserData::new(email_value, api_key_value)),
+                    AuthMethod::OAuth => AuthService::login(&username_value, &password_value)
+                        .await
+                        .map_err(VerificationFailure::from_error),
+                };
 
-            if !user_data.is_valid() {
-                error_message.set(Some("Please provide both email and API key.".to_string()));
-                console::warn!("Form validation failed - missing email or API key");
-                return;
-            }
+                // Probe the API so bad credentials are caught here rather than
+                // on the first fetch
+                let verified = match result {
+                    Ok(user_data) => AuthService::verify(&user_data).await.map(|_| user_data),
+                    Err(failure) => Err(failure),
+                };
+                let user_data = match verified {
+                    Ok(user_data) => user_data,
+                    Err(failure) => {
+                        console::warn_with_context!(
+                            "LOGIN",
+                            "Credential verification failed: {:?}",
+                            failure
+                        );
+                        verification.set(Some(failure));
+                        submitting.set(false);
+                        return;
+                    }
+                };
+                // The password is only needed for the token exchange
+                password.set(String::new());
 
-            console::log!("Creating user data");
+                // Unnamed profiles are named after the account
+                let profile = Profile {
+                    name: if profile_name_value.is_empty() {
+                        user_data.account().to_string()
+                    } else {
+                        profile_name_value
+                    },
+                    user_data,
+                };
 
-            // Try to save user data
-            if let Err(e) = StorageService::save_user_data(&user_data) {
-                console::error_with_context!("LOGIN", "Failed to save credentials: {}", e);
-                // Continue anyway, don't block login
-            } else {
-                console::log_with_context!("LOGIN", "User credentials saved successfully");
-            }
+                // Try to save the profile
+                let saved = profile.clone();
+                match StorageService::update_profiles(move |store| {
+                    store.add(saved);
+                    Ok(())
+                })
+                .await
+                {
+                    Ok(store) => {
+                        console::log_with_context!("LOGIN", "Profile {} saved", profile.name);
+                        on_profiles_change.emit(store);
+                    }
+                    // Continue anyway, don't block login
+                    Err(e) => {
+                        console::error_with_context!("LOGIN", "Failed to save credentials: {}", e)
+                    }
+                }
 
-            // Transition to dashboard
-            on_state_change.emit(AppState::Dashboard(user_data));
+                submitting.set(false);
+                // Transition to dashboard
+                on_state_change.emit(AppState::Dashboard(profile));
+            });
         })
     };
 
+    let method_class = |button_method: AuthMethod| {
+        if *method == button_method {
+            "tab-button active"
+        } else {
+            "tab-button"
+        }
+    };
+
     html! {
         <>
             <h1 id="title">{"Arson"}</h1>
@@ -107,31 +213,121 @@ pub fn login(props: &LoginProps) -> Html {
                     html! {}
                 }}
 
-                <div class="input-group">
-                    <label for="email">{"Email Address:"}</label>
-                    <input
-                        type="email"
-                        id="email"
-                        class="input"
-                        placeholder="Enter your email"
-                        value={(*email).clone()}
-                        oninput={on_email_input}
-                    />
+                if let Some(failure) = (*verification).as_ref() {
+                    <div class={classes!("verification-error", verification_class(failure))}>
+                        <strong>{failure.title()}</strong>
+                        <p>{failure.hint()}</p>
+                        <p class="verification-detail">{failure.detail()}</p>
+                    </div>
+                }
+
+                if !props.profiles.is_empty() {
+                    <div class="saved-profiles">
+                        <h3>{"Saved Profiles"}</h3>
+                        {for props.profiles.profiles.iter().map(|profile| html! {
+                            <button class="saved-profile" onclick={open_profile(profile)}>
+                                <span class="saved-profile-name">
+                                    {&profile.name}
+                                    if props.profiles.is_default(&profile.name) {
+                                        <span class="profile-badge">{"default"}</span>
+                                    }
+                                </span>
+                                <span class="saved-profile-account">
+                                    {format!("{} · {}", profile.user_data.account(), profile.user_data.method_label())}
+                                </span>
+                            </button>
+                        })}
+                        <p class="saved-profiles-note">{"Or add another profile:"}</p>
+                    </div>
+                }
+
+                <div class="auth-method-tabs">
+                    <button
+                        class={method_class(AuthMethod::ApiKey)}
+                        onclick={select_method(AuthMethod::ApiKey)}
+                    >
+                        {"API Key"}
+                    </button>
+                    <button
+                        class={method_class(AuthMethod::OAuth)}
+                        onclick={select_method(AuthMethod::OAuth)}
+                    >
+                        {"OAuth"}
+                    </button>
                 </div>
 
+                {match *method {
+                    AuthMethod::ApiKey => html! {
+                        <>
+                            <div class="input-group">
+                                <label for="email">{"Email Address:"}</label>
+                                <input
+                                    type="email"
+                                    id="email"
+                                    class="input"
+                                    placeholder="Enter your email"
+                                    value={(*email).clone()}
+                                    oninput={text_input(&email, &error_message, &verification)}
+                                />
+                            </div>
+
+                            <div class="input-group">
+                                <label for="api-key">{"ACLED API Key:"}</label>
+                                <input
+                                    type="password"
+                                    id="api-key"
+                                    class="input"
+                                    placeholder="Enter your ACLED API key"
+                                    value={(*api_key).clone()}
+                                    oninput={text_input(&api_key, &error_message, &verification)}
+                                />
+                            </div>
+                        </>
+                    },
+                    AuthMethod::OAuth => html! {
+                        <>
+                            <div class="input-group">
+                                <label for="username">{"ACLED Username:"}</label>
+                                <input
+                                    type="email"
+                                    id="username"
+                                    class="input"
+                                    placeholder="Enter your ACLED account email"
+                                    value={(*username).clone()}
+                                    oninput={text_input(&username, &error_message, &verification)}
+                                />
+                            </div>
+
+                            <div class="input-group">
+                                <label for="password">{"Password:"}</label>
+                                <input
+                                    type="password"
+                                    id="password"
+                                    class="input"
+                                    placeholder="Enter your ACLED password"
+                                    value={(*password).clone()}
+                                    oninput={text_input(&password, &error_message, &verification)}
+                                />
+                            </div>
+                        </>
+                    },
+                }}
+
                 <div class="input-group">
-                    <label for="api-key">{"ACLED API Key:"}</label>
+                    <label for="profile-name">{"Profile Name:"}</label>
                     <input
-                        type="password"
-                        id="api-key"
+                        type="text"
+                        id="profile-name"
                         class="input"
-                        placeholder="Enter your ACLED API key"
-                        value={(*api_key).clone()}
-                        oninput={on_api_key_input}
+                        placeholder="Optional; defaults to the account"
+                        value={(*profile_name).clone()}
+                        oninput={text_input(&profile_name, &error_message, &verification)}
                     />
                 </div>
 
-                <button class="button" onclick={on_submit}>{"Initialize"}</button>
+                <button class="button" onclick={on_submit} disabled={*submitting}>
+                    {if *submitting { "Verifying..." } else { "Initialize" }}
+                </button>
             </div>
         </>
     }
@@ -1,12 +1,22 @@
+use super::lenient;
+use crate::geo::Bounds;
+use crate::utils::text;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
 pub struct AcledParams {
     pub start_date: String,
     pub end_date: String,
-    pub country: String,
-    pub event_type: String,
+    pub countries: Vec<String>,
+    /// An empty list requests every event type.
+    pub event_types: Vec<String>,
     pub limit: u32,
+    /// Walk ACLED's `page` parameter until exhaustion, treating `limit` as the page size.
+    #[serde(default)]
+    pub fetch_all_pages: bool,
+    /// Only fetch events inside this box, via ACLED's latitude/longitude filters.
+    #[serde(default)]
+    pub area: Option<Bounds>,
 }
 
 impl Default for AcledParams {
@@ -14,9 +24,11 @@ impl Default for AcledParams {
         Self {
             start_date: "2024-01-01".to_string(),
             end_date: "2024-12-31".to_string(),
-            country: "Lebanon".to_string(),
-            event_type: "Battles".to_string(),
+            countries: vec!["Lebanon".to_string()],
+            event_types: vec!["Battles".to_string()],
             limit: 50,
+            fetch_all_pages: false,
+            area: None,
         }
     }
 }
@@ -25,34 +37,157 @@ impl AcledParams {
     pub fn is_valid(&self) -> bool {
         !self.start_date.is_empty()
             && !self.end_date.is_empty()
-            && !self.country.is_empty()
+            && !self.countries.is_empty()
             && self.limit > 0
             && self.limit <= 5000
     }
 
     pub fn to_query_params(&self) -> Vec<(String, String)> {
-        vec![
+        let mut query = vec![
             ("start".to_string(), self.start_date.clone()),
             ("end".to_string(), self.end_date.clone()),
-            ("country".to_string(), self.country.clone()),
-            ("event_type".to_string(), self.event_type.clone()),
-            ("limit".to_string(), self.limit.to_string()),
+        ];
+
+        if let Some(countries) = Self::or_filter("country", &self.countries) {
+            query.push(("country".to_string(), countries));
+        }
+
+        if let Some(event_types) = Self::or_filter("event_type", &self.event_types) {
+            query.push(("event_type".to_string(), event_types));
+        }
+
+        if let Some(area) = &self.area {
+            query.extend(Self::between("latitude", area.south, area.north));
+            query.extend(Self::between("longitude", area.west, area.east));
+        }
+
+        query.push(("limit".to_string(), self.limit.to_string()));
+        query
+    }
+
+    // ACLED ranges take both ends in one value, `field=low|high&field_where=BETWEEN`
+    fn between(field: &str, low: f64, high: f64) -> [(String, String); 2] {
+        [
+            (field.to_string(), format!("{:.4}|{:.4}", low, high)),
+            (format!("{}_where", field), "BETWEEN".to_string()),
         ]
     }
+
+    // ACLED ORs repeated values of one field as `field=a:OR:field=b`; the leading
+    // `field=` is supplied by the query pair itself.
+    fn or_filter(field: &str, values: &[String]) -> Option<String> {
+        let mut values = values.iter().filter(|value| !value.is_empty());
+        let first = values.next()?.clone();
+
+        Some(values.fold(first, |acc, value| {
+            format!("{}:OR:{}={}", acc, field, value)
+        }))
+    }
+
+    pub fn to_page_query_params(&self, page: u32) -> Vec<(String, String)> {
+        let mut query = self.to_query_params();
+        query.push(("page".to_string(), page.to_string()));
+        query
+    }
 }
 
-#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+/// Emitted after each page of a paginated fetch.
+#[derive(Clone, PartialEq, Debug)]
+pub struct FetchProgress {
+    pub page: u32,
+    /// Events of this page only; earlier pages were emitted before.
+    pub page_events: Vec<AcledEvent>,
+    /// Events fetched so far, including this page.
+    pub total_events: usize,
+}
+
+/// Everything a paginated fetch returned.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PagedEvents {
+    pub events: Vec<AcledEvent>,
+    /// The fetch stopped at `MAX_FETCH_PAGES` with pages still left.
+    pub truncated: bool,
+}
+
+/// A failed request that is about to be retried.
+#[derive(Clone, PartialEq, Debug)]
+pub struct RetryStatus {
+    /// 1-based number of the upcoming attempt.
+    pub attempt: u32,
+    pub max_attempts: u32,
+    pub delay_ms: u32,
+    pub reason: String,
+}
+
+/// A single ACLED event row. ACLED encodes most numbers as strings, so numeric
+/// fields go through the lenient deserializers and every field tolerates absence.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+#[serde(default)]
 pub struct AcledEvent {
+    #[serde(deserialize_with = "lenient::number")]
     pub data_id: u32,
+    #[serde(deserialize_with = "lenient::string")]
+    pub event_id_cnty: String,
+    #[serde(deserialize_with = "lenient::string")]
     pub event_date: String,
+    #[serde(deserialize_with = "lenient::opt_number")]
+    pub year: Option<u32>,
+    #[serde(deserialize_with = "lenient::opt_number")]
+    pub time_precision: Option<u8>,
+    #[serde(deserialize_with = "lenient::string")]
+    pub disorder_type: String,
+    #[serde(deserialize_with = "lenient::string")]
     pub event_type: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub sub_event_type: String,
+    #[serde(deserialize_with = "lenient::string")]
     pub actor1: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub assoc_actor_1: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub inter1: String,
+    #[serde(deserialize_with = "lenient::string")]
     pub actor2: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub assoc_actor_2: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub inter2: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub interaction: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub civilian_targeting: String,
+    #[serde(deserialize_with = "lenient::opt_number")]
+    pub iso: Option<u32>,
+    #[serde(deserialize_with = "lenient::string")]
+    pub region: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub country: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub admin1: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub admin2: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub admin3: String,
+    #[serde(deserialize_with = "lenient::string")]
     pub location: String,
+    #[serde(deserialize_with = "lenient::opt_number")]
     pub latitude: Option<f64>,
+    #[serde(deserialize_with = "lenient::opt_number")]
     pub longitude: Option<f64>,
+    #[serde(deserialize_with = "lenient::opt_number")]
+    pub geo_precision: Option<u8>,
+    #[serde(deserialize_with = "lenient::string")]
+    pub source: String,
+    #[serde(deserialize_with = "lenient::string")]
+    pub source_scale: String,
+    #[serde(deserialize_with = "lenient::string")]
     pub notes: String,
+    #[serde(deserialize_with = "lenient::opt_number")]
     pub fatalities: Option<u32>,
+    #[serde(deserialize_with = "lenient::string")]
+    pub tags: String,
+    #[serde(deserialize_with = "lenient::opt_number")]
+    pub timestamp: Option<u64>,
 }
 
 impl AcledEvent {
@@ -61,7 +196,6 @@ impl AcledEvent {
         self.latitude.is_some() && self.longitude.is_some()
     }
 
-    #[allow(dead_code)]
     pub fn coordinates(&self) -> Option<(f64, f64)> {
         match (self.latitude, self.longitude) {
             (Some(lat), Some(lng)) => Some((lat, lng)),
@@ -69,10 +203,70 @@ impl AcledEvent {
         }
     }
 
-    #[allow(dead_code)]
     pub fn fatality_count(&self) -> u32 {
         self.fatalities.unwrap_or(0)
     }
+
+    pub fn targets_civilians(&self) -> bool {
+        !self.civilian_targeting.is_empty()
+    }
+
+    /// Location followed by its admin hierarchy, skipping empty levels.
+    pub fn full_location(&self) -> String {
+        [
+            &self.location,
+            &self.admin3,
+            &self.admin2,
+            &self.admin1,
+            &self.country,
+        ]
+        .iter()
+        .filter(|part| !part.is_empty())
+        .fold(Vec::<&str>::new(), |mut parts, part| {
+            // ACLED often repeats the location as its admin3/admin2 name
+            if parts.last() != Some(&part.as_str()) {
+                parts.push(part.as_str());
+            }
+            parts
+        })
+        .join(", ")
+    }
+
+    pub fn geo_precision_label(&self) -> Option<&'static str> {
+        match self.geo_precision? {
+            1 => Some("Exact location"),
+            2 => Some("Near location / part of region"),
+            3 => Some("Provincial capital / region"),
+            _ => None,
+        }
+    }
+
+    pub fn time_precision_label(&self) -> Option<&'static str> {
+        match self.time_precision? {
+            1 => Some("Exact date"),
+            2 => Some("Within the week"),
+            3 => Some("Within the month"),
+            _ => None,
+        }
+    }
+
+    pub fn tag_list(&self) -> Vec<&str> {
+        self.tags
+            .split(';')
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+            .collect()
+    }
+
+    /// Whether every search token (see [`text::search_tokens`]) appears in
+    /// the notes, either actor, or the location name.
+    pub fn matches_search(&self, tokens: &[String]) -> bool {
+        let fields = [&self.notes, &self.actor1, &self.actor2, &self.location]
+            .map(|field| text::fold(field));
+        tokens
+            .iter()
+            .all(|token| fields.iter().any(|field| field.contains(token.as_str())))
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
@@ -84,9 +278,11 @@ pub struct AcledError {
 #[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
 pub struct AcledResponse {
     pub success: Option<bool>,
+    #[serde(default, deserialize_with = "lenient::opt_number")]
     pub count: Option<u32>,
     pub data: Option<Vec<AcledEvent>>,
     pub pagination: Option<serde_json::Value>,
+    #[serde(default, deserialize_with = "lenient::opt_number")]
     pub status: Option<u32>,
     pub error: Option<AcledError>,
     pub message: Option<String>,
@@ -101,6 +297,14 @@ impl AcledResponse {
         self.data.clone().unwrap_or_default()
     }
 
+    /// Status from the error payload, which ACLED may send with HTTP 200.
+    pub fn get_error_status(&self) -> Option<u32> {
+        self.error
+            .as_ref()
+            .map(|error| error.status)
+            .or(self.status)
+    }
+
     pub fn get_error_message(&self) -> Option<String> {
         if let Some(error) = &self.error {
             Some(error.message.clone())
@@ -108,4 +312,97 @@ impl AcledResponse {
             self.message.clone()
         }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(countries: &[&str], event_types: &[&str]) -> AcledParams {
+        AcledParams {
+            start_date: "2024-03-01".to_string(),
+            end_date: "2024-03-31".to_string(),
+            countries: countries.iter().map(|c| c.to_string()).collect(),
+            event_types: event_types.iter().map(|t| t.to_string()).collect(),
+            limit: 500,
+            ..Default::default()
+        }
+    }
+
+    fn pairs(query: &[(&str, &str)]) -> Vec<(String, String)> {
+        query
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn single_country_and_every_event_type() {
+        assert_eq!(
+            params(&["Sudan"], &[]).to_query_params(),
+            pairs(&[
+                ("start", "2024-03-01"),
+                ("end", "2024-03-31"),
+                ("country", "Sudan"),
+                ("limit", "500"),
+            ])
+        );
+    }
+
+    #[test]
+    fn several_values_are_joined_with_or() {
+        let query =
+            params(&["Sudan", "South Sudan", "", "Chad"], &["Battles", "Riots"]).to_query_params();
+
+        assert_eq!(
+            query,
+            pairs(&[
+                ("start", "2024-03-01"),
+                ("end", "2024-03-31"),
+                ("country", "Sudan:OR:country=South Sudan:OR:country=Chad"),
+                ("event_type", "Battles:OR:event_type=Riots"),
+                ("limit", "500"),
+            ])
+        );
+    }
+
+    #[test]
+    fn single_event_type_has_no_or() {
+        let query = params(&["Sudan"], &["Protests"]).to_query_params();
+        assert!(query.contains(&("event_type".to_string(), "Protests".to_string())));
+    }
+
+    #[test]
+    fn area_becomes_between_ranges() {
+        let mut params = params(&["Sudan"], &[]);
+        params.area = Some(Bounds {
+            south: 8.5,
+            west: 21.8,
+            north: 15.123_456,
+            east: 38.6,
+        });
+
+        assert_eq!(
+            params.to_query_params(),
+            pairs(&[
+                ("start", "2024-03-01"),
+                ("end", "2024-03-31"),
+                ("country", "Sudan"),
+                ("latitude", "8.5000|15.1235"),
+                ("latitude_where", "BETWEEN"),
+                ("longitude", "21.8000|38.6000"),
+                ("longitude_where", "BETWEEN"),
+                ("limit", "500"),
+            ])
+        );
+    }
+
+    #[test]
+    fn pages_follow_the_base_query() {
+        let params = params(&["Sudan"], &[]);
+        let query = params.to_page_query_params(3);
+
+        assert_eq!(query[..query.len() - 1], params.to_query_params()[..]);
+        assert_eq!(query.last(), Some(&("page".to_string(), "3".to_string())));
+    }
 } 
\ No newline at end of file
@@ -1,33 +1,114 @@
-use super::UserData;
+use super::Profile;
+use crate::config::{
+    DARK_ATTRIBUTION, DARK_TILE_URL, MAP_ATTRIBUTION, MAP_TILE_URL, SATELLITE_ATTRIBUTION,
+    SATELLITE_TILE_URL, TERRAIN_ATTRIBUTION, TERRAIN_TILE_URL,
+};
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, PartialEq, Debug)]
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Theme {
+    #[default]
     Light,
     Blue,
     Dark,
     Terminal,
 }
 
-impl Default for Theme {
-    fn default() -> Self {
-        Self::Light
-    }
-}
-
 #[derive(Clone, PartialEq, Debug)]
 pub enum AppState {
     Login,
-    Dashboard(UserData),
+    Dashboard(Profile),
 }
 
-#[derive(Clone, PartialEq, Debug)]
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum DashboardView {
     Map,
+    #[default]
     DataList,
+    Table,
 }
 
-impl Default for DashboardView {
-    fn default() -> Self {
-        Self::DataList
+impl DashboardView {
+    pub const ALL: [DashboardView; 3] = [
+        DashboardView::DataList,
+        DashboardView::Table,
+        DashboardView::Map,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            DashboardView::Map => "Map View",
+            DashboardView::DataList => "Data View",
+            DashboardView::Table => "Table View",
+        }
+    }
+}
+
+/// Tile layer drawn beneath the events on the map.
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Basemap {
+    #[default]
+    Satellite,
+    Streets,
+    Terrain,
+    Dark,
+    /// No tiles, for exports and when nothing else loads.
+    Blank,
+    /// A local MBTiles file or tile directory served by the desktop backend.
+    Offline,
+}
+
+impl Basemap {
+    pub const ALL: [Basemap; 6] = [
+        Basemap::Satellite,
+        Basemap::Streets,
+        Basemap::Terrain,
+        Basemap::Dark,
+        Basemap::Blank,
+        Basemap::Offline,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Basemap::Satellite => "Satellite",
+            Basemap::Streets => "Streets",
+            Basemap::Terrain => "Terrain",
+            Basemap::Dark => "Dark",
+            Basemap::Blank => "Blank",
+            Basemap::Offline => "Offline",
+        }
+    }
+
+    /// URL template of the online tile servers; the blank and offline
+    /// basemaps have none.
+    pub fn tile_url(self) -> Option<&'static str> {
+        match self {
+            Basemap::Satellite => Some(SATELLITE_TILE_URL),
+            Basemap::Streets => Some(MAP_TILE_URL),
+            Basemap::Terrain => Some(TERRAIN_TILE_URL),
+            Basemap::Dark => Some(DARK_TILE_URL),
+            Basemap::Blank | Basemap::Offline => None,
+        }
+    }
+
+    pub fn attribution(self) -> &'static str {
+        match self {
+            Basemap::Satellite => SATELLITE_ATTRIBUTION,
+            Basemap::Streets => MAP_ATTRIBUTION,
+            Basemap::Terrain => TERRAIN_ATTRIBUTION,
+            Basemap::Dark => DARK_ATTRIBUTION,
+            Basemap::Blank | Basemap::Offline => "",
+        }
+    }
+
+    pub fn max_zoom(self) -> u32 {
+        match self {
+            Basemap::Terrain => 17,
+            Basemap::Dark => 20,
+            _ => 18,
+        }
     }
 }
@@ -0,0 +1,227 @@
+use super::AcledEvent;
+
+/// How a field's values compare and filter.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FieldKind {
+    Text,
+    Number,
+    Date,
+}
+
+/// Every column of [`AcledEvent`], in ACLED's export order.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+pub enum EventField {
+    EventIdCnty,
+    EventDate,
+    Year,
+    TimePrecision,
+    DisorderType,
+    EventType,
+    SubEventType,
+    Actor1,
+    AssocActor1,
+    Inter1,
+    Actor2,
+    AssocActor2,
+    Inter2,
+    Interaction,
+    CivilianTargeting,
+    Iso,
+    Region,
+    Country,
+    Admin1,
+    Admin2,
+    Admin3,
+    Location,
+    Latitude,
+    Longitude,
+    GeoPrecision,
+    Source,
+    SourceScale,
+    Notes,
+    Fatalities,
+    Tags,
+    Timestamp,
+    DataId,
+}
+
+impl EventField {
+    pub const ALL: [EventField; 32] = [
+        EventField::EventIdCnty,
+        EventField::EventDate,
+        EventField::Year,
+        EventField::TimePrecision,
+        EventField::DisorderType,
+        EventField::EventType,
+        EventField::SubEventType,
+        EventField::Actor1,
+        EventField::AssocActor1,
+        EventField::Inter1,
+        EventField::Actor2,
+        EventField::AssocActor2,
+        EventField::Inter2,
+        EventField::Interaction,
+        EventField::CivilianTargeting,
+        EventField::Iso,
+        EventField::Region,
+        EventField::Country,
+        EventField::Admin1,
+        EventField::Admin2,
+        EventField::Admin3,
+        EventField::Location,
+        EventField::Latitude,
+        EventField::Longitude,
+        EventField::GeoPrecision,
+        EventField::Source,
+        EventField::SourceScale,
+        EventField::Notes,
+        EventField::Fatalities,
+        EventField::Tags,
+        EventField::Timestamp,
+        EventField::DataId,
+    ];
+
+    /// ACLED's column name.
+    pub fn key(&self) -> &'static str {
+        match self {
+            EventField::EventIdCnty => "event_id_cnty",
+            EventField::EventDate => "event_date",
+            EventField::Year => "year",
+            EventField::TimePrecision => "time_precision",
+            EventField::DisorderType => "disorder_type",
+            EventField::EventType => "event_type",
+            EventField::SubEventType => "sub_event_type",
+            EventField::Actor1 => "actor1",
+            EventField::AssocActor1 => "assoc_actor_1",
+            EventField::Inter1 => "inter1",
+            EventField::Actor2 => "actor2",
+            EventField::AssocActor2 => "assoc_actor_2",
+            EventField::Inter2 => "inter2",
+            EventField::Interaction => "interaction",
+            EventField::CivilianTargeting => "civilian_targeting",
+            EventField::Iso => "iso",
+            EventField::Region => "region",
+            EventField::Country => "country",
+            EventField::Admin1 => "admin1",
+            EventField::Admin2 => "admin2",
+            EventField::Admin3 => "admin3",
+            EventField::Location => "location",
+            EventField::Latitude => "latitude",
+            EventField::Longitude => "longitude",
+            EventField::GeoPrecision => "geo_precision",
+            EventField::Source => "source",
+            EventField::SourceScale => "source_scale",
+            EventField::Notes => "notes",
+            EventField::Fatalities => "fatalities",
+            EventField::Tags => "tags",
+            EventField::Timestamp => "timestamp",
+            EventField::DataId => "data_id",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            EventField::EventIdCnty => "Event ID",
+            EventField::EventDate => "Date",
+            EventField::Year => "Year",
+            EventField::TimePrecision => "Time Precision",
+            EventField::DisorderType => "Disorder Type",
+            EventField::EventType => "Event Type",
+            EventField::SubEventType => "Sub-Event Type",
+            EventField::Actor1 => "Actor 1",
+            EventField::AssocActor1 => "Assoc. Actor 1",
+            EventField::Inter1 => "Inter 1",
+            EventField::Actor2 => "Actor 2",
+            EventField::AssocActor2 => "Assoc. Actor 2",
+            EventField::Inter2 => "Inter 2",
+            EventField::Interaction => "Interaction",
+            EventField::CivilianTargeting => "Civilian Targeting",
+            EventField::Iso => "ISO",
+            EventField::Region => "Region",
+            EventField::Country => "Country",
+            EventField::Admin1 => "Admin 1",
+            EventField::Admin2 => "Admin 2",
+            EventField::Admin3 => "Admin 3",
+            EventField::Location => "Location",
+            EventField::Latitude => "Latitude",
+            EventField::Longitude => "Longitude",
+            EventField::GeoPrecision => "Geo Precision",
+            EventField::Source => "Source",
+            EventField::SourceScale => "Source Scale",
+            EventField::Notes => "Notes",
+            EventField::Fatalities => "Fatalities",
+            EventField::Tags => "Tags",
+            EventField::Timestamp => "Timestamp",
+            EventField::DataId => "Data ID",
+        }
+    }
+
+    pub fn kind(&self) -> FieldKind {
+        match self {
+            EventField::EventDate => FieldKind::Date,
+            EventField::Year
+            | EventField::TimePrecision
+            | EventField::Iso
+            | EventField::Latitude
+            | EventField::Longitude
+            | EventField::GeoPrecision
+            | EventField::Fatalities
+            | EventField::Timestamp
+            | EventField::DataId => FieldKind::Number,
+            _ => FieldKind::Text,
+        }
+    }
+
+    /// Numeric value for [`FieldKind::Number`] fields.
+    pub fn number(&self, event: &AcledEvent) -> Option<f64> {
+        match self {
+            EventField::Year => event.year.map(f64::from),
+            EventField::TimePrecision => event.time_precision.map(f64::from),
+            EventField::Iso => event.iso.map(f64::from),
+            EventField::Latitude => event.latitude,
+            EventField::Longitude => event.longitude,
+            EventField::GeoPrecision => event.geo_precision.map(f64::from),
+            EventField::Fatalities => event.fatalities.map(f64::from),
+            EventField::Timestamp => event.timestamp.map(|timestamp| timestamp as f64),
+            EventField::DataId => Some(f64::from(event.data_id)),
+            _ => None,
+        }
+    }
+
+    /// Display/export text; missing numbers render as an empty string.
+    pub fn text(&self, event: &AcledEvent) -> String {
+        let text = match self {
+            EventField::EventIdCnty => &event.event_id_cnty,
+            EventField::EventDate => &event.event_date,
+            EventField::DisorderType => &event.disorder_type,
+            EventField::EventType => &event.event_type,
+            EventField::SubEventType => &event.sub_event_type,
+            EventField::Actor1 => &event.actor1,
+            EventField::AssocActor1 => &event.assoc_actor_1,
+            EventField::Inter1 => &event.inter1,
+            EventField::Actor2 => &event.actor2,
+            EventField::AssocActor2 => &event.assoc_actor_2,
+            EventField::Inter2 => &event.inter2,
+            EventField::Interaction => &event.interaction,
+            EventField::CivilianTargeting => &event.civilian_targeting,
+            EventField::Region => &event.region,
+            EventField::Country => &event.country,
+            EventField::Admin1 => &event.admin1,
+            EventField::Admin2 => &event.admin2,
+            EventField::Admin3 => &event.admin3,
+            EventField::Location => &event.location,
+            EventField::Source => &event.source,
+            EventField::SourceScale => &event.source_scale,
+            EventField::Notes => &event.notes,
+            EventField::Tags => &event.tags,
+            numeric => {
+                return numeric
+                    .number(event)
+                    .map(|value| value.to_string())
+                    .unwrap_or_default()
+            }
+        };
+
+        text.clone()
+    }
+}
@@ -0,0 +1,183 @@
+//! Deserializers for ACLED fields that arrive as either JSON numbers or
+//! string-encoded numbers (`"fatalities": "3"`), and occasionally as `""`,
+//! and for stored settings that should survive a single malformed field.
+
+use serde::de::{Deserialize, DeserializeOwned, Deserializer, Error};
+use serde_json::Value;
+
+fn number_from_value<T: std::str::FromStr>(value: Value) -> Result<Option<T>, String> {
+    let text = match value {
+        Value::Null => return Ok(None),
+        Value::Number(number) => number.to_string(),
+        Value::String(text) => text,
+        other => return Err(format!("expected a number, found {}", other)),
+    };
+
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+
+    // Integers are sometimes serialized as floats ("12.0")
+    if let Ok(parsed) = text.parse::<T>() {
+        return Ok(Some(parsed));
+    }
+
+    match text.parse::<f64>() {
+        Ok(float) if float.fract() == 0.0 => format!("{}", float as i64)
+            .parse::<T>()
+            .map(Some)
+            .map_err(|_| format!("invalid number: {}", text)),
+        _ => Err(format!("invalid number: {}", text)),
+    }
+}
+
+pub fn opt_number<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: std::str::FromStr,
+{
+    let value = Value::deserialize(deserializer)?;
+    number_from_value(value).map_err(D::Error::custom)
+}
+
+pub fn number<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: std::str::FromStr,
+{
+    opt_number(deserializer)?.ok_or_else(|| D::Error::custom("missing number"))
+}
+
+/// Accepts strings, numbers and nulls, always yielding a `String`.
+pub fn string<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Value::deserialize(deserializer)? {
+        Value::Null => Ok(String::new()),
+        Value::String(text) => Ok(text),
+        Value::Number(number) => Ok(number.to_string()),
+        Value::Bool(flag) => Ok(flag.to_string()),
+        other => Err(D::Error::custom(format!(
+            "expected a string, found {}",
+            other
+        ))),
+    }
+}
+
+/// Falls back to `T::default()` when the value doesn't fit `T`, such as a
+/// hand-edited or truncated field, instead of failing the whole document.
+pub fn or_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned + Default,
+{
+    let value = Value::deserialize(deserializer)?;
+    Ok(T::deserialize(value).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Deserialize, Debug)]
+    struct Row {
+        #[serde(default, deserialize_with = "opt_number")]
+        fatalities: Option<u32>,
+        #[serde(default, deserialize_with = "opt_number")]
+        latitude: Option<f64>,
+        #[serde(default, deserialize_with = "string")]
+        iso: String,
+    }
+
+    fn row(value: Value) -> Result<Row, serde_json::Error> {
+        serde_json::from_value(value)
+    }
+
+    #[test]
+    fn numbers_accept_json_numbers_and_strings() {
+        let parsed = row(json!({ "fatalities": 3, "latitude": "12.5", "iso": "566" })).unwrap();
+        assert_eq!(parsed.fatalities, Some(3));
+        assert_eq!(parsed.latitude, Some(12.5));
+
+        let parsed = row(json!({ "fatalities": " 7 ", "latitude": -4 })).unwrap();
+        assert_eq!(parsed.fatalities, Some(7));
+        assert_eq!(parsed.latitude, Some(-4.0));
+    }
+
+    #[test]
+    fn integers_accept_whole_floats_only() {
+        assert_eq!(
+            row(json!({ "fatalities": "12.0" })).unwrap().fatalities,
+            Some(12)
+        );
+        assert_eq!(
+            row(json!({ "fatalities": 12.0 })).unwrap().fatalities,
+            Some(12)
+        );
+        assert!(row(json!({ "fatalities": "12.5" })).is_err());
+        assert!(row(json!({ "fatalities": "-1" })).is_err());
+    }
+
+    #[test]
+    fn blanks_and_nulls_are_missing() {
+        let parsed = row(json!({ "fatalities": "", "latitude": null, "iso": null })).unwrap();
+        assert_eq!(parsed.fatalities, None);
+        assert_eq!(parsed.latitude, None);
+        assert_eq!(parsed.iso, "");
+
+        assert_eq!(row(json!({ "fatalities": "  " })).unwrap().fatalities, None);
+    }
+
+    #[test]
+    fn numbers_reject_garbage() {
+        let error = row(json!({ "fatalities": "three" })).unwrap_err();
+        assert!(error.to_string().contains("invalid number: three"));
+        assert!(row(json!({ "fatalities": true })).is_err());
+        assert!(row(json!({ "latitude": [1.0] })).is_err());
+    }
+
+    #[test]
+    fn required_numbers_must_be_present() {
+        #[derive(Deserialize)]
+        struct Required {
+            #[serde(deserialize_with = "number")]
+            count: u32,
+        }
+
+        let parsed: Required = serde_json::from_value(json!({ "count": "42" })).unwrap();
+        assert_eq!(parsed.count, 42);
+
+        let error = serde_json::from_value::<Required>(json!({ "count": "" }))
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("missing number"));
+    }
+
+    #[test]
+    fn strings_accept_scalars() {
+        assert_eq!(row(json!({ "iso": 566 })).unwrap().iso, "566");
+        assert_eq!(row(json!({ "iso": false })).unwrap().iso, "false");
+        assert_eq!(row(json!({ "iso": "NGA" })).unwrap().iso, "NGA");
+        assert!(row(json!({ "iso": { "code": 566 } })).is_err());
+    }
+
+    #[test]
+    fn or_default_keeps_the_rest_of_the_document() {
+        #[derive(Deserialize)]
+        struct Settings {
+            #[serde(default, deserialize_with = "or_default")]
+            page_size: u32,
+            #[serde(default, deserialize_with = "or_default")]
+            countries: Vec<String>,
+        }
+
+        let parsed: Settings =
+            serde_json::from_value(json!({ "page_size": "lots", "countries": ["Mali"] })).unwrap();
+        assert_eq!(parsed.page_size, 0);
+        assert_eq!(parsed.countries, vec!["Mali".to_string()]);
+    }
+}
@@ -1,8 +1,19 @@
 pub mod acled;
 pub mod app;
+pub mod event_field;
+mod lenient;
+pub mod preferences;
+pub mod profile;
+pub mod saved_query;
+pub mod symbology;
 pub mod user;
 
 // Re-export commonly used types
 pub use acled::*;
 pub use app::*;
+pub use event_field::*;
+pub use preferences::*;
+pub use profile::*;
+pub use saved_query::*;
+pub use symbology::*;
 pub use user::*; 
\ No newline at end of file
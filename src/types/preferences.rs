@@ -0,0 +1,262 @@
+use super::lenient;
+use super::{AcledParams, Basemap, DashboardView, MarkerStyle, Symbology, Theme};
+use crate::errors::{AppError, AppResult, IntoAppError};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Version written with stored preferences. Bump it together with a new
+/// entry in [`MIGRATIONS`] whenever a stored field changes shape or meaning;
+/// newly added fields need no migration and start at their defaults.
+pub const PREFERENCES_VERSION: u32 = 1;
+
+/// Upgrades the stored JSON one version at a time: entry `i` turns a
+/// version `i + 1` object into version `i + 2`.
+const MIGRATIONS: &[fn(&mut Map<String, Value>)] = &[];
+
+const _: () = assert!(MIGRATIONS.len() as u32 + 1 == PREFERENCES_VERSION);
+
+/// Where the map was last left.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct MapView {
+    pub lat: f64,
+    pub lng: f64,
+    pub zoom: f64,
+}
+
+/// Settings kept across sessions. A field that fails to parse falls back to
+/// its default rather than discarding everything else.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct UserPreferences {
+    pub version: u32,
+    #[serde(default, deserialize_with = "lenient::or_default")]
+    pub theme: Theme,
+    /// View the dashboard opens in.
+    #[serde(default, deserialize_with = "lenient::or_default")]
+    pub default_view: DashboardView,
+    #[serde(default, deserialize_with = "lenient::or_default")]
+    pub basemap: Basemap,
+    /// MBTiles file or tile directory behind [`Basemap::Offline`].
+    #[serde(default, deserialize_with = "lenient::or_default")]
+    pub offline_tiles: Option<String>,
+    #[serde(default, deserialize_with = "lenient::or_default")]
+    pub symbology: Symbology,
+    /// Parameters of the last fetch, restored into the query form.
+    #[serde(default, deserialize_with = "lenient::or_default")]
+    pub last_params: Option<AcledParams>,
+    #[serde(default, deserialize_with = "lenient::or_default")]
+    pub map_view: Option<MapView>,
+}
+
+impl Default for UserPreferences {
+    fn default() -> Self {
+        Self {
+            version: PREFERENCES_VERSION,
+            theme: Theme::default(),
+            default_view: DashboardView::default(),
+            basemap: Basemap::default(),
+            offline_tiles: None,
+            symbology: Symbology::default(),
+            last_params: None,
+            map_view: None,
+        }
+    }
+}
+
+pub enum PreferencesAction {
+    SetTheme(Theme),
+    SetDefaultView(DashboardView),
+    SetBasemap(Basemap),
+    SetOfflineTiles(Option<String>),
+    SetMarkerStyle(MarkerStyle),
+    SetEventColor { event_type: String, color: String },
+    ResetEventColors,
+    SetLastParams(AcledParams),
+    SetMapView(MapView),
+    ForgetLastParams,
+    ForgetMapView,
+}
+
+/// Runs the `migrations` that apply to the object's `version`, leaving it at
+/// the version after the last migration. Objects without a version are v1.
+fn migrate(
+    object: &mut Map<String, Value>,
+    migrations: &[fn(&mut Map<String, Value>)],
+) -> AppResult<()> {
+    let current = migrations.len() as u64 + 1;
+    let version = object
+        .get("version")
+        .and_then(Value::as_u64)
+        .unwrap_or(1)
+        .max(1);
+    if version > current {
+        return Err(AppError::validation(
+            "version",
+            format!(
+                "Preferences were written by a newer version (v{}); this app reads up to v{}",
+                version, current
+            ),
+        ));
+    }
+
+    for migrate in &migrations[version as usize - 1..] {
+        migrate(object);
+    }
+    object.insert("version".to_string(), current.into());
+    Ok(())
+}
+
+impl UserPreferences {
+    /// Reads preferences written by this or any earlier version, migrating
+    /// them to the current layout.
+    pub fn from_json(json: &str) -> AppResult<Self> {
+        let mut object: Map<String, Value> = serde_json::from_str(json).into_app_error()?;
+        migrate(&mut object, MIGRATIONS)?;
+        serde_json::from_value(Value::Object(object)).into_app_error()
+    }
+
+    pub fn to_json(&self) -> AppResult<String> {
+        serde_json::to_string(self).into_app_error()
+    }
+
+    pub fn apply(&mut self, action: PreferencesAction) {
+        match action {
+            PreferencesAction::SetTheme(theme) => self.theme = theme,
+            PreferencesAction::SetDefaultView(view) => self.default_view = view,
+            PreferencesAction::SetBasemap(basemap) => self.basemap = basemap,
+            PreferencesAction::SetOfflineTiles(path) => self.offline_tiles = path,
+            PreferencesAction::SetMarkerStyle(style) => self.symbology.style = style,
+            PreferencesAction::SetEventColor { event_type, color } => {
+                self.symbology.set_color(&event_type, &color)
+            }
+            PreferencesAction::ResetEventColors => self.symbology.colors.clear(),
+            PreferencesAction::SetLastParams(params) => self.last_params = Some(params),
+            PreferencesAction::SetMapView(view) => self.map_view = Some(view),
+            PreferencesAction::ForgetLastParams => self.last_params = None,
+            PreferencesAction::ForgetMapView => self.map_view = None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn object(value: Value) -> Map<String, Value> {
+        match value {
+            Value::Object(object) => object,
+            _ => unreachable!(),
+        }
+    }
+
+    // v1 -> v2 renames `colour` to `color`; v2 -> v3 wraps `zoom` in a view
+    fn rename_colour(object: &mut Map<String, Value>) {
+        if let Some(colour) = object.remove("colour") {
+            object.insert("color".to_string(), colour);
+        }
+    }
+
+    fn nest_zoom(object: &mut Map<String, Value>) {
+        if let Some(zoom) = object.remove("zoom") {
+            object.insert("view".to_string(), json!({ "zoom": zoom }));
+        }
+    }
+
+    const TEST_MIGRATIONS: &[fn(&mut Map<String, Value>)] = &[rename_colour, nest_zoom];
+
+    #[test]
+    fn migrate_runs_every_step_from_v1() {
+        let mut stored = object(json!({ "version": 1, "colour": "#ff0000", "zoom": 4 }));
+        migrate(&mut stored, TEST_MIGRATIONS).unwrap();
+        assert_eq!(
+            Value::Object(stored),
+            json!({ "version": 3, "color": "#ff0000", "view": { "zoom": 4 } })
+        );
+    }
+
+    #[test]
+    fn migrate_skips_steps_already_applied() {
+        // A v2 object already has `color`; a stray `colour` must survive
+        let mut stored = object(json!({ "version": 2, "colour": "kept", "zoom": 6 }));
+        migrate(&mut stored, TEST_MIGRATIONS).unwrap();
+        assert_eq!(
+            Value::Object(stored),
+            json!({ "version": 3, "colour": "kept", "view": { "zoom": 6 } })
+        );
+
+        let mut current = object(json!({ "version": 3, "zoom": 2 }));
+        migrate(&mut current, TEST_MIGRATIONS).unwrap();
+        assert_eq!(current.get("zoom"), Some(&json!(2)));
+    }
+
+    #[test]
+    fn migrate_treats_missing_or_bad_versions_as_v1() {
+        for version in [Value::Null, json!(0), json!("2")] {
+            let mut stored = object(json!({ "version": version, "colour": "blue" }));
+            migrate(&mut stored, TEST_MIGRATIONS).unwrap();
+            assert_eq!(stored.get("color"), Some(&json!("blue")));
+        }
+
+        let mut unversioned = object(json!({ "colour": "blue" }));
+        migrate(&mut unversioned, TEST_MIGRATIONS).unwrap();
+        assert_eq!(unversioned.get("version"), Some(&json!(3)));
+    }
+
+    #[test]
+    fn migrate_rejects_newer_versions() {
+        let mut stored = object(json!({ "version": 4 }));
+        assert!(matches!(
+            migrate(&mut stored, TEST_MIGRATIONS),
+            Err(AppError::Validation { field, .. }) if field == "version"
+        ));
+    }
+
+    #[test]
+    fn from_json_round_trips() {
+        let mut preferences = UserPreferences::default();
+        preferences.apply(PreferencesAction::SetTheme(Theme::Dark));
+        preferences.apply(PreferencesAction::SetBasemap(Basemap::Terrain));
+        preferences.apply(PreferencesAction::SetMapView(MapView {
+            lat: 12.5,
+            lng: -3.25,
+            zoom: 6.0,
+        }));
+
+        let json = preferences.to_json().unwrap();
+        assert_eq!(UserPreferences::from_json(&json).unwrap(), preferences);
+    }
+
+    #[test]
+    fn from_json_keeps_good_fields_next_to_bad_ones() {
+        let json = r#"{
+            "version": 1,
+            "theme": "neon",
+            "default_view": "table",
+            "basemap": 7,
+            "map_view": { "lat": 9.0, "lng": 8.0, "zoom": 5.0 }
+        }"#;
+
+        let preferences = UserPreferences::from_json(json).unwrap();
+        assert_eq!(preferences.theme, Theme::default());
+        assert_eq!(preferences.default_view, DashboardView::Table);
+        assert_eq!(preferences.basemap, Basemap::default());
+        assert_eq!(
+            preferences.map_view,
+            Some(MapView {
+                lat: 9.0,
+                lng: 8.0,
+                zoom: 5.0
+            })
+        );
+        assert_eq!(preferences.version, PREFERENCES_VERSION);
+    }
+
+    #[test]
+    fn from_json_rejects_non_objects_and_newer_versions() {
+        assert!(UserPreferences::from_json("[]").is_err());
+        assert!(UserPreferences::from_json("not json").is_err());
+
+        let newer = format!(r#"{{ "version": {} }}"#, PREFERENCES_VERSION + 1);
+        assert!(UserPreferences::from_json(&newer).is_err());
+    }
+}
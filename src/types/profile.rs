@@ -0,0 +1,213 @@
+use super::UserData;
+use crate::errors::{AppError, AppResult, IntoAppError};
+use serde::{Deserialize, Serialize};
+
+/// A named set of ACLED credentials.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Profile {
+    pub name: String,
+    pub user_data: UserData,
+}
+
+/// Credentials as saved before OAuth support, without a `method` tag.
+#[derive(Deserialize)]
+struct LegacyUserData {
+    email: String,
+    api_key: String,
+}
+
+/// Every saved profile, kept sorted by name, and which one opens on startup.
+/// This is the credential blob kept in the secret store (or local storage).
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+pub struct ProfileStore {
+    pub profiles: Vec<Profile>,
+    #[serde(default)]
+    pub default: Option<String>,
+}
+
+impl ProfileStore {
+    /// Reads a stored profile list, or the single set of credentials saved by
+    /// versions without profiles.
+    pub fn from_json(json: &str) -> AppResult<Self> {
+        if let Ok(store) = serde_json::from_str::<ProfileStore>(json) {
+            return Ok(store);
+        }
+
+        if let Ok(legacy) = serde_json::from_str::<LegacyUserData>(json) {
+            return Ok(Self::from_user_data(UserData::new(
+                legacy.email,
+                legacy.api_key,
+            )));
+        }
+
+        let user_data: UserData = serde_json::from_str(json).into_app_error()?;
+        Ok(Self::from_user_data(user_data))
+    }
+
+    /// A store holding just `user_data`, as the default profile.
+    pub fn from_user_data(user_data: UserData) -> Self {
+        let mut store = Self::default();
+        store.add(Profile {
+            name: user_data.account().to_string(),
+            user_data,
+        });
+        store
+    }
+
+    pub fn to_json(&self) -> AppResult<String> {
+        serde_json::to_string(self).into_app_error()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|profile| profile.name == name)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.profiles.is_empty()
+    }
+
+    pub fn default_profile(&self) -> Option<&Profile> {
+        self.default.as_deref().and_then(|name| self.get(name))
+    }
+
+    pub fn is_default(&self, name: &str) -> bool {
+        self.default.as_deref() == Some(name)
+    }
+
+    /// Stores `profile`, replacing any profile of the same name. The first
+    /// profile saved becomes the default.
+    pub fn add(&mut self, profile: Profile) {
+        if self.default_profile().is_none() {
+            self.default = Some(profile.name.clone());
+        }
+        self.profiles
+            .retain(|existing| existing.name != profile.name);
+        self.profiles.push(profile);
+        self.sort();
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.profiles.retain(|profile| profile.name != name);
+        if self.is_default(name) {
+            self.default = None;
+        }
+    }
+
+    pub fn set_default(&mut self, name: &str) -> AppResult<()> {
+        if self.get(name).is_none() {
+            return Err(AppError::validation(
+                "profile",
+                format!("No profile named \"{}\"", name),
+            ));
+        }
+        self.default = Some(name.to_string());
+        Ok(())
+    }
+
+    /// Replaces the credentials of every profile for the same account and
+    /// method, e.g. after an OAuth token refresh. Returns whether any changed.
+    pub fn update_credentials(&mut self, user_data: &UserData) -> bool {
+        let mut changed = false;
+        for profile in &mut self.profiles {
+            if profile.user_data.account() == user_data.account()
+                && profile.user_data.method_label() == user_data.method_label()
+                && profile.user_data != *user_data
+            {
+                profile.user_data = user_data.clone();
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    fn sort(&mut self) {
+        self.profiles
+            .sort_by_key(|profile| profile.name.to_lowercase());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(name: &str, email: &str) -> Profile {
+        Profile {
+            name: name.to_string(),
+            user_data: UserData::new(email.to_string(), "key".to_string()),
+        }
+    }
+
+    #[test]
+    fn adding_a_name_again_replaces_the_profile() {
+        let mut store = ProfileStore::default();
+        store.add(profile("work", "old@example.org"));
+        store.add(profile("Home", "home@example.org"));
+        store.add(profile("work", "new@example.org"));
+
+        assert_eq!(store.profiles.len(), 2);
+        assert_eq!(store.profiles[0].name, "Home");
+        assert_eq!(
+            store.get("work").unwrap().user_data.account(),
+            "new@example.org"
+        );
+        assert!(store.is_default("work"));
+    }
+
+    #[test]
+    fn removing_the_default_clears_it_until_the_next_add() {
+        let mut store = ProfileStore::default();
+        store.add(profile("work", "work@example.org"));
+        store.add(profile("home", "home@example.org"));
+
+        store.remove("work");
+        assert_eq!(store.default, None);
+        assert!(store.default_profile().is_none());
+        assert!(store.get("home").is_some());
+
+        store.add(profile("field", "field@example.org"));
+        assert!(store.is_default("field"));
+    }
+
+    #[test]
+    fn set_default_rejects_unknown_names() {
+        let mut store = ProfileStore::default();
+        store.add(profile("work", "work@example.org"));
+        store.add(profile("home", "home@example.org"));
+
+        assert!(matches!(
+            store.set_default("missing"),
+            Err(AppError::Validation { .. })
+        ));
+        assert!(store.is_default("work"));
+
+        assert!(store.set_default("home").is_ok());
+        assert!(store.is_default("home"));
+    }
+
+    #[test]
+    fn legacy_credentials_become_the_default_profile() {
+        let expected = UserData::new("analyst@example.org".to_string(), "k3y".to_string());
+
+        for json in [
+            r#"{"email":"analyst@example.org","api_key":"k3y"}"#,
+            r#"{"method":"api_key","email":"analyst@example.org","api_key":"k3y"}"#,
+        ] {
+            let store = ProfileStore::from_json(json).unwrap();
+            assert_eq!(store.profiles.len(), 1);
+            let default = store.default_profile().unwrap();
+            assert_eq!(default.name, "analyst@example.org");
+            assert_eq!(default.user_data, expected);
+        }
+    }
+
+    #[test]
+    fn stores_round_trip_through_json() {
+        let mut store = ProfileStore::default();
+        store.add(profile("work", "work@example.org"));
+        store.add(profile("home", "home@example.org"));
+
+        let json = store.to_json().unwrap();
+        assert_eq!(ProfileStore::from_json(&json).unwrap(), store);
+        assert!(ProfileStore::from_json("not json").is_err());
+    }
+}
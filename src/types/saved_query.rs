@@ -0,0 +1,237 @@
+use super::AcledParams;
+use crate::errors::{AppError, AppResult, IntoAppError};
+use serde::{Deserialize, Serialize};
+
+/// Version written to stored and exported collections; files from a newer
+/// version of the app are rejected rather than half-read.
+pub const SAVED_QUERIES_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct SavedQuery {
+    pub name: String,
+    pub params: AcledParams,
+}
+
+/// Named query presets, kept sorted by name. This is both the
+/// local storage format and the JSON file teammates exchange.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct SavedQueries {
+    pub version: u32,
+    pub queries: Vec<SavedQuery>,
+}
+
+impl Default for SavedQueries {
+    fn default() -> Self {
+        Self {
+            version: SAVED_QUERIES_VERSION,
+            queries: Vec::new(),
+        }
+    }
+}
+
+impl SavedQueries {
+    pub fn from_json(json: &str) -> AppResult<Self> {
+        let saved: SavedQueries = serde_json::from_str(json).into_app_error()?;
+
+        if saved.version > SAVED_QUERIES_VERSION {
+            return Err(AppError::validation(
+                "version",
+                format!(
+                    "Saved queries were written by a newer version (v{}); this app reads up to v{}",
+                    saved.version, SAVED_QUERIES_VERSION
+                ),
+            ));
+        }
+
+        Ok(Self {
+            version: SAVED_QUERIES_VERSION,
+            ..saved
+        })
+    }
+
+    pub fn to_json(&self) -> AppResult<String> {
+        serde_json::to_string_pretty(self).into_app_error()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&SavedQuery> {
+        self.queries.iter().find(|query| query.name == name)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queries.is_empty()
+    }
+
+    /// Stores `params` under `name`, replacing any query of the same name.
+    pub fn save(&mut self, name: &str, params: AcledParams) -> AppResult<()> {
+        let name = Self::validate_name(name)?;
+        self.queries.retain(|query| query.name != name);
+        self.queries.push(SavedQuery { name, params });
+        self.sort();
+        Ok(())
+    }
+
+    pub fn rename(&mut self, from: &str, to: &str) -> AppResult<()> {
+        let to = Self::validate_name(to)?;
+        if to != from && self.get(&to).is_some() {
+            return Err(AppError::validation(
+                "name",
+                format!("A saved query named \"{}\" already exists", to),
+            ));
+        }
+
+        let query = self
+            .queries
+            .iter_mut()
+            .find(|query| query.name == from)
+            .ok_or_else(|| {
+                AppError::validation("name", format!("No saved query named \"{}\"", from))
+            })?;
+        query.name = to;
+        self.sort();
+        Ok(())
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.queries.retain(|query| query.name != name);
+    }
+
+    /// Adds every query from `other`; imported queries replace local ones
+    /// with the same name. Returns how many were imported.
+    pub fn merge(&mut self, other: SavedQueries) -> usize {
+        let count = other.queries.len();
+        for query in other.queries {
+            self.queries.retain(|existing| existing.name != query.name);
+            self.queries.push(query);
+        }
+        self.sort();
+        count
+    }
+
+    fn validate_name(name: &str) -> AppResult<String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(AppError::validation("name", "Query name is required"));
+        }
+        Ok(name.to_string())
+    }
+
+    fn sort(&mut self) {
+        self.queries.sort_by_key(|query| query.name.to_lowercase());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(country: &str) -> AcledParams {
+        AcledParams {
+            countries: vec![country.to_string()],
+            ..AcledParams::default()
+        }
+    }
+
+    fn names(saved: &SavedQueries) -> Vec<&str> {
+        saved
+            .queries
+            .iter()
+            .map(|query| query.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn save_trims_replaces_and_sorts() {
+        let mut saved = SavedQueries::default();
+        saved.save("  sahel ", params("Mali")).unwrap();
+        saved.save("Levant", params("Lebanon")).unwrap();
+        saved.save("sahel", params("Niger")).unwrap();
+
+        assert_eq!(names(&saved), vec!["Levant", "sahel"]);
+        assert_eq!(saved.get("sahel").unwrap().params.countries, vec!["Niger"]);
+        assert!(matches!(
+            saved.save("   ", params("Chad")),
+            Err(AppError::Validation { .. })
+        ));
+    }
+
+    #[test]
+    fn rename_refuses_existing_names() {
+        let mut saved = SavedQueries::default();
+        saved.save("Sahel", params("Mali")).unwrap();
+        saved.save("Levant", params("Lebanon")).unwrap();
+
+        assert!(saved.rename("Sahel", "Levant").is_err());
+        assert!(saved.rename("Horn", "East").is_err());
+        saved.rename("Sahel", "Sahel").unwrap();
+        saved.rename("Sahel", "Central Sahel").unwrap();
+        assert_eq!(names(&saved), vec!["Central Sahel", "Levant"]);
+    }
+
+    #[test]
+    fn merge_replaces_same_names_and_counts_imports() {
+        let mut local = SavedQueries::default();
+        local.save("Sahel", params("Mali")).unwrap();
+        local.save("Levant", params("Lebanon")).unwrap();
+
+        let mut imported = SavedQueries::default();
+        imported.save("Sahel", params("Burkina Faso")).unwrap();
+        imported.save("Horn", params("Somalia")).unwrap();
+
+        assert_eq!(local.merge(imported), 2);
+        assert_eq!(names(&local), vec!["Horn", "Levant", "Sahel"]);
+        assert_eq!(
+            local.get("Sahel").unwrap().params.countries,
+            vec!["Burkina Faso"]
+        );
+        assert_eq!(
+            local.get("Levant").unwrap().params.countries,
+            vec!["Lebanon"]
+        );
+    }
+
+    #[test]
+    fn from_json_round_trips() {
+        let mut saved = SavedQueries::default();
+        saved.save("Sahel", params("Mali")).unwrap();
+
+        let json = saved.to_json().unwrap();
+        assert_eq!(SavedQueries::from_json(&json).unwrap(), saved);
+    }
+
+    #[test]
+    fn from_json_reads_files_without_newer_fields() {
+        let json = r#"{
+            "version": 1,
+            "queries": [{
+                "name": "Sahel",
+                "params": {
+                    "start_date": "2024-01-01",
+                    "end_date": "2024-06-30",
+                    "countries": ["Mali"],
+                    "event_types": [],
+                    "limit": 500
+                }
+            }]
+        }"#;
+
+        let saved = SavedQueries::from_json(json).unwrap();
+        let query = saved.get("Sahel").unwrap();
+        assert_eq!(query.params.limit, 500);
+        assert!(!query.params.fetch_all_pages);
+        assert_eq!(query.params.area, None);
+    }
+
+    #[test]
+    fn from_json_rejects_newer_versions_and_garbage() {
+        let newer = format!(
+            r#"{{ "version": {}, "queries": [] }}"#,
+            SAVED_QUERIES_VERSION + 1
+        );
+        assert!(matches!(
+            SavedQueries::from_json(&newer),
+            Err(AppError::Validation { field, .. }) if field == "version"
+        ));
+        assert!(SavedQueries::from_json("{ \"queries\": [] }").is_err());
+        assert!(SavedQueries::from_json("not json").is_err());
+    }
+}
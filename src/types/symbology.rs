@@ -0,0 +1,148 @@
+use crate::config::{
+    EVENT_TYPE_COLORS, FALLBACK_EVENT_COLOR, MARKER_MAX_RADIUS_PX, MARKER_MIN_RADIUS_PX,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MarkerStyle {
+    /// Vector circles, scaled by fatalities.
+    #[default]
+    Circle,
+    /// SVG map pins, scaled by fatalities.
+    Pin,
+}
+
+impl MarkerStyle {
+    pub const ALL: [MarkerStyle; 2] = [MarkerStyle::Circle, MarkerStyle::Pin];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            MarkerStyle::Circle => "Circles",
+            MarkerStyle::Pin => "Pins",
+        }
+    }
+}
+
+/// How individual events are drawn on the map: color by event type, size by
+/// fatalities and opacity by how precisely the location is known.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+pub struct Symbology {
+    #[serde(default)]
+    pub style: MarkerStyle,
+    /// Colors chosen by the user, by event type; other types keep
+    /// [`EVENT_TYPE_COLORS`].
+    #[serde(default)]
+    pub colors: BTreeMap<String, String>,
+}
+
+impl Symbology {
+    pub fn default_color(event_type: &str) -> &'static str {
+        EVENT_TYPE_COLORS
+            .iter()
+            .find(|(name, _)| *name == event_type)
+            .map_or(FALLBACK_EVENT_COLOR, |(_, color)| color)
+    }
+
+    /// Always a `#rrggbb` color; stored overrides end up in marker HTML, so
+    /// anything else falls back to the default.
+    pub fn color(&self, event_type: &str) -> &str {
+        self.colors
+            .get(event_type)
+            .map(String::as_str)
+            .filter(|color| is_hex_color(color))
+            .unwrap_or_else(|| Self::default_color(event_type))
+    }
+
+    pub fn set_color(&mut self, event_type: &str, color: &str) {
+        if color.eq_ignore_ascii_case(Self::default_color(event_type)) {
+            self.colors.remove(event_type);
+        } else if is_hex_color(color) {
+            self.colors
+                .insert(event_type.to_string(), color.to_ascii_lowercase());
+        }
+    }
+}
+
+fn is_hex_color(color: &str) -> bool {
+    color.len() == 7
+        && color.starts_with('#')
+        && color[1..].bytes().all(|byte| byte.is_ascii_hexdigit())
+}
+
+/// Marker radius in pixels. Area grows with fatalities, up to a cap, so
+/// deadly events stand out without hiding their neighbours.
+pub fn fatality_radius(fatalities: u32) -> f64 {
+    (MARKER_MIN_RADIUS_PX + 1.5 * f64::from(fatalities).sqrt()).min(MARKER_MAX_RADIUS_PX)
+}
+
+/// Marker opacity by ACLED's `geo_precision`: exact locations are solid,
+/// region-level ones faint.
+pub fn precision_opacity(geo_precision: Option<u8>) -> f64 {
+    match geo_precision {
+        Some(1) => 0.9,
+        Some(3) => 0.4,
+        _ => 0.65,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_color_ignores_invalid_hex() {
+        let mut symbology = Symbology::default();
+        for color in [
+            "red", "#fff", "#12345g", "#1234567", "123456#", "#12\"<>a", "",
+        ] {
+            symbology.set_color("Battles", color);
+        }
+        assert!(symbology.colors.is_empty());
+
+        symbology.set_color("Battles", "#00AAFF");
+        assert_eq!(symbology.color("Battles"), "#00aaff");
+    }
+
+    #[test]
+    fn default_color_clears_the_override() {
+        let mut symbology = Symbology::default();
+        symbology.set_color("Battles", "#00aaff");
+        symbology.set_color("Battles", "#E41A1C");
+
+        assert!(symbology.colors.is_empty());
+        assert_eq!(symbology.color("Battles"), "#e41a1c");
+    }
+
+    #[test]
+    fn unknown_types_and_bad_stored_colors_fall_back() {
+        let mut symbology = Symbology::default();
+        assert_eq!(symbology.color("Something new"), FALLBACK_EVENT_COLOR);
+        assert_eq!(Symbology::default_color(""), FALLBACK_EVENT_COLOR);
+
+        // Hand-edited preferences bypass set_color
+        symbology
+            .colors
+            .insert("Riots".to_string(), "red' onmouseover='x".to_string());
+        assert_eq!(symbology.color("Riots"), Symbology::default_color("Riots"));
+    }
+
+    #[test]
+    fn radius_is_clamped() {
+        assert_eq!(fatality_radius(0), MARKER_MIN_RADIUS_PX);
+        assert!(fatality_radius(1) > MARKER_MIN_RADIUS_PX);
+        assert!(fatality_radius(25) < fatality_radius(100));
+        assert_eq!(fatality_radius(10_000), MARKER_MAX_RADIUS_PX);
+        assert_eq!(fatality_radius(u32::MAX), MARKER_MAX_RADIUS_PX);
+    }
+
+    #[test]
+    fn opacity_follows_geo_precision() {
+        assert_eq!(precision_opacity(Some(1)), 0.9);
+        assert_eq!(precision_opacity(Some(2)), 0.65);
+        assert_eq!(precision_opacity(Some(3)), 0.4);
+        assert_eq!(precision_opacity(Some(7)), 0.65);
+        assert_eq!(precision_opacity(None), 0.65);
+    }
+}
@@ -1,17 +1,61 @@
 use serde::{Deserialize, Serialize};
 
+/// Tokens from ACLED's OAuth password grant.
 #[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
-pub struct UserData {
-    pub email: String,
-    pub api_key: String,
+pub struct OAuthTokens {
+    pub access_token: String,
+    pub refresh_token: String,
+    /// Expiry of `access_token`, in milliseconds since the epoch.
+    pub expires_at: f64,
+}
+
+impl OAuthTokens {
+    /// Whether the access token expires within `margin_ms` of `now_ms`.
+    pub fn expires_within(&self, now_ms: f64, margin_ms: f64) -> bool {
+        now_ms + margin_ms >= self.expires_at
+    }
+}
+
+/// How the user authenticates with ACLED.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum UserData {
+    /// Legacy access key, sent as `key=`/`email=` query parameters.
+    ApiKey { email: String, api_key: String },
+    /// OAuth username/password login; only the tokens are kept, never the password.
+    #[serde(rename = "oauth")]
+    OAuth {
+        username: String,
+        tokens: OAuthTokens,
+    },
 }
 
 impl UserData {
     pub fn new(email: String, api_key: String) -> Self {
-        Self { email, api_key }
+        Self::ApiKey { email, api_key }
     }
 
     pub fn is_valid(&self) -> bool {
-        !self.email.is_empty() && !self.api_key.is_empty()
+        match self {
+            UserData::ApiKey { email, api_key } => !email.is_empty() && !api_key.is_empty(),
+            UserData::OAuth { username, tokens } => {
+                !username.is_empty() && !tokens.access_token.is_empty()
+            }
+        }
+    }
+
+    /// The email or username the user logged in with.
+    pub fn account(&self) -> &str {
+        match self {
+            UserData::ApiKey { email, .. } => email,
+            UserData::OAuth { username, .. } => username,
+        }
+    }
+
+    pub fn method_label(&self) -> &'static str {
+        match self {
+            UserData::ApiKey { .. } => "API Key",
+            UserData::OAuth { .. } => "OAuth",
+        }
     }
 } 
\ No newline at end of file
@@ -0,0 +1,112 @@
+//! `YYYY-MM-DD` dates as day numbers (days since 1970-01-01), which keeps
+//! date arithmetic independent of the JS `Date` API.
+
+// Howard Hinnant's days_from_civil
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let year_of_era = year - era * 400;
+    let month_index = (month + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = if days >= 0 { days } else { days - 146_096 } / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Parses the leading `YYYY-MM-DD` of `text`, ignoring any time component.
+pub fn parse_day(text: &str) -> Option<i64> {
+    let mut parts = text.get(..10)?.split('-');
+    let year = parts.next()?.parse::<i64>().ok()?;
+    let month = parts.next()?.parse::<i64>().ok()?;
+    let day = parts.next()?.parse::<i64>().ok()?;
+
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    Some(days_from_civil(year, month, day))
+}
+
+pub fn format_day(days: i64) -> String {
+    let (year, month, day) = civil_from_days(days);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(text: &str) -> i64 {
+        parse_day(text).unwrap()
+    }
+
+    #[test]
+    fn epoch_is_day_zero() {
+        assert_eq!(day("1970-01-01"), 0);
+        assert_eq!(day("1969-12-31"), -1);
+        assert_eq!(format_day(0), "1970-01-01");
+    }
+
+    #[test]
+    fn days_roll_over_months_and_years() {
+        assert_eq!(format_day(day("2024-01-31") + 1), "2024-02-01");
+        assert_eq!(format_day(day("2024-04-30") + 1), "2024-05-01");
+        assert_eq!(format_day(day("2023-12-31") + 1), "2024-01-01");
+        assert_eq!(format_day(day("2024-01-01") - 1), "2023-12-31");
+        assert_eq!(day("2025-01-01") - day("2024-01-01"), 366);
+        assert_eq!(day("2024-01-01") - day("2023-01-01"), 365);
+    }
+
+    #[test]
+    fn leap_days() {
+        assert_eq!(format_day(day("2024-02-28") + 1), "2024-02-29");
+        assert_eq!(format_day(day("2024-02-29") + 1), "2024-03-01");
+        assert_eq!(format_day(day("2023-02-28") + 1), "2023-03-01");
+        // Centuries are leap years only when divisible by 400
+        assert_eq!(format_day(day("1900-02-28") + 1), "1900-03-01");
+        assert_eq!(format_day(day("2000-02-28") + 1), "2000-02-29");
+    }
+
+    #[test]
+    fn format_round_trips_every_day_of_a_leap_year() {
+        let start = day("2024-01-01");
+        for offset in 0..366 {
+            assert_eq!(day(&format_day(start + offset)), start + offset);
+        }
+        assert_eq!(format_day(start + 366), "2025-01-01");
+    }
+
+    #[test]
+    fn parse_ignores_time_and_rejects_malformed_dates() {
+        assert_eq!(day("2024-03-05T23:59:59Z"), day("2024-03-05"));
+        assert_eq!(day("2024-03-05 00:00"), day("2024-03-05"));
+
+        for text in [
+            "",
+            "2024-03",
+            "2024-13-01",
+            "2024-00-10",
+            "2024-01-32",
+            "03/05/2024",
+        ] {
+            assert_eq!(parse_day(text), None, "{:?}", text);
+        }
+    }
+}
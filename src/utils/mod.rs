@@ -0,0 +1,4 @@
+pub mod date;
+pub mod redact;
+pub mod retry;
+pub mod text;
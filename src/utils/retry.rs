@@ -0,0 +1,100 @@
+//! Delays between attempts of a failed request. Randomness and the clock are
+//! passed in so the arithmetic stays independent of the JS APIs.
+
+/// Exponential backoff with "equal jitter": half of the capped delay is fixed
+/// and the other half is scaled by `random` (in `0.0..1.0`), so concurrent
+/// clients spread out without any delay collapsing to zero. `attempt` is the
+/// 1-based number of the attempt that just failed.
+pub fn backoff_delay_ms(attempt: u32, base_ms: u32, max_ms: u32, random: f64) -> u32 {
+    let exponential = u64::from(base_ms) << attempt.saturating_sub(1).min(16);
+    let capped = exponential.min(u64::from(max_ms)) as f64;
+    (capped / 2.0 + capped / 2.0 * random.clamp(0.0, 1.0)) as u32
+}
+
+/// Parses a `Retry-After` header, given either as seconds or as an HTTP date
+/// (parsed by `parse_date` into milliseconds since the epoch).
+pub fn retry_after_ms(value: &str, now_ms: f64, parse_date: impl Fn(&str) -> f64) -> Option<u32> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u32>() {
+        return Some(seconds.saturating_mul(1000));
+    }
+
+    let at = parse_date(value);
+    (!at.is_nan()).then(|| (at - now_ms).clamp(0.0, f64::from(u32::MAX)) as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_per_attempt() {
+        assert_eq!(backoff_delay_ms(1, 1_000, 30_000, 1.0), 1_000);
+        assert_eq!(backoff_delay_ms(2, 1_000, 30_000, 1.0), 2_000);
+        assert_eq!(backoff_delay_ms(3, 1_000, 30_000, 1.0), 4_000);
+        assert_eq!(backoff_delay_ms(5, 1_000, 30_000, 1.0), 16_000);
+    }
+
+    #[test]
+    fn backoff_jitter_keeps_half_the_delay() {
+        assert_eq!(backoff_delay_ms(3, 1_000, 30_000, 0.0), 2_000);
+        assert_eq!(backoff_delay_ms(3, 1_000, 30_000, 0.5), 3_000);
+        // Out-of-range randomness is clamped
+        assert_eq!(backoff_delay_ms(3, 1_000, 30_000, -1.0), 2_000);
+        assert_eq!(backoff_delay_ms(3, 1_000, 30_000, 7.0), 4_000);
+    }
+
+    #[test]
+    fn backoff_is_capped() {
+        assert_eq!(backoff_delay_ms(6, 1_000, 30_000, 1.0), 30_000);
+        assert_eq!(backoff_delay_ms(6, 1_000, 30_000, 0.0), 15_000);
+        // Large attempt numbers neither overflow nor exceed the cap
+        assert_eq!(backoff_delay_ms(40, 1_000, 30_000, 1.0), 30_000);
+        assert_eq!(
+            backoff_delay_ms(u32::MAX, u32::MAX, u32::MAX, 1.0),
+            u32::MAX
+        );
+        // Attempt 0 behaves like the first attempt
+        assert_eq!(backoff_delay_ms(0, 1_000, 30_000, 1.0), 1_000);
+    }
+
+    fn no_date(_: &str) -> f64 {
+        f64::NAN
+    }
+
+    #[test]
+    fn retry_after_reads_seconds() {
+        assert_eq!(retry_after_ms("120", 0.0, no_date), Some(120_000));
+        assert_eq!(retry_after_ms(" 0 ", 0.0, no_date), Some(0));
+        assert_eq!(retry_after_ms("5000000", 0.0, no_date), Some(u32::MAX));
+    }
+
+    #[test]
+    fn retry_after_reads_dates() {
+        let now = 1_700_000_000_000.0;
+        let date = |value: &str| {
+            if value == "Wed, 15 Nov 2023 00:00:00 GMT" {
+                now + 30_000.0
+            } else {
+                f64::NAN
+            }
+        };
+
+        assert_eq!(
+            retry_after_ms("Wed, 15 Nov 2023 00:00:00 GMT", now, date),
+            Some(30_000)
+        );
+        // Dates in the past mean retry now
+        assert_eq!(
+            retry_after_ms("Wed, 15 Nov 2023 00:00:00 GMT", now + 60_000.0, date),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn retry_after_rejects_garbage() {
+        assert_eq!(retry_after_ms("soon", 0.0, no_date), None);
+        assert_eq!(retry_after_ms("-5", 0.0, no_date), None);
+        assert_eq!(retry_after_ms("", 0.0, no_date), None);
+    }
+}
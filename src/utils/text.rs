@@ -0,0 +1,145 @@
+//! Case- and diacritic-insensitive text matching for free-text search.
+//!
+//! Text is folded one char at a time (canonical decomposition, combining marks
+//! dropped, lowercased) so matches found in folded text can be mapped back to
+//! byte ranges of the original string for highlighting.
+
+use std::ops::Range;
+use unicode_normalization::char::{decompose_canonical, is_combining_mark};
+
+fn fold_char(c: char, mut emit: impl FnMut(char)) {
+    decompose_canonical(c, |decomposed| {
+        if !is_combining_mark(decomposed) {
+            decomposed.to_lowercase().for_each(&mut emit);
+        }
+    });
+}
+
+pub fn fold(text: &str) -> String {
+    let mut folded = String::with_capacity(text.len());
+    for c in text.chars() {
+        fold_char(c, |f| folded.push(f));
+    }
+    folded
+}
+
+/// Escapes text for interpolation into HTML strings such as Leaflet popups,
+/// in element content and quoted attribute values alike.
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Folded, whitespace-separated search terms; every term has to match.
+pub fn search_tokens(query: &str) -> Vec<String> {
+    fold(query).split_whitespace().map(str::to_string).collect()
+}
+
+/// Byte ranges of `text` matching any of `tokens`, sorted and merged.
+pub fn match_ranges(text: &str, tokens: &[String]) -> Vec<Range<usize>> {
+    let mut folded: Vec<(char, Range<usize>)> = Vec::with_capacity(text.len());
+    for (start, c) in text.char_indices() {
+        let source = start..start + c.len_utf8();
+        fold_char(c, |f| folded.push((f, source.clone())));
+    }
+    let chars: Vec<char> = folded.iter().map(|(c, _)| *c).collect();
+
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    for token in tokens {
+        let needle: Vec<char> = token.chars().collect();
+        if needle.is_empty() || needle.len() > chars.len() {
+            continue;
+        }
+        for (start, window) in chars.windows(needle.len()).enumerate() {
+            if window == needle.as_slice() {
+                let end = start + needle.len() - 1;
+                ranges.push(folded[start].1.start..folded[end].1.end);
+            }
+        }
+    }
+
+    ranges.sort_by_key(|range| range.start);
+    let mut merged: Vec<Range<usize>> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(query: &str) -> Vec<String> {
+        search_tokens(query)
+    }
+
+    #[test]
+    fn fold_drops_case_and_diacritics() {
+        assert_eq!(fold("Côte d'Ivoire"), "cote d'ivoire");
+        assert_eq!(fold("SÃO TOMÉ"), "sao tome");
+        assert_eq!(fold("Ağrı"), "agrı");
+        assert_eq!(fold(""), "");
+    }
+
+    #[test]
+    fn escape_html_escapes_markup_and_quotes() {
+        assert_eq!(
+            escape_html(r#"<img src=x onerror="alert('hi')"> & more"#),
+            "&lt;img src=x onerror=&quot;alert(&#39;hi&#39;)&quot;&gt; &amp; more"
+        );
+        assert_eq!(escape_html("Côte d'Ivoire"), "Côte d&#39;Ivoire");
+        assert_eq!(escape_html("plain"), "plain");
+    }
+
+    #[test]
+    fn search_tokens_split_on_whitespace() {
+        assert_eq!(tokens("  Boko   HARAM "), vec!["boko", "haram"]);
+        assert!(tokens("   ").is_empty());
+    }
+
+    #[test]
+    fn match_ranges_map_back_to_original_bytes() {
+        let text = "São Tomé";
+        let ranges = match_ranges(text, &tokens("sao tome"));
+        assert_eq!(ranges, vec![0..4, 5..10]);
+        assert_eq!(&text[ranges[0].clone()], "São");
+        assert_eq!(&text[ranges[1].clone()], "Tomé");
+    }
+
+    #[test]
+    fn match_ranges_find_every_occurrence() {
+        assert_eq!(
+            match_ranges("Protest in Abuja; protesters dispersed", &tokens("protest")),
+            vec![0..7, 18..25]
+        );
+    }
+
+    #[test]
+    fn match_ranges_merge_overlaps() {
+        // "abuja" and "juba" overlap in "Abujuba"
+        assert_eq!(match_ranges("Abujuba", &tokens("abuj juba")), vec![0..7]);
+        // Adjacent matches merge as well
+        assert_eq!(match_ranges("foobar", &tokens("foo bar")), vec![0..6]);
+    }
+
+    #[test]
+    fn match_ranges_skip_empty_and_long_tokens() {
+        assert!(match_ranges("Mali", &[String::new()]).is_empty());
+        assert!(match_ranges("Mali", &tokens("Malinke")).is_empty());
+        assert!(match_ranges("", &tokens("mali")).is_empty());
+    }
+}